@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+
+use truetype::Tag;
+
+use crate::font::{checksum, compose, sum};
+use crate::{Font, Result, Table};
+
+/// A builder of fonts.
+///
+/// The builder collects tables and writes them out as a single font with an offset table whose
+/// search parameters, table checksums, padding, and `head.checkSumAdjustment` are computed.
+#[derive(Clone, Debug)]
+pub struct Builder {
+    version: u32,
+    tables: BTreeMap<Tag, Vec<u8>>,
+}
+
+impl Builder {
+    /// Create a builder for a font of a given version (sfntVersion).
+    #[inline]
+    pub fn new(version: u32) -> Self {
+        Self {
+            version,
+            tables: Default::default(),
+        }
+    }
+
+    /// Create a builder populated with the version and tables of another font.
+    pub fn from_font<T>(font: &Font, tape: &mut T) -> Result<Self>
+    where
+        T: crate::tape::Read,
+    {
+        Ok(Self {
+            version: font.offsets.header.version,
+            tables: font.take_tables(tape)?.into_iter().collect(),
+        })
+    }
+
+    /// Add a table, replacing the one with the same tag if any.
+    pub fn add<T>(&mut self, table: &T) -> Result<&mut Self>
+    where
+        T: Table + crate::value::Write,
+    {
+        let mut data = vec![];
+        crate::tape::Write::give(&mut data, table)?;
+        Ok(self.add_bytes(T::tag(), data))
+    }
+
+    /// Add a table given as raw data, replacing the one with the same tag if any.
+    #[inline]
+    pub fn add_bytes(&mut self, tag: Tag, data: Vec<u8>) -> &mut Self {
+        self.tables.insert(tag, data);
+        self
+    }
+
+    /// Remove a table.
+    #[inline]
+    pub fn remove(&mut self, tag: Tag) -> Option<Vec<u8>> {
+        self.tables.remove(&tag)
+    }
+
+    /// Return the data of a table.
+    #[inline]
+    pub fn get(&self, tag: Tag) -> Option<&[u8]> {
+        self.tables.get(&tag).map(|data| &data[..])
+    }
+
+    /// Write the font.
+    pub fn write<T>(&self, tape: &mut T) -> Result<()>
+    where
+        T: crate::tape::Write,
+    {
+        tape.give_bytes(&self.build()?)
+    }
+
+    /// Write the font into a buffer.
+    pub fn build(&self) -> Result<Vec<u8>> {
+        let tables = self
+            .tables
+            .iter()
+            .map(|(tag, data)| {
+                let mut data = data.clone();
+                if tag.0 == *b"head" {
+                    if data.len() < 12 {
+                        raise!("found a malformed font header");
+                    }
+                    data[8..12].copy_from_slice(&[0; 4]);
+                }
+                Ok((*tag, checksum(tag, &data), data))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let fonts = vec![(self.version, (0..tables.len()).collect())];
+        let (offsets, mut data) = compose(fonts, tables)?;
+        let head = offsets[0]
+            .records
+            .iter()
+            .find(|record| record.tag.0 == *b"head");
+        if let Some(record) = head {
+            let value = 0xB1B0AFBAu32.wrapping_sub(sum(&data));
+            let offset = record.offset as usize + 8;
+            data[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+        }
+        Ok(data)
+    }
+}
@@ -0,0 +1,68 @@
+//! The [font collection][1].
+//!
+//! [1]: https://learn.microsoft.com/en-us/typography/opentype/spec/otff#collections
+
+use truetype::Tag;
+
+use crate::Result;
+
+/// The header of a font collection.
+#[derive(Clone, Debug)]
+pub enum Header {
+    /// Version 1.0.
+    Version1(Header1),
+    /// Version 2.0.
+    Version2(Header2),
+}
+
+table! {
+    /// The header of a font collection of version 1.0.
+    pub Header1 { // TTCHeader
+        tag           (Tag) = { Tag(*b"ttcf") }, // ttcTag
+        major_version (u16) = { 1 }, // majorVersion
+        minor_version (u16) = { 0 }, // minorVersion
+        font_count    (u32), // numFonts
+
+        offsets (Vec<u32>) |this, tape| { // tableDirectoryOffsets
+            tape.take_given(this.font_count as usize)
+        },
+    }
+}
+
+table! {
+    /// The header of a font collection of version 2.0.
+    pub Header2 { // TTCHeader
+        tag           (Tag) = { Tag(*b"ttcf") }, // ttcTag
+        major_version (u16) = { 2 }, // majorVersion
+        minor_version (u16) = { 0 }, // minorVersion
+        font_count    (u32), // numFonts
+
+        offsets (Vec<u32>) |this, tape| { // tableDirectoryOffsets
+            tape.take_given(this.font_count as usize)
+        },
+
+        signature_tag    (u32), // dsigTag
+        signature_size   (u32), // dsigLength
+        signature_offset (u32), // dsigOffset
+    }
+}
+
+impl Header {
+    /// Return the offsets of the fonts.
+    pub fn offsets(&self) -> &[u32] {
+        match self {
+            Self::Version1(ref header) => &header.offsets,
+            Self::Version2(ref header) => &header.offsets,
+        }
+    }
+}
+
+impl crate::value::Read for Header {
+    fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
+        Ok(match tape.peek::<(Tag, u32)>()?.1 {
+            0x00010000 => Self::Version1(tape.take()?),
+            0x00020000 => Self::Version2(tape.take()?),
+            _ => raise!("found an unknown version of the font collection"),
+        })
+    }
+}
@@ -1,7 +1,8 @@
 use truetype::Tag;
 
+use crate::collection::Header;
 use crate::tape::Read;
-use crate::{Font, Result};
+use crate::{woff, woff2, Font, Result};
 
 /// A file.
 pub struct File {
@@ -12,12 +13,18 @@ pub struct File {
 impl File {
     /// Read a file.
     pub fn read<T: crate::tape::Read>(tape: &mut T) -> Result<File> {
-        if &Read::peek::<Tag>(tape)?.0 == b"ttcf" {
-            raise!("found a TrueType collection, which is not supported yet");
-        }
-        Ok(File {
-            fonts: vec![Font::read(tape)?],
-        })
+        let position = tape.position()?;
+        let fonts = match &Read::peek::<Tag>(tape)?.0 {
+            b"ttcf" => {
+                let header = tape.take::<Header>()?;
+                let offsets = header.offsets();
+                jump_take!(@unwrap tape, position, offsets.len(), offsets)
+            }
+            b"wOFF" => vec![tape.take::<woff::File>()?.try_into()?],
+            b"wOF2" => return tape.take::<woff2::File>()?.try_into(),
+            _ => vec![Font::read(tape)?],
+        };
+        Ok(File { fonts })
     }
 }
 
@@ -1,14 +1,35 @@
-use truetype::tables::offsets::Offsets;
+use std::io::Cursor;
+use std::sync::Arc;
 
-use crate::tape::Read;
+use truetype::tables::offsets::{Header, Offsets, Record};
+use truetype::Tag;
+
+use crate::tape::{Read, Write};
 use crate::{Result, Table};
 
 /// A font.
 pub struct Font {
     /// The offset table.
     pub offsets: Offsets,
+
+    data: Option<Arc<[u8]>>,
 }
 
+macro_rules! take(
+    ($font:ident, $tape:ident, $type:ty, |$tape_:ident| $body:expr) => (
+        match $font.data {
+            Some(ref data) => {
+                let $tape_ = &mut Cursor::new(&data[..]);
+                $font.locate::<_, $type>($tape_)?.map(|_| $body).transpose()
+            }
+            _ => {
+                let $tape_ = $tape;
+                $font.locate::<_, $type>($tape_)?.map(|_| $body).transpose()
+            }
+        }
+    );
+);
+
 impl Font {
     /// Read a file.
     #[inline]
@@ -27,23 +48,18 @@ impl Font {
     }
 
     /// Jump to the position of the table.
+    ///
+    /// The function fails for fonts that have been decoded from a container, such as WOFF, since
+    /// their tables are not stored in the tape; `take` and `take_given` should be used instead.
     pub fn position<T, U>(&self, tape: &mut T) -> Result<Option<()>>
     where
         T: crate::tape::Read,
         U: Table,
     {
-        let tag = U::tag();
-        for record in &self.offsets.records {
-            if record.tag == tag {
-                #[cfg(not(feature = "ignore-invalid-checksums"))]
-                if record.checksum != record.checksum(tape)? {
-                    raise!("found a malformed font table with {:?}", record.tag);
-                }
-                Read::jump(tape, record.offset as u64)?;
-                return Ok(Some(()));
-            }
+        if self.data.is_some() {
+            raise!("found a decoded font, which cannot be positioned in the tape");
         }
-        Ok(None)
+        self.locate::<T, U>(tape)
     }
 
     /// Read a table.
@@ -53,9 +69,7 @@ impl Font {
         T: crate::tape::Read,
         U: Table + crate::value::Read,
     {
-        self.position::<T, U>(tape)?
-            .map(|_| tape.take::<U>())
-            .transpose()
+        take!(self, tape, U, |tape| tape.take::<U>())
     }
 
     /// Read a table given a parameter.
@@ -64,9 +78,64 @@ impl Font {
         T: crate::tape::Read,
         U: Table + crate::walue::Read<'l>,
     {
-        self.position::<T, U>(tape)?
-            .map(|_| tape.take_given::<U>(parameter))
-            .transpose()
+        take!(self, tape, U, |tape| tape.take_given::<U>(parameter))
+    }
+
+    pub(crate) fn assemble(
+        fonts: Vec<(u32, Vec<usize>)>,
+        tables: Vec<(Tag, u32, Vec<u8>)>,
+    ) -> Result<Vec<Self>> {
+        let (offsets, data) = compose(fonts, tables)?;
+        let data: Arc<[u8]> = data.into();
+        Ok(offsets
+            .into_iter()
+            .map(|offsets| Self {
+                offsets,
+                data: Some(data.clone()),
+            })
+            .collect())
+    }
+
+    pub(crate) fn take_tables<T>(&self, tape: &mut T) -> Result<Vec<(Tag, Vec<u8>)>>
+    where
+        T: crate::tape::Read,
+    {
+        fn read<T: crate::tape::Read>(
+            records: &[Record],
+            tape: &mut T,
+        ) -> Result<Vec<(Tag, Vec<u8>)>> {
+            records
+                .iter()
+                .map(|record| {
+                    Read::jump(tape, record.offset as u64)?;
+                    Ok((record.tag, tape.take_bytes(record.size as usize)?))
+                })
+                .collect()
+        }
+
+        match self.data {
+            Some(ref data) => read(&self.offsets.records, &mut Cursor::new(&data[..])),
+            _ => read(&self.offsets.records, tape),
+        }
+    }
+
+    fn locate<T, U>(&self, tape: &mut T) -> Result<Option<()>>
+    where
+        T: crate::tape::Read,
+        U: Table,
+    {
+        let tag = U::tag();
+        for record in &self.offsets.records {
+            if record.tag == tag {
+                #[cfg(not(feature = "ignore-invalid-checksums"))]
+                if record.checksum != record.checksum(tape)? {
+                    raise!("found a malformed font table with {:?}", record.tag);
+                }
+                Read::jump(tape, record.offset as u64)?;
+                return Ok(Some(()));
+            }
+        }
+        Ok(None)
     }
 }
 
@@ -75,6 +144,109 @@ impl crate::value::Read for Font {
     fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
         Ok(Self {
             offsets: tape.take()?,
+            data: None,
+        })
+    }
+}
+
+/// Lay out fonts sharing a set of tables, each given as a tag, a checksum, and data.
+pub(crate) fn compose(
+    fonts: Vec<(u32, Vec<usize>)>,
+    tables: Vec<(Tag, u32, Vec<u8>)>,
+) -> Result<(Vec<Offsets>, Vec<u8>)> {
+    let font_count = fonts.len();
+    let mut offset = if font_count > 1 {
+        4 * 3 + font_count * 4
+    } else {
+        0
+    };
+    let mut font_offsets = Vec::with_capacity(font_count);
+    for (_, indices) in fonts.iter() {
+        font_offsets.push(offset as u32);
+        offset += 4 * 3 + indices.len() * 4 * 4;
+    }
+    let mut table_offsets = Vec::with_capacity(tables.len());
+    for (_, _, data) in tables.iter() {
+        table_offsets.push(offset as u32);
+        offset += pad(data.len());
+    }
+    let offsets = fonts
+        .into_iter()
+        .map(|(version, mut indices)| {
+            indices.sort_by_key(|i| tables[*i].0);
+            let table_count = indices.len() as u16;
+            let (search_range, entry_selector, range_shift) = search(table_count);
+            Offsets {
+                header: Header {
+                    version,
+                    table_count,
+                    search_range,
+                    entry_selector,
+                    range_shift,
+                },
+                records: indices
+                    .into_iter()
+                    .map(|i| Record {
+                        tag: tables[i].0,
+                        checksum: tables[i].1,
+                        offset: table_offsets[i],
+                        size: tables[i].2.len() as u32,
+                    })
+                    .collect(),
+            }
         })
+        .collect::<Vec<_>>();
+    let mut data = Vec::with_capacity(offset);
+    if font_count > 1 {
+        data.give(b"ttcf")?;
+        data.give(&1u16)?;
+        data.give(&0u16)?;
+        data.give(&(font_count as u32))?;
+        data.give(&font_offsets[..])?;
+    }
+    for offsets in offsets.iter() {
+        data.give(offsets)?;
+    }
+    for (_, _, table) in tables {
+        data.extend(table);
+        data.resize(pad(data.len()), 0);
+    }
+    Ok((offsets, data))
+}
+
+/// Compute the checksum of a table.
+pub(crate) fn checksum(tag: &Tag, data: &[u8]) -> u32 {
+    let total = sum(data);
+    match data.get(8..12) {
+        Some(value) if tag.0 == *b"head" => {
+            total.wrapping_sub(u32::from_be_bytes([value[0], value[1], value[2], value[3]]))
+        }
+        _ => total,
+    }
+}
+
+/// Compute the sum of big-endian 32-bit words with the last one padded with zeros.
+pub(crate) fn sum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |total, chunk| {
+        let mut value = [0; 4];
+        value[..chunk.len()].copy_from_slice(chunk);
+        total.wrapping_add(u32::from_be_bytes(value))
+    })
+}
+
+#[inline]
+fn pad(size: usize) -> usize {
+    (size + 4 - 1) & !(4 - 1)
+}
+
+fn search(count: u16) -> (u16, u16, u16) {
+    let mut power = 1u16;
+    let mut entry_selector = 0;
+    while power.saturating_mul(2) <= count {
+        power *= 2;
+        entry_selector += 1;
     }
+    let search_range = power.saturating_mul(16);
+    let range_shift = count.saturating_mul(16).saturating_sub(search_range);
+    (search_range, entry_selector, range_shift)
 }
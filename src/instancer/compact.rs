@@ -0,0 +1,353 @@
+use postscript::compact1::Number;
+use truetype::tables::{HorizontalHeader, HorizontalMetrics, MaximumProfile};
+use truetype::{GlyphID, Tag};
+
+use crate::instancer::metrics::{clamp, Metrics};
+use crate::tables::compact2::{Compact2, Operand, Operator, Outline, Segment};
+use crate::tables::HorizontalVariations;
+use crate::tape::Write;
+use crate::{Builder, Font, Result};
+
+/// Bake the blends of the compact-font-format table of version 2 into a static table and return
+/// the metrics of the glyphs.
+///
+/// The character strings are replaced with the varied outlines, which leaves out hints and
+/// subroutines, and the operands of the private dictionaries are blended. The advance widths
+/// are taken from the horizontal-metrics variations if present.
+pub fn instantiate<T>(
+    font: &Font,
+    tape: &mut T,
+    builder: &mut Builder,
+    coordinates: &[f32],
+) -> Result<Option<Vec<Metrics>>>
+where
+    T: crate::tape::Read,
+{
+    let table = match font.take::<_, Compact2>(tape)? {
+        Some(table) => table,
+        _ => return Ok(None),
+    };
+    let maximum_profile = match font.take::<_, MaximumProfile>(tape)? {
+        Some(table) => table,
+        _ => raise!("found no maximum profile"),
+    };
+    let horizontal_header = match font.take::<_, HorizontalHeader>(tape)? {
+        Some(table) => table,
+        _ => raise!("found no horizontal header"),
+    };
+    let horizontal_metrics = match font
+        .take_given::<_, HorizontalMetrics>(tape, (&horizontal_header, &maximum_profile))?
+    {
+        Some(table) => table,
+        _ => raise!("found no horizontal metrics"),
+    };
+    let horizontal_variations = font.take::<_, HorizontalVariations>(tape)?;
+
+    let mut strings = Vec::with_capacity(table.glyph_count());
+    let mut metrics = Vec::with_capacity(table.glyph_count());
+    for glyph_id in 0..table.glyph_count() {
+        let glyph_id = glyph_id as GlyphID;
+        let outline = table.outline(glyph_id, coordinates)?;
+        let (advance_width, left_side_bearing) = match horizontal_variations {
+            Some(ref table) => table.get(&horizontal_metrics, glyph_id, coordinates),
+            _ => {
+                let (advance_width, left_side_bearing) = horizontal_metrics.get(glyph_id);
+                (advance_width as f32, left_side_bearing as f32)
+            }
+        };
+        let mut value = Metrics {
+            advance_width: clamp(advance_width, 0.0, u16::MAX as f32) as u16,
+            left_side_bearing: clamp(left_side_bearing, i16::MIN as f32, i16::MAX as f32) as i16,
+            ..Default::default()
+        };
+        if let Some(bounds) = bound(&outline) {
+            value.left_side_bearing = bounds[0];
+            value.bounds = Some(bounds);
+            value.default_max_y = bound(&table.outline(glyph_id, &[])?).map(|bounds| bounds[3]);
+        }
+        metrics.push(value);
+        strings.push(encode(&outline)?);
+    }
+    builder.add_bytes(Tag(*b"CFF2"), write(&table, strings, coordinates)?);
+    Ok(Some(metrics))
+}
+
+/// Write a static table with given character strings.
+fn write(table: &Compact2, strings: Vec<Vec<u8>>, coordinates: &[f32]) -> Result<Vec<u8>> {
+    const HEADER_SIZE: usize = 5;
+
+    let mut privates = Vec::with_capacity(table.privates.len());
+    for private in table.privates.iter() {
+        let index = private
+            .operations
+            .get_integer(Operator::VSIndex)
+            .unwrap_or(0);
+        let scalars = table
+            .store
+            .as_ref()
+            .and_then(|store| store.scalars(index as u16, coordinates));
+        let mut data = vec![];
+        let mut operations = private.operations.iter().collect::<Vec<_>>();
+        operations.sort_by_key(|(operator, _)| operator.code());
+        for (operator, operands) in operations {
+            if matches!(operator, Operator::Subrs | Operator::VSIndex) {
+                continue;
+            }
+            for operand in operands.iter() {
+                give(&mut data, blend(operand, scalars.as_deref())?)?;
+            }
+            give_operator(&mut data, *operator)?;
+        }
+        privates.push(data);
+    }
+    let selector = match table.selector {
+        Some(_) => Some(select(table)?),
+        _ => None,
+    };
+
+    let mut top = vec![];
+    if let Some(operands) = table.top_dictionary.0.get(&Operator::FontMatrix) {
+        for operand in operands.iter() {
+            give(&mut top, operand.value)?;
+        }
+        give_operator(&mut top, Operator::FontMatrix)?;
+    }
+    let top_size = top.len() + 6 + 7 + if selector.is_some() { 7 } else { 0 };
+    let global = index(&[])?;
+    let strings = index(&strings)?;
+    let strings_offset = HEADER_SIZE + top_size + global.len();
+    let selector_offset = strings_offset + strings.len();
+    let dictionaries_offset = selector_offset + selector.as_ref().map_or(0, Vec::len);
+    // The dictionaries refer to the private dictionaries with numbers of fixed size, so that
+    // their size is known beforehand.
+    let dictionaries_size = index(&vec![vec![0; 11]; privates.len()])?.len();
+    let mut offset = dictionaries_offset + dictionaries_size;
+    let mut dictionaries = Vec::with_capacity(privates.len());
+    for private in privates.iter() {
+        let mut data = vec![];
+        give_fixed(&mut data, private.len())?;
+        give_fixed(&mut data, offset)?;
+        give_operator(&mut data, Operator::Private)?;
+        dictionaries.push(data);
+        offset += private.len();
+    }
+    give_fixed(&mut top, strings_offset)?;
+    give_operator(&mut top, Operator::CharStrings)?;
+    give_fixed(&mut top, dictionaries_offset)?;
+    give_operator(&mut top, Operator::FDArray)?;
+    if selector.is_some() {
+        give_fixed(&mut top, selector_offset)?;
+        give_operator(&mut top, Operator::FDSelect)?;
+    }
+    debug_assert_eq!(top.len(), top_size);
+
+    let mut data = vec![];
+    data.give(&2u8)?;
+    data.give(&0u8)?;
+    data.give(&(HEADER_SIZE as u8))?;
+    data.give(&(top.len() as u16))?;
+    data.give_bytes(&top)?;
+    data.give_bytes(&global)?;
+    data.give_bytes(&strings)?;
+    if let Some(selector) = selector {
+        data.give_bytes(&selector)?;
+    }
+    data.give_bytes(&index(&dictionaries)?)?;
+    for private in privates {
+        data.give_bytes(&private)?;
+    }
+    Ok(data)
+}
+
+/// Encode an outline as a character string with integral coordinates.
+fn encode(outline: &Outline) -> Result<Vec<u8>> {
+    let mut data = vec![];
+    let mut point = (0, 0);
+    let mut step = |data: &mut Vec<u8>, (x, y): (f32, f32)| -> Result<()> {
+        let next = (x.round() as i32, y.round() as i32);
+        give_string(data, next.0 - point.0)?;
+        give_string(data, next.1 - point.1)?;
+        point = next;
+        Ok(())
+    };
+    for contour in outline.contours.iter() {
+        step(&mut data, contour.start)?;
+        // rmoveto
+        data.push(0x15);
+        for segment in contour.segments.iter() {
+            match *segment {
+                Segment::Linear(a) => {
+                    step(&mut data, a)?;
+                    // rlineto
+                    data.push(0x05);
+                }
+                Segment::Cubic(a, b, c) => {
+                    step(&mut data, a)?;
+                    step(&mut data, b)?;
+                    step(&mut data, c)?;
+                    // rrcurveto
+                    data.push(0x08);
+                }
+            }
+        }
+    }
+    Ok(data)
+}
+
+/// Write a mapping of glyphs to font dictionaries in format 3 or, if there are too many font
+/// dictionaries, in format 4.
+fn select(table: &Compact2) -> Result<Vec<u8>> {
+    let mut ranges: Vec<(u32, u16)> = vec![];
+    for glyph_id in 0..table.glyph_count() {
+        let index = table.dictionary_index(glyph_id as GlyphID).unwrap_or(0) as u16;
+        match ranges.last() {
+            Some(&(_, last)) if last == index => {}
+            _ => ranges.push((glyph_id as u32, index)),
+        }
+    }
+    let sentinel = table.glyph_count() as u32;
+    let mut data = vec![];
+    if table.dictionaries.len() <= 256 {
+        data.give(&3u8)?;
+        data.give(&(ranges.len() as u16))?;
+        for (first, index) in ranges {
+            data.give(&(first as u16))?;
+            data.give(&(index as u8))?;
+        }
+        data.give(&(sentinel as u16))?;
+    } else {
+        data.give(&4u8)?;
+        data.give(&(ranges.len() as u32))?;
+        for (first, index) in ranges {
+            data.give(&first)?;
+            data.give(&index)?;
+        }
+        data.give(&sentinel)?;
+    }
+    Ok(data)
+}
+
+/// Write an index of version 2.
+fn index(values: &[Vec<u8>]) -> Result<Vec<u8>> {
+    let mut data = vec![];
+    data.give(&(values.len() as u32))?;
+    if values.is_empty() {
+        return Ok(data);
+    }
+    let last = values.iter().map(Vec::len).sum::<usize>() + 1;
+    let size = match last {
+        0..=0xFF => 1,
+        0x100..=0xFFFF => 2,
+        0x10000..=0xFFFFFF => 3,
+        _ => 4,
+    };
+    data.give(&(size as u8))?;
+    let mut offset = 1;
+    for value in std::iter::once(&vec![]).chain(values.iter()) {
+        offset += value.len();
+        data.give_bytes(&(offset as u32).to_be_bytes()[4 - size..])?;
+    }
+    for value in values.iter() {
+        data.give_bytes(value)?;
+    }
+    Ok(data)
+}
+
+fn blend(operand: &Operand, scalars: Option<&[f32]>) -> Result<Number> {
+    if operand.deltas.is_empty() {
+        return Ok(operand.value);
+    }
+    let value = match scalars {
+        Some(scalars) => operand.blend(scalars),
+        _ => raise!("found no variation data for a private dictionary"),
+    };
+    if value.fract() == 0.0 && value.abs() < i32::MAX as f32 {
+        Ok(Number::Integer(value as i32))
+    } else {
+        Ok(Number::Real(value))
+    }
+}
+
+fn bound(outline: &Outline) -> Option<[i16; 4]> {
+    let ((min_x, min_y), (max_x, max_y)) = outline.bounding_box()?;
+    let narrow = |value: f32| value.clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+    Some([
+        narrow(min_x.floor()),
+        narrow(min_y.floor()),
+        narrow(max_x.ceil()),
+        narrow(max_y.ceil()),
+    ])
+}
+
+/// Write a number of a dictionary.
+fn give(data: &mut Vec<u8>, value: Number) -> Result<()> {
+    match value {
+        Number::Integer(value) => match value {
+            -107..=107 => data.give(&((value + 139) as u8)),
+            108..=1131 => {
+                let value = value - 108;
+                data.give_bytes(&[((value >> 8) + 247) as u8, value as u8])
+            }
+            -1131..=-108 => {
+                let value = -value - 108;
+                data.give_bytes(&[((value >> 8) + 251) as u8, value as u8])
+            }
+            -32768..=32767 => {
+                data.give(&0x1cu8)?;
+                data.give(&(value as i16))
+            }
+            _ => {
+                data.give(&0x1du8)?;
+                data.give(&value)
+            }
+        },
+        Number::Real(value) => {
+            let mut nibbles = vec![];
+            for character in format!("{value}").chars() {
+                nibbles.push(match character {
+                    '0'..='9' => character as u8 - b'0',
+                    '.' => 0xa,
+                    '-' => 0xe,
+                    _ => raise!("found a malformed real number ({value})"),
+                });
+            }
+            nibbles.push(0xf);
+            if nibbles.len() % 2 > 0 {
+                nibbles.push(0xf);
+            }
+            data.give(&0x1eu8)?;
+            for pair in nibbles.chunks(2) {
+                data.give(&(pair[0] << 4 | pair[1]))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Write a number of a dictionary with the largest encoding.
+fn give_fixed(data: &mut Vec<u8>, value: usize) -> Result<()> {
+    if value > i32::MAX as usize {
+        raise!("found a table too large to write");
+    }
+    data.give(&0x1du8)?;
+    data.give(&(value as i32))
+}
+
+fn give_operator(data: &mut Vec<u8>, operator: Operator) -> Result<()> {
+    match operator.code() {
+        code @ 0x0c00..=0x0cff => data.give_bytes(&[0x0c, code as u8]),
+        code => data.give(&(code as u8)),
+    }
+}
+
+/// Write a number of a character string.
+fn give_string(data: &mut Vec<u8>, value: i32) -> Result<()> {
+    match value {
+        -1131..=1131 => give(data, Number::Integer(value)),
+        -32768..=32767 => {
+            data.give(&0x1cu8)?;
+            data.give(&(value as i16))
+        }
+        _ => raise!("found a coordinate out of range ({value})"),
+    }
+}
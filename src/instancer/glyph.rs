@@ -0,0 +1,259 @@
+use truetype::tables::glyph_data::{Arguments, Description, Glyph, Options};
+use truetype::tables::{
+    FontHeader, GlyphData, GlyphMapping, HorizontalHeader, HorizontalMetrics, MaximumProfile,
+};
+use truetype::{GlyphID, Tag};
+
+use crate::instancer::metrics::{clamp, narrow, Metrics};
+use crate::instancer::patch;
+use crate::tables::{GlyphVariations, HorizontalVariations};
+use crate::tape::Write;
+use crate::{Builder, Font, Result};
+
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+
+/// Bake the glyph variations into the outlines of the glyphs and return their metrics.
+///
+/// The glyph data and mapping are rewritten with the varied points and component offsets. The
+/// advance widths are taken from the horizontal-metrics variations if present and from the
+/// varied phantom points otherwise.
+pub fn instantiate<T>(
+    font: &Font,
+    tape: &mut T,
+    builder: &mut Builder,
+    coordinates: &[f32],
+) -> Result<Option<Vec<Metrics>>>
+where
+    T: crate::tape::Read,
+{
+    let glyph_variations = match font.take::<_, GlyphVariations>(tape)? {
+        Some(table) => table,
+        _ => return Ok(None),
+    };
+    let font_header = match font.take::<_, FontHeader>(tape)? {
+        Some(table) => table,
+        _ => raise!("found no font header"),
+    };
+    let maximum_profile = match font.take::<_, MaximumProfile>(tape)? {
+        Some(table) => table,
+        _ => raise!("found no maximum profile"),
+    };
+    let horizontal_header = match font.take::<_, HorizontalHeader>(tape)? {
+        Some(table) => table,
+        _ => raise!("found no horizontal header"),
+    };
+    let horizontal_metrics = match font
+        .take_given::<_, HorizontalMetrics>(tape, (&horizontal_header, &maximum_profile))?
+    {
+        Some(table) => table,
+        _ => raise!("found no horizontal metrics"),
+    };
+    let glyph_mapping =
+        match font.take_given::<_, GlyphMapping>(tape, (&font_header, &maximum_profile))? {
+            Some(table) => table,
+            _ => raise!("found no glyph mapping"),
+        };
+    let glyph_data = match font.take_given::<_, GlyphData>(tape, &glyph_mapping)? {
+        Some(table) => table,
+        _ => raise!("found no glyph data"),
+    };
+    let horizontal_variations = font.take::<_, HorizontalVariations>(tape)?;
+
+    let mut data = vec![];
+    let mut offsets = vec![0u32];
+    let mut metrics = Vec::with_capacity(glyph_data.len());
+    for (glyph_id, glyph) in glyph_data.iter().enumerate() {
+        let glyph_id = glyph_id as GlyphID;
+        let outline =
+            glyph_variations.apply(&glyph_data, &horizontal_metrics, glyph_id, coordinates)?;
+        let advance_width = match horizontal_variations {
+            Some(ref table) => table.get(&horizontal_metrics, glyph_id, coordinates).0,
+            _ => outline.advance_width(),
+        };
+        let mut value = Metrics {
+            advance_width: clamp(advance_width, 0.0, u16::MAX as f32) as u16,
+            left_side_bearing: horizontal_metrics.get(glyph_id).1,
+            vertical_deltas: (outline.advance_height_delta(), outline.phantom_points[2].1),
+            ..Default::default()
+        };
+        let glyph = match glyph {
+            Some(glyph) => glyph,
+            _ => {
+                metrics.push(value);
+                offsets.push(data.len() as u32);
+                continue;
+            }
+        };
+        let points = outline
+            .points
+            .iter()
+            .map(|&(x, y)| (round(x), round(y)))
+            .collect::<Vec<_>>();
+        let [min_x, min_y, max_x, max_y] = bound(&points);
+        value.left_side_bearing = clamp(
+            min_x as f32 - outline.phantom_points[0].0,
+            i16::MIN as f32,
+            i16::MAX as f32,
+        ) as i16;
+        if !points.is_empty() {
+            value.bounds = Some([min_x, min_y, max_x, max_y]);
+            value.default_max_y = Some(glyph.max_y);
+        }
+        metrics.push(value);
+        data.give(&glyph.contour_count)?;
+        data.give(&min_x)?;
+        data.give(&min_y)?;
+        data.give(&max_x)?;
+        data.give(&max_y)?;
+        encode(
+            &mut data,
+            glyph,
+            glyph_id,
+            &points,
+            &outline.on_curve,
+            &glyph_variations,
+            coordinates,
+        )?;
+        while data.len() % 4 != 0 {
+            data.push(0);
+        }
+        offsets.push(data.len() as u32);
+    }
+
+    let mut mapping = Vec::with_capacity(4 * offsets.len());
+    for offset in offsets {
+        mapping.give(&offset)?;
+    }
+    let mut head = match builder.get(Tag(*b"head")) {
+        Some(data) => data.to_vec(),
+        _ => raise!("found no font header"),
+    };
+    patch(&mut head, 50, 1i16)?;
+    builder.add_bytes(Tag(*b"glyf"), data);
+    builder.add_bytes(Tag(*b"loca"), mapping);
+    builder.add_bytes(Tag(*b"head"), head);
+    Ok(Some(metrics))
+}
+
+fn encode(
+    data: &mut Vec<u8>,
+    glyph: &Glyph,
+    glyph_id: GlyphID,
+    points: &[(i32, i32)],
+    on_curve: &[bool],
+    glyph_variations: &GlyphVariations,
+    coordinates: &[f32],
+) -> Result<()> {
+    match glyph.description {
+        Description::Simple(ref description) => {
+            for end_point in description.end_points.iter() {
+                data.give(end_point)?;
+            }
+            data.give(&description.instruction_size)?;
+            data.give_bytes(&description.instructions)?;
+            let coordinates = on_curve
+                .iter()
+                .zip(points)
+                .map(|(on_curve, (x, y))| (*on_curve, *x, *y))
+                .collect::<Vec<_>>();
+            let overlap = description
+                .flags
+                .first()
+                .is_some_and(|flags| flags.is_overlap_simple());
+            crate::woff2::transform::encode(data, &coordinates, overlap)?;
+        }
+        Description::Composite(ref description) => {
+            let mut points = description
+                .components
+                .iter()
+                .map(|component| match component.arguments {
+                    Arguments::Offsets(x, y) => (x as f32, y as f32),
+                    Arguments::Indices(..) => (0.0, 0.0),
+                })
+                .collect::<Vec<_>>();
+            points.extend([(0.0, 0.0); 4]);
+            let deltas = glyph_variations.deltas(glyph_id, &points, &[], coordinates);
+            for ((component, point), delta) in description.components.iter().zip(points).zip(deltas)
+            {
+                let arguments = match component.arguments {
+                    Arguments::Offsets(..) => {
+                        (round(point.0 + delta.0), round(point.1 + delta.1), true)
+                    }
+                    Arguments::Indices(i, j) => (i as i32, j as i32, false),
+                };
+                let words = match arguments {
+                    (x, y, true) => !(-128..128).contains(&x) || !(-128..128).contains(&y),
+                    (i, j, _) => i > 255 || j > 255,
+                };
+                let mut flags = component.flags.0 & !ARG_1_AND_2_ARE_WORDS;
+                if words {
+                    flags |= ARG_1_AND_2_ARE_WORDS;
+                }
+                data.give(&flags)?;
+                data.give(&component.glyph_id)?;
+                match arguments {
+                    (x, y, true) if words => {
+                        data.give(&narrow(x))?;
+                        data.give(&narrow(y))?;
+                    }
+                    (x, y, true) => {
+                        data.give(&(x as i8))?;
+                        data.give(&(y as i8))?;
+                    }
+                    (i, j, _) if words => {
+                        data.give(&(i as u16))?;
+                        data.give(&(j as u16))?;
+                    }
+                    (i, j, _) => {
+                        data.give(&(i as u8))?;
+                        data.give(&(j as u8))?;
+                    }
+                }
+                match component.options {
+                    Options::None => {}
+                    Options::Scalar(value) => data.give(&value)?,
+                    Options::Vector(x, y) => {
+                        data.give(&x)?;
+                        data.give(&y)?;
+                    }
+                    Options::Matrix(xx, xy, yx, yy) => {
+                        data.give(&xx)?;
+                        data.give(&xy)?;
+                        data.give(&yx)?;
+                        data.give(&yy)?;
+                    }
+                }
+            }
+            let instructions = description
+                .components
+                .last()
+                .is_some_and(|component| component.flags.has_instructions());
+            if instructions {
+                data.give(&description.instruction_size)?;
+                data.give_bytes(&description.instructions)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn bound(points: &[(i32, i32)]) -> [i16; 4] {
+    let mut iterator = points.iter();
+    let (mut min_x, mut min_y) = match iterator.next() {
+        Some(&point) => point,
+        _ => return [0; 4],
+    };
+    let (mut max_x, mut max_y) = (min_x, min_y);
+    for &(x, y) in iterator {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+    [narrow(min_x), narrow(min_y), narrow(max_x), narrow(max_y)]
+}
+
+#[inline]
+fn round(value: f32) -> i32 {
+    value.round() as i32
+}
@@ -0,0 +1,227 @@
+use truetype::{GlyphID, Tag};
+
+use crate::instancer::{patch, peek};
+use crate::tables::VerticalVariations;
+use crate::tape::Write;
+use crate::{Builder, Font, Result};
+
+/// The metrics of a varied glyph.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Metrics {
+    /// The advance width.
+    pub advance_width: u16,
+    /// The left side bearing.
+    pub left_side_bearing: i16,
+    /// The bounding box if the glyph has an outline.
+    pub bounds: Option<[i16; 4]>,
+    /// The maximum vertical coordinate of the default outline if any.
+    pub default_max_y: Option<i16>,
+    /// The deltas of the advance height and vertical origin given by the outline.
+    pub vertical_deltas: (f32, f32),
+}
+
+/// Write the horizontal metrics and update the font header, horizontal header, and average
+/// advance width of the OS/2 table accordingly.
+pub fn horizontal(builder: &mut Builder, metrics: &[Metrics]) -> Result<()> {
+    write(
+        builder,
+        (Tag(*b"hmtx"), Tag(*b"hhea")),
+        metrics.iter().map(|metrics| {
+            (
+                metrics.advance_width,
+                metrics.left_side_bearing,
+                metrics.bounds.map(|[min_x, _, max_x, _]| (min_x, max_x)),
+            )
+        }),
+    )?;
+    let mut head = match builder.get(Tag(*b"head")) {
+        Some(data) => data.to_vec(),
+        _ => raise!("found no font header"),
+    };
+    let bounds = metrics.iter().filter_map(|metrics| metrics.bounds).reduce(
+        |[x0, y0, x1, y1], [min_x, min_y, max_x, max_y]| {
+            [x0.min(min_x), y0.min(min_y), x1.max(max_x), y1.max(max_y)]
+        },
+    );
+    let [min_x, min_y, max_x, max_y] = bounds.unwrap_or_default();
+    patch(&mut head, 36, min_x)?;
+    patch(&mut head, 38, min_y)?;
+    patch(&mut head, 40, max_x)?;
+    patch(&mut head, 42, max_y)?;
+    builder.add_bytes(Tag(*b"head"), head);
+    if let Some(data) = builder.get(Tag(*b"OS/2")) {
+        // The average is taken over the glyphs with nonzero advances as of version 3.
+        let widths = metrics
+            .iter()
+            .map(|metrics| metrics.advance_width as f32)
+            .filter(|width| *width > 0.0)
+            .collect::<Vec<_>>();
+        let width = match widths.len() {
+            0 => 0.0,
+            count => widths.iter().sum::<f32>() / count as f32,
+        };
+        let mut data = data.to_vec();
+        patch(&mut data, 2, width.round() as i16)?;
+        builder.add_bytes(Tag(*b"OS/2"), data);
+    }
+    Ok(())
+}
+
+/// Vary the vertical metrics and origins, if any, and update the vertical header accordingly.
+///
+/// The vertical-metrics variations are used if present; otherwise, the deltas given by the
+/// outlines are. Side bearings without variations of their own follow the varied outlines.
+pub fn vertical<T>(
+    font: &Font,
+    tape: &mut T,
+    builder: &mut Builder,
+    metrics: &[Metrics],
+    coordinates: &[f32],
+) -> Result<()>
+where
+    T: crate::tape::Read,
+{
+    let data = match builder.get(Tag(*b"vmtx")) {
+        Some(data) => data,
+        _ => return Ok(()),
+    };
+    let metric_count = match builder.get(Tag(*b"vhea")) {
+        Some(header) => peek::<u16>(header, 34)? as usize,
+        _ => raise!("found no vertical header"),
+    };
+    if metric_count == 0 && !metrics.is_empty() {
+        raise!("found malformed vertical metrics");
+    }
+    let mut values = Vec::with_capacity(metrics.len());
+    for i in 0..metrics.len() {
+        if i < metric_count {
+            values.push((peek::<u16>(data, 4 * i)?, peek::<i16>(data, 4 * i + 2)?));
+        } else {
+            let advance_height = peek::<u16>(data, 4 * (metric_count - 1))?;
+            let offset = 4 * metric_count + 2 * (i - metric_count);
+            values.push((advance_height, peek::<i16>(data, offset)?));
+        }
+    }
+    let variations = font.take::<_, VerticalVariations>(tape)?;
+    let mut entries = Vec::with_capacity(metrics.len());
+    let mut origin_deltas = Vec::with_capacity(metrics.len());
+    for (glyph_id, (metrics, (advance_height, top_side_bearing))) in
+        metrics.iter().zip(values).enumerate()
+    {
+        let glyph_id = glyph_id as GlyphID;
+        let (advance_height_delta, origin_delta) = match variations {
+            Some(ref table) => (
+                table.advance_height_delta(glyph_id, coordinates),
+                table
+                    .vertical_origin_delta(glyph_id, coordinates)
+                    .unwrap_or(metrics.vertical_deltas.1),
+            ),
+            _ => metrics.vertical_deltas,
+        };
+        let bearing_delta = variations
+            .as_ref()
+            .and_then(|table| table.top_side_bearing_delta(glyph_id, coordinates));
+        let top_side_bearing = match (bearing_delta, metrics.default_max_y, metrics.bounds) {
+            (Some(delta), ..) => top_side_bearing as f32 + delta,
+            (_, Some(default_max_y), Some([_, _, _, max_y])) => {
+                default_max_y as f32 + top_side_bearing as f32 + origin_delta - max_y as f32
+            }
+            _ => top_side_bearing as f32,
+        };
+        entries.push((
+            clamp(
+                advance_height as f32 + advance_height_delta,
+                0.0,
+                u16::MAX as f32,
+            ) as u16,
+            clamp(top_side_bearing, i16::MIN as f32, i16::MAX as f32) as i16,
+            metrics.bounds.map(|[_, min_y, _, max_y]| (min_y, max_y)),
+        ));
+        origin_deltas.push(origin_delta);
+    }
+    write(builder, (Tag(*b"vmtx"), Tag(*b"vhea")), entries.into_iter())?;
+    if let Some(data) = builder.get(Tag(*b"VORG")) {
+        let data = origins(data, &origin_deltas)?;
+        builder.add_bytes(Tag(*b"VORG"), data);
+    }
+    Ok(())
+}
+
+fn write<I>(builder: &mut Builder, (table, header): (Tag, Tag), entries: I) -> Result<()>
+where
+    I: Iterator<Item = (u16, i16, Option<(i16, i16)>)>,
+{
+    let mut data = vec![];
+    let mut count = 0u16;
+    let mut advance_max = 0;
+    let mut extrema: Option<(i16, i16, i16)> = None;
+    for (advance, bearing, span) in entries {
+        data.give(&advance)?;
+        data.give(&bearing)?;
+        count = count.saturating_add(1);
+        advance_max = advance_max.max(advance);
+        if let Some((start, end)) = span {
+            let size = end as i32 - start as i32;
+            let (first, second, extent) = (
+                bearing,
+                narrow(advance as i32 - bearing as i32 - size),
+                narrow(bearing as i32 + size),
+            );
+            extrema = Some(match extrema {
+                Some((x0, x1, x2)) => (x0.min(first), x1.min(second), x2.max(extent)),
+                _ => (first, second, extent),
+            });
+        }
+    }
+    let mut header_data = match builder.get(header) {
+        Some(data) => data.to_vec(),
+        _ => raise!("found no header for {table:?}"),
+    };
+    let (first, second, extent) = extrema.unwrap_or_default();
+    patch(&mut header_data, 10, advance_max)?;
+    patch(&mut header_data, 12, first)?;
+    patch(&mut header_data, 14, second)?;
+    patch(&mut header_data, 16, extent)?;
+    patch(&mut header_data, 34, count)?;
+    builder.add_bytes(table, data);
+    builder.add_bytes(header, header_data);
+    Ok(())
+}
+
+/// Vary the vertical origins of a vertical-origin table.
+fn origins(data: &[u8], deltas: &[f32]) -> Result<Vec<u8>> {
+    let default = peek::<i16>(data, 4)?;
+    let count = peek::<u16>(data, 6)? as usize;
+    let mut values = vec![default; deltas.len()];
+    for i in 0..count {
+        let glyph_id = peek::<u16>(data, 8 + 4 * i)? as usize;
+        if let Some(value) = values.get_mut(glyph_id) {
+            *value = peek::<i16>(data, 8 + 4 * i + 2)?;
+        }
+    }
+    let mut records = vec![];
+    for (glyph_id, (value, delta)) in values.iter().zip(deltas).enumerate() {
+        let value = clamp(*value as f32 + delta, i16::MIN as f32, i16::MAX as f32) as i16;
+        if value != default {
+            records.push((glyph_id as u16, value));
+        }
+    }
+    let mut other = data[..4].to_vec();
+    other.give(&default)?;
+    other.give(&(records.len() as u16))?;
+    for (glyph_id, value) in records {
+        other.give(&glyph_id)?;
+        other.give(&value)?;
+    }
+    Ok(other)
+}
+
+#[inline]
+pub fn clamp(value: f32, minimum: f32, maximum: f32) -> f32 {
+    value.round().clamp(minimum, maximum)
+}
+
+#[inline]
+pub fn narrow(value: i32) -> i16 {
+    value.clamp(i16::MIN as i32, i16::MAX as i32) as i16
+}
@@ -0,0 +1,138 @@
+mod compact;
+mod glyph;
+mod metrics;
+mod value;
+
+use std::io::Cursor;
+
+use truetype::Tag;
+
+use crate::tables::{
+    AxisVariations, FontVariations, GlyphDefinition, GlyphPositioning, GlyphSubstitution,
+};
+use crate::{Builder, Font, Result};
+
+const TAGS: [&[u8; 4]; 7] = [
+    b"HVAR", b"MVAR", b"VVAR", b"avar", b"cvar", b"fvar", b"gvar",
+];
+
+/// An instancer of variable fonts.
+///
+/// The instancer bakes a variable font into a static font at given axis coordinates. The glyph
+/// variations are applied to the TrueType outlines, and the blends of CFF2 outlines are resolved
+/// with hints and subroutines dropped. The horizontal and vertical metrics variations are applied
+/// to the metrics, vertical origins, and average advance width, the metric variations are applied
+/// to the values they refer to, the feature variations of the glyph substitution and positioning
+/// are applied, the variation corrections of the glyph positioning and definition are resolved
+/// into plain values, and the variation tables are dropped. Fonts with control-value variations
+/// can only be instantiated at the default coordinates.
+#[derive(Clone, Debug)]
+pub struct Instancer {
+    coordinates: Vec<f32>,
+}
+
+impl Instancer {
+    /// Create an instance for axis values in user units.
+    ///
+    /// The values are normalized using the font variations and axis variations of the font.
+    /// Axes that are not given are taken to be at their default values.
+    pub fn new<T, U>(font: &Font, tape: &mut T, values: U) -> Result<Self>
+    where
+        T: crate::tape::Read,
+        U: IntoIterator<Item = (Tag, f32)>,
+    {
+        let font_variations = match font.take::<_, FontVariations>(tape)? {
+            Some(table) => table,
+            _ => raise!("found no font variations"),
+        };
+        let axis_variations = font.take::<_, AxisVariations>(tape)?;
+        Ok(Self {
+            coordinates: font_variations.normalize(values, axis_variations.as_ref()),
+        })
+    }
+
+    /// Create an instance for normalized axis coordinates.
+    #[inline]
+    pub fn with_coordinates(coordinates: Vec<f32>) -> Self {
+        Self { coordinates }
+    }
+
+    /// Return the normalized axis coordinates.
+    #[inline]
+    pub fn coordinates(&self) -> &[f32] {
+        &self.coordinates
+    }
+
+    /// Bake a font into a builder of the static font.
+    pub fn instantiate<T>(&self, font: &Font, tape: &mut T) -> Result<Builder>
+    where
+        T: crate::tape::Read,
+    {
+        let mut builder = Builder::from_font(font, tape)?;
+        if self.coordinates.iter().any(|value| *value != 0.0) {
+            if builder.get(Tag(*b"cvar")).is_some() {
+                raise!(
+                    "found variations that cannot be instantiated in {:?}",
+                    Tag(*b"cvar")
+                );
+            }
+            let metrics = match glyph::instantiate(font, tape, &mut builder, &self.coordinates)? {
+                Some(metrics) => Some(metrics),
+                _ => compact::instantiate(font, tape, &mut builder, &self.coordinates)?,
+            };
+            if let Some(metrics) = metrics {
+                metrics::horizontal(&mut builder, &metrics)?;
+                metrics::vertical(font, tape, &mut builder, &metrics, &self.coordinates)?;
+            }
+            value::instantiate(font, tape, &mut builder, &self.coordinates)?;
+        }
+        let definition = font.take::<_, GlyphDefinition>(tape)?;
+        if let Some(mut table) = font.take::<_, GlyphSubstitution>(tape)? {
+            if table.variations.is_some() {
+                table.substitute(&self.coordinates);
+                builder.add(&table)?;
+            }
+        }
+        if let Some(mut table) = font.take::<_, GlyphPositioning>(tape)? {
+            let store = definition
+                .as_ref()
+                .and_then(|table| table.variations.as_ref());
+            if table.variations.is_some() || store.is_some() {
+                table.substitute(&self.coordinates);
+                if let Some(store) = store {
+                    table.instantiate(store, &self.coordinates);
+                }
+                builder.add(&table)?;
+            }
+        }
+        if let Some(mut table) = definition {
+            if table.variations.is_some() {
+                table.instantiate(&self.coordinates);
+                builder.add(&table)?;
+            }
+        }
+        for tag in TAGS {
+            builder.remove(Tag(*tag));
+        }
+        Ok(builder)
+    }
+}
+
+/// Overwrite a value at a given position in the data of a table.
+fn patch<T: crate::value::Write>(data: &mut [u8], offset: usize, value: T) -> Result<()> {
+    let mut bytes = vec![];
+    crate::tape::Write::give(&mut bytes, &value)?;
+    match data.get_mut(offset..offset + bytes.len()) {
+        Some(slice) => slice.copy_from_slice(&bytes),
+        _ => raise!("found a malformed table"),
+    }
+    Ok(())
+}
+
+/// Read a value at a given position in the data of a table.
+fn peek<T: crate::value::Read>(data: &[u8], offset: usize) -> Result<T> {
+    match data.get(offset..) {
+        Some(data) => crate::tape::Read::take(&mut Cursor::new(data)),
+        _ => raise!("found a malformed table"),
+    }
+}
@@ -0,0 +1,90 @@
+use truetype::Tag;
+
+use crate::instancer::patch;
+use crate::tables::MetricVariations;
+use crate::{Builder, Font, Result};
+
+// The value tag, the tag of the affected table, the offset of the value, and whether the value
+// is signed.
+const FIELDS: [(&[u8; 4], &[u8; 4], usize, bool); 38] = [
+    (b"cpht", b"OS/2", 88, true),
+    (b"gsp0", b"gasp", 4, false),
+    (b"gsp1", b"gasp", 8, false),
+    (b"gsp2", b"gasp", 12, false),
+    (b"gsp3", b"gasp", 16, false),
+    (b"gsp4", b"gasp", 20, false),
+    (b"gsp5", b"gasp", 24, false),
+    (b"gsp6", b"gasp", 28, false),
+    (b"gsp7", b"gasp", 32, false),
+    (b"gsp8", b"gasp", 36, false),
+    (b"gsp9", b"gasp", 40, false),
+    (b"hasc", b"OS/2", 68, true),
+    (b"hcla", b"OS/2", 74, false),
+    (b"hcld", b"OS/2", 76, false),
+    (b"hcof", b"hhea", 22, true),
+    (b"hcrn", b"hhea", 20, true),
+    (b"hcrs", b"hhea", 18, true),
+    (b"hdsc", b"OS/2", 70, true),
+    (b"hlgp", b"OS/2", 72, true),
+    (b"sbxo", b"OS/2", 14, true),
+    (b"sbxs", b"OS/2", 10, true),
+    (b"sbyo", b"OS/2", 16, true),
+    (b"sbys", b"OS/2", 12, true),
+    (b"spxo", b"OS/2", 22, true),
+    (b"spxs", b"OS/2", 18, true),
+    (b"spyo", b"OS/2", 24, true),
+    (b"spys", b"OS/2", 20, true),
+    (b"stro", b"OS/2", 28, true),
+    (b"strs", b"OS/2", 26, true),
+    (b"undo", b"post", 8, true),
+    (b"unds", b"post", 10, true),
+    (b"vasc", b"vhea", 4, true),
+    (b"vcof", b"vhea", 22, true),
+    (b"vcrn", b"vhea", 20, true),
+    (b"vcrs", b"vhea", 18, true),
+    (b"vdsc", b"vhea", 6, true),
+    (b"vlgp", b"vhea", 8, true),
+    (b"xhgt", b"OS/2", 86, true),
+];
+
+/// Bake the metric variations into the values of the tables they refer to.
+///
+/// Values identified by unknown tags and values of absent tables are left as they are.
+pub fn instantiate<T>(
+    font: &Font,
+    tape: &mut T,
+    builder: &mut Builder,
+    coordinates: &[f32],
+) -> Result<()>
+where
+    T: crate::tape::Read,
+{
+    let table = match font.take::<_, MetricVariations>(tape)? {
+        Some(table) => table,
+        _ => return Ok(()),
+    };
+    for record in table.records.iter() {
+        let (_, target, offset, signed) =
+            match FIELDS.iter().find(|(tag, ..)| Tag(**tag) == record.tag) {
+                Some(field) => *field,
+                _ => continue,
+            };
+        let delta = table.delta(record.tag, coordinates).unwrap_or(0.0);
+        let mut data = match builder.get(Tag(*target)) {
+            Some(data) if data.len() >= offset + 2 => data.to_vec(),
+            _ => continue,
+        };
+        let value = u16::from_be_bytes([data[offset], data[offset + 1]]);
+        if signed {
+            let value = value as i16 as f32 + delta;
+            let value = value.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+            patch(&mut data, offset, value)?;
+        } else {
+            let value = value as f32 + delta;
+            let value = value.round().clamp(0.0, u16::MAX as f32) as u16;
+            patch(&mut data, offset, value)?;
+        }
+        builder.add_bytes(Tag(*target), data);
+    }
+    Ok(())
+}
@@ -0,0 +1,254 @@
+//! The glyph buffer.
+
+use truetype::GlyphID;
+
+use crate::layout::skip::Skip;
+use crate::Result;
+
+/// The number of operations allowed per initial glyph and the minimum when applying lookups.
+const OPERATION_LIMIT: (usize, usize) = (1024, 16384);
+
+/// The number of glyphs allowed per initial glyph and the minimum when applying lookups.
+const LENGTH_LIMIT: (usize, usize) = (64, 16384);
+
+/// A glyph buffer.
+#[derive(Clone, Debug, Default)]
+pub struct Buffer {
+    /// The glyphs.
+    pub glyphs: Vec<Glyph>,
+
+    ligature_count: u16,
+    operation_count: usize,
+    operation_limit: usize,
+    length_limit: usize,
+}
+
+/// A glyph of a buffer.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Glyph {
+    /// The glyph identifier.
+    pub glyph_id: GlyphID,
+    /// The index of the input character or cluster the glyph originates from.
+    pub cluster: usize,
+    /// The class used when the glyph definition does not classify glyphs or zero if unknown.
+    ///
+    /// Classes 1, 2, 3, and 4 stand for base glyphs, ligatures, marks, and components,
+    /// respectively.
+    pub class: u16,
+    /// The identifier of the ligature the glyph belongs to or zero if none.
+    pub ligature_id: u16,
+    /// The index of the ligature component the glyph is attached to.
+    pub component_index: u16,
+    /// The horizontal advance.
+    pub x_advance: i32,
+    /// The vertical advance.
+    pub y_advance: i32,
+    /// The horizontal offset from the pen position.
+    pub x_offset: i32,
+    /// The vertical offset from the pen position.
+    pub y_offset: i32,
+    /// The attachment to another glyph if any.
+    pub attachment: Option<Attachment>,
+}
+
+/// An attachment of a glyph to another glyph given by its position in a buffer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Attachment {
+    /// A cursive attachment.
+    Cursive(usize),
+    /// A mark attachment.
+    Mark(usize),
+}
+
+impl Buffer {
+    /// Create an instance with one cluster per glyph.
+    pub fn new<T: IntoIterator<Item = GlyphID>>(glyph_ids: T) -> Self {
+        Self {
+            glyphs: glyph_ids
+                .into_iter()
+                .enumerate()
+                .map(|(cluster, glyph_id)| Glyph {
+                    glyph_id,
+                    cluster,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Iterate over the glyph identifiers.
+    pub fn glyph_ids(&self) -> impl Iterator<Item = GlyphID> + '_ {
+        self.glyphs.iter().map(|glyph| glyph.glyph_id)
+    }
+
+    /// Return the number of glyphs.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    /// Check if the buffer is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    /// Resolve the offsets of attached glyphs relative to the glyphs they are attached to.
+    ///
+    /// The glyphs are assumed to be laid out from left to right.
+    pub(crate) fn resolve(&mut self) {
+        let mut done = vec![false; self.glyphs.len()];
+        let mut chain = vec![];
+        for position in 0..self.glyphs.len() {
+            // The chain of attachments is followed up to a glyph that is either resolved or not
+            // attached and then resolved from there on.
+            let mut current = position;
+            while !done[current] {
+                done[current] = true;
+                match self.parent(current) {
+                    Some((parent, _)) => {
+                        chain.push(current);
+                        current = parent;
+                    }
+                    _ => break,
+                }
+            }
+            while let Some(current) = chain.pop() {
+                self.resolve_given(current);
+            }
+        }
+    }
+
+    fn parent(&self, position: usize) -> Option<(usize, bool)> {
+        let (parent, is_mark) = match self.glyphs[position].attachment {
+            Some(Attachment::Cursive(parent)) => (parent, false),
+            Some(Attachment::Mark(parent)) => (parent, true),
+            _ => return None,
+        };
+        if parent >= self.glyphs.len() || parent == position {
+            return None;
+        }
+        Some((parent, is_mark))
+    }
+
+    fn resolve_given(&mut self, position: usize) {
+        let (parent, is_mark) = match self.parent(position) {
+            Some(value) => value,
+            _ => return,
+        };
+        let (x_offset, y_offset) = (self.glyphs[parent].x_offset, self.glyphs[parent].y_offset);
+        let advance = if parent < position {
+            -self.glyphs[parent..position]
+                .iter()
+                .map(|glyph| glyph.x_advance)
+                .sum::<i32>()
+        } else {
+            self.glyphs[position..parent]
+                .iter()
+                .map(|glyph| glyph.x_advance)
+                .sum::<i32>()
+        };
+        let glyph = &mut self.glyphs[position];
+        glyph.y_offset += y_offset;
+        if is_mark {
+            glyph.x_offset += x_offset + advance;
+        }
+    }
+
+    /// Set the limits on the number of operations and glyphs in proportion to the number of
+    /// glyphs.
+    pub(crate) fn limit(&mut self) {
+        let (factor, minimum) = OPERATION_LIMIT;
+        self.operation_count = 0;
+        self.operation_limit = self.glyphs.len().saturating_mul(factor).max(minimum);
+        let (factor, minimum) = LENGTH_LIMIT;
+        self.length_limit = self.glyphs.len().saturating_mul(factor).max(minimum);
+    }
+
+    /// Count an operation and check that the limits are respected.
+    pub(crate) fn count(&mut self) -> Result<()> {
+        self.operation_count += 1;
+        if self.operation_count > self.operation_limit {
+            raise!("exceeded the limit on operations");
+        }
+        if self.glyphs.len() > self.length_limit {
+            raise!("exceeded the limit on glyphs");
+        }
+        Ok(())
+    }
+
+    /// Allocate a new ligature identifier.
+    pub(crate) fn allocate_ligature(&mut self) -> u16 {
+        self.ligature_count = self.ligature_count.wrapping_add(1).max(1);
+        self.ligature_count
+    }
+
+    /// Match glyphs following a position and return their positions.
+    ///
+    /// The glyphs skipped by the filter are not taken into account.
+    pub(crate) fn match_forward<F>(
+        &self,
+        position: usize,
+        skip: Skip,
+        count: usize,
+        check: F,
+    ) -> Option<Vec<usize>>
+    where
+        F: Fn(usize, GlyphID) -> bool,
+    {
+        let mut positions = Vec::with_capacity(count);
+        let mut position = position;
+        for i in 0..count {
+            position = self.next(position, skip)?;
+            if !check(i, self.glyphs[position].glyph_id) {
+                return None;
+            }
+            positions.push(position);
+        }
+        Some(positions)
+    }
+
+    /// Match glyphs preceding a position going backward.
+    ///
+    /// The glyphs skipped by the filter are not taken into account.
+    pub(crate) fn match_backward<F>(
+        &self,
+        position: usize,
+        skip: Skip,
+        count: usize,
+        check: F,
+    ) -> bool
+    where
+        F: Fn(usize, GlyphID) -> bool,
+    {
+        let mut position = position;
+        for i in 0..count {
+            position = match self.previous(position, skip) {
+                Some(position) => position,
+                _ => return false,
+            };
+            if !check(i, self.glyphs[position].glyph_id) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Return the position of the next glyph that is not skipped by the filter.
+    pub(crate) fn next(&self, position: usize, skip: Skip) -> Option<usize> {
+        skip.forward(&self.glyphs, position).next()
+    }
+
+    /// Return the position of the previous glyph that is not skipped by the filter.
+    pub(crate) fn previous(&self, position: usize, skip: Skip) -> Option<usize> {
+        skip.backward(&self.glyphs, position).next()
+    }
+}
+
+impl FromIterator<GlyphID> for Buffer {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = GlyphID>>(iterator: T) -> Self {
+        Self::new(iterator)
+    }
+}
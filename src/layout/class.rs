@@ -1,7 +1,11 @@
 //! The glyph class.
 
+use std::collections::BTreeMap;
+
 use truetype::GlyphID;
 
+use crate::layout::graph::{Graph, Object, Pack};
+use crate::tape::Write;
 use crate::Result;
 
 /// A class.
@@ -39,6 +43,7 @@ table! {
 }
 
 table! {
+    @write
     /// A class record.
     #[derive(Copy)]
     pub Record { // ClassRangeRecord
@@ -48,6 +53,116 @@ table! {
     }
 }
 
+impl Class {
+    /// Create an instance from glyphs sorted in ascending order and their classes.
+    pub(crate) fn from_pairs(pairs: &[(GlyphID, u16)]) -> Self {
+        let mut records: Vec<Record> = vec![];
+        for (glyph_id, index) in pairs.iter().filter(|(_, index)| *index > 0) {
+            match records.last_mut() {
+                Some(record)
+                    if record.end_glyph_id.checked_add(1) == Some(*glyph_id)
+                        && record.index == *index =>
+                {
+                    record.end_glyph_id = *glyph_id;
+                }
+                _ => records.push(Record {
+                    start_glyph_id: *glyph_id,
+                    end_glyph_id: *glyph_id,
+                    index: *index,
+                }),
+            }
+        }
+        let span = match (records.first(), records.last()) {
+            (Some(first), Some(last)) => (last.end_glyph_id - first.start_glyph_id) as usize + 1,
+            _ => 0,
+        };
+        if span > 0 && span <= 3 * records.len() {
+            let start_glyph_id = records[0].start_glyph_id;
+            let mut indices = vec![0; span];
+            for record in records.iter() {
+                for glyph_id in record.start_glyph_id..=record.end_glyph_id {
+                    indices[(glyph_id - start_glyph_id) as usize] = record.index;
+                }
+            }
+            Class::Format1(Class1 {
+                format: 1,
+                start_glyph_id,
+                glyph_count: span as u16,
+                indices,
+            })
+        } else {
+            Class::Format2(Class2 {
+                format: 2,
+                record_count: records.len() as u16,
+                records,
+            })
+        }
+    }
+
+    /// Return the class of a glyph, which is zero for glyphs that are not listed.
+    pub fn get(&self, glyph_id: GlyphID) -> u16 {
+        match self {
+            Class::Format1(value) => glyph_id
+                .checked_sub(value.start_glyph_id)
+                .and_then(|i| value.indices.get(i as usize))
+                .cloned()
+                .unwrap_or(0),
+            Class::Format2(value) => value
+                .records
+                .binary_search_by(|record| {
+                    if record.end_glyph_id < glyph_id {
+                        std::cmp::Ordering::Less
+                    } else if record.start_glyph_id > glyph_id {
+                        std::cmp::Ordering::Greater
+                    } else {
+                        std::cmp::Ordering::Equal
+                    }
+                })
+                .map(|i| value.records[i].index)
+                .unwrap_or(0),
+        }
+    }
+
+    /// Iterate over the glyphs with nonzero classes and their classes.
+    pub fn iter(&self) -> impl Iterator<Item = (GlyphID, u16)> + '_ {
+        let first = match self {
+            Class::Format1(value) => {
+                Some(value.indices.iter().enumerate().map_while(|(i, index)| {
+                    let glyph_id = value.start_glyph_id.checked_add(i.try_into().ok()?)?;
+                    Some((glyph_id, *index))
+                }))
+            }
+            _ => None,
+        };
+        let second = match self {
+            Class::Format2(value) => Some(value.records.iter().flat_map(|record| {
+                (record.start_glyph_id..=record.end_glyph_id)
+                    .map(|glyph_id| (glyph_id, record.index))
+            })),
+            _ => None,
+        };
+        first
+            .into_iter()
+            .flatten()
+            .chain(second.into_iter().flatten())
+            .filter(|(_, index)| *index > 0)
+    }
+
+    /// Return the glyphs in ascending order grouped by their nonzero classes.
+    ///
+    /// Class zero is not included, as it holds all the glyphs that are not listed.
+    pub fn invert(&self) -> BTreeMap<u16, Vec<GlyphID>> {
+        let mut classes = BTreeMap::<u16, Vec<GlyphID>>::new();
+        for (glyph_id, index) in self.iter() {
+            classes.entry(index).or_default().push(glyph_id);
+        }
+        for glyph_ids in classes.values_mut() {
+            glyph_ids.sort_unstable();
+        }
+        classes
+    }
+}
+
 impl Default for Class {
     #[inline]
     fn default() -> Self {
@@ -64,3 +179,23 @@ impl crate::value::Read for Class {
         })
     }
 }
+
+impl Pack for Class {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        match self {
+            Class::Format1(value) => {
+                object.give(&1u16)?;
+                object.give(&value.start_glyph_id)?;
+                object.give(&(value.indices.len() as u16))?;
+                object.give(&value.indices[..])?;
+            }
+            Class::Format2(value) => {
+                object.give(&2u16)?;
+                object.give(&(value.records.len() as u16))?;
+                object.give(&value.records[..])?;
+            }
+        }
+        Ok(graph.add(object))
+    }
+}
@@ -2,7 +2,12 @@
 
 use truetype::GlyphID;
 
+use crate::layout::buffer::Buffer;
+use crate::layout::graph::{Graph, Object, Pack};
+use crate::layout::lookup::{Apply, Lookups};
+use crate::layout::skip::Skip;
 use crate::layout::{Class, Coverage};
+use crate::tape::Write;
 use crate::Result;
 
 /// A contextual lookup.
@@ -367,6 +372,7 @@ table! {
 }
 
 table! {
+    @write
     /// A contextual action.
     #[derive(Copy)]
     pub Action { // SequenceLookupRecord
@@ -375,6 +381,174 @@ table! {
     }
 }
 
+impl Context {
+    /// Apply the lookups of the first matching rule at a position and return the position to
+    /// continue from if any rule matches.
+    pub(crate) fn apply<T: Apply>(
+        &self,
+        lookups: &Lookups<T>,
+        buffer: &mut Buffer,
+        position: usize,
+        skip: Skip,
+        parameter: &T::Parameter<'_>,
+        depth: usize,
+    ) -> Result<Option<usize>> {
+        match self.find(buffer, position, skip) {
+            Some((positions, actions)) => {
+                perform(lookups, buffer, positions, actions, skip, parameter, depth).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn find(
+        &self,
+        buffer: &Buffer,
+        position: usize,
+        skip: Skip,
+    ) -> Option<(Vec<usize>, &[Action])> {
+        let glyph_id = buffer.glyphs[position].glyph_id;
+        match self {
+            Self::Format1(value) => {
+                let index = value.coverage.index(glyph_id)?;
+                value
+                    .records
+                    .get(index as usize)?
+                    .records
+                    .iter()
+                    .find_map(|record| {
+                        let positions = buffer.match_forward(
+                            position,
+                            skip,
+                            record.glyph_ids.len(),
+                            |i, glyph_id| record.glyph_ids[i] == glyph_id,
+                        )?;
+                        Some((prepend(position, positions), &record.actions[..]))
+                    })
+            }
+            Self::Format2(value) => {
+                value.coverage.index(glyph_id)?;
+                let index = value.class.get(glyph_id);
+                let records = value.records.get(index as usize)?.as_ref()?;
+                records.records.iter().find_map(|record| {
+                    let positions = buffer.match_forward(
+                        position,
+                        skip,
+                        record.indices.len(),
+                        |i, glyph_id| record.indices[i] == value.class.get(glyph_id),
+                    )?;
+                    Some((prepend(position, positions), &record.actions[..]))
+                })
+            }
+            Self::Format3(value) => {
+                let (first, others) = value.coverages.split_first()?;
+                first.index(glyph_id)?;
+                let positions =
+                    buffer.match_forward(position, skip, others.len(), |i, glyph_id| {
+                        others[i].contains(glyph_id)
+                    })?;
+                Some((prepend(position, positions), &value.actions[..]))
+            }
+        }
+    }
+}
+
+impl ChainedContext {
+    /// Apply the lookups of the first matching rule at a position and return the position to
+    /// continue from if any rule matches.
+    pub(crate) fn apply<T: Apply>(
+        &self,
+        lookups: &Lookups<T>,
+        buffer: &mut Buffer,
+        position: usize,
+        skip: Skip,
+        parameter: &T::Parameter<'_>,
+        depth: usize,
+    ) -> Result<Option<usize>> {
+        match self.find(buffer, position, skip) {
+            Some((positions, actions)) => {
+                perform(lookups, buffer, positions, actions, skip, parameter, depth).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn find(
+        &self,
+        buffer: &Buffer,
+        position: usize,
+        skip: Skip,
+    ) -> Option<(Vec<usize>, &[Action])> {
+        let glyph_id = buffer.glyphs[position].glyph_id;
+        match self {
+            Self::Format1(value) => {
+                let index = value.coverage.index(glyph_id)?;
+                value
+                    .records
+                    .get(index as usize)?
+                    .records
+                    .iter()
+                    .find_map(|record| {
+                        let positions = find(
+                            buffer,
+                            position,
+                            skip,
+                            (record.backward_glyph_ids.len(), |i, glyph_id| {
+                                record.backward_glyph_ids[i] == glyph_id
+                            }),
+                            (record.glyph_ids.len(), |i, glyph_id| {
+                                record.glyph_ids[i] == glyph_id
+                            }),
+                            (record.forward_glyph_ids.len(), |i, glyph_id| {
+                                record.forward_glyph_ids[i] == glyph_id
+                            }),
+                        )?;
+                        Some((positions, &record.actions[..]))
+                    })
+            }
+            Self::Format2(value) => {
+                value.coverage.index(glyph_id)?;
+                let index = value.class.get(glyph_id);
+                let records = value.records.get(index as usize)?.as_ref()?;
+                records.records.iter().find_map(|record| {
+                    let positions = find(
+                        buffer,
+                        position,
+                        skip,
+                        (record.backward_indices.len(), |i, glyph_id| {
+                            record.backward_indices[i] == value.backward_class.get(glyph_id)
+                        }),
+                        (record.indices.len(), |i, glyph_id| {
+                            record.indices[i] == value.class.get(glyph_id)
+                        }),
+                        (record.forward_indices.len(), |i, glyph_id| {
+                            record.forward_indices[i] == value.forward_class.get(glyph_id)
+                        }),
+                    )?;
+                    Some((positions, &record.actions[..]))
+                })
+            }
+            Self::Format3(value) => {
+                let (first, others) = value.coverages.split_first()?;
+                first.index(glyph_id)?;
+                let positions = find(
+                    buffer,
+                    position,
+                    skip,
+                    (value.backward_coverages.len(), |i, glyph_id| {
+                        value.backward_coverages[i].contains(glyph_id)
+                    }),
+                    (others.len(), |i, glyph_id| others[i].contains(glyph_id)),
+                    (value.forward_coverages.len(), |i, glyph_id| {
+                        value.forward_coverages[i].contains(glyph_id)
+                    }),
+                )?;
+                Some((positions, &value.actions[..]))
+            }
+        }
+    }
+}
+
 impl crate::value::Read for Context {
     fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
         Ok(match tape.peek::<u16>()? {
@@ -398,3 +572,236 @@ impl crate::value::Read for ChainedContext {
         })
     }
 }
+
+impl Pack for Context {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        match self {
+            Self::Format1(value) => {
+                object.give(&1u16)?;
+                object.link(value.coverage.pack(graph)?)?;
+                object.give(&(value.records.len() as u16))?;
+                for record in value.records.iter() {
+                    object.link(record.pack(graph)?)?;
+                }
+            }
+            Self::Format2(value) => {
+                object.give(&2u16)?;
+                object.link(value.coverage.pack(graph)?)?;
+                object.link(value.class.pack(graph)?)?;
+                object.give(&(value.records.len() as u16))?;
+                for record in value.records.iter() {
+                    object.link_maybe(
+                        record
+                            .as_ref()
+                            .map(|record| record.pack(graph))
+                            .transpose()?,
+                    )?;
+                }
+            }
+            Self::Format3(value) => {
+                object.give(&3u16)?;
+                object.give(&(value.coverages.len() as u16))?;
+                object.give(&(value.actions.len() as u16))?;
+                for coverage in value.coverages.iter() {
+                    object.link(coverage.pack(graph)?)?;
+                }
+                object.give(&value.actions[..])?;
+            }
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for ChainedContext {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        match self {
+            Self::Format1(value) => {
+                object.give(&1u16)?;
+                object.link(value.coverage.pack(graph)?)?;
+                object.give(&(value.records.len() as u16))?;
+                for record in value.records.iter() {
+                    object.link(record.pack(graph)?)?;
+                }
+            }
+            Self::Format2(value) => {
+                object.give(&2u16)?;
+                object.link(value.coverage.pack(graph)?)?;
+                object.link(value.backward_class.pack(graph)?)?;
+                object.link(value.class.pack(graph)?)?;
+                object.link(value.forward_class.pack(graph)?)?;
+                object.give(&(value.records.len() as u16))?;
+                for record in value.records.iter() {
+                    object.link_maybe(
+                        record
+                            .as_ref()
+                            .map(|record| record.pack(graph))
+                            .transpose()?,
+                    )?;
+                }
+            }
+            Self::Format3(value) => {
+                object.give(&3u16)?;
+                for coverages in [
+                    &value.backward_coverages,
+                    &value.coverages,
+                    &value.forward_coverages,
+                ] {
+                    object.give(&(coverages.len() as u16))?;
+                    for coverage in coverages.iter() {
+                        object.link(coverage.pack(graph)?)?;
+                    }
+                }
+                object.give(&(value.actions.len() as u16))?;
+                object.give(&value.actions[..])?;
+            }
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for Record {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&(self.glyph_ids.len() as u16 + 1))?;
+        object.give(&(self.actions.len() as u16))?;
+        object.give(&self.glyph_ids[..])?;
+        object.give(&self.actions[..])?;
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for ClassRecord {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&(self.indices.len() as u16 + 1))?;
+        object.give(&(self.actions.len() as u16))?;
+        object.give(&self.indices[..])?;
+        object.give(&self.actions[..])?;
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for ChainedRecord {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&(self.backward_glyph_ids.len() as u16))?;
+        object.give(&self.backward_glyph_ids[..])?;
+        object.give(&(self.glyph_ids.len() as u16 + 1))?;
+        object.give(&self.glyph_ids[..])?;
+        object.give(&(self.forward_glyph_ids.len() as u16))?;
+        object.give(&self.forward_glyph_ids[..])?;
+        object.give(&(self.actions.len() as u16))?;
+        object.give(&self.actions[..])?;
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for ChainedClassRecord {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&(self.backward_indices.len() as u16))?;
+        object.give(&self.backward_indices[..])?;
+        object.give(&(self.indices.len() as u16 + 1))?;
+        object.give(&self.indices[..])?;
+        object.give(&(self.forward_indices.len() as u16))?;
+        object.give(&self.forward_indices[..])?;
+        object.give(&(self.actions.len() as u16))?;
+        object.give(&self.actions[..])?;
+        Ok(graph.add(object))
+    }
+}
+
+macro_rules! implement {
+    ($($type:ident,)*) => {
+        $(
+            impl Pack for $type {
+                fn pack(&self, graph: &mut Graph) -> Result<usize> {
+                    let mut object = Object::default();
+                    object.give(&(self.records.len() as u16))?;
+                    for record in self.records.iter() {
+                        object.link(record.pack(graph)?)?;
+                    }
+                    Ok(graph.add(object))
+                }
+            }
+        )*
+    };
+}
+
+implement! {
+    ChainedClassRecords,
+    ChainedRecords,
+    ClassRecords,
+    Records,
+}
+
+/// Match a chained context given the number of glyphs and a check for the backtrack, input
+/// excluding the first glyph, and lookahead sequences and return the input positions.
+fn find<F, G, H>(
+    buffer: &Buffer,
+    position: usize,
+    skip: Skip,
+    (backward_count, backward_check): (usize, F),
+    (count, check): (usize, G),
+    (forward_count, forward_check): (usize, H),
+) -> Option<Vec<usize>>
+where
+    F: Fn(usize, GlyphID) -> bool,
+    G: Fn(usize, GlyphID) -> bool,
+    H: Fn(usize, GlyphID) -> bool,
+{
+    let positions = buffer.match_forward(position, skip, count, check)?;
+    let last = positions.last().cloned().unwrap_or(position);
+    buffer.match_forward(last, skip, forward_count, forward_check)?;
+    if !buffer.match_backward(position, skip, backward_count, backward_check) {
+        return None;
+    }
+    Some(prepend(position, positions))
+}
+
+/// Apply the lookups of matched actions and return the position following the input sequence.
+fn perform<T: Apply>(
+    lookups: &Lookups<T>,
+    buffer: &mut Buffer,
+    mut positions: Vec<usize>,
+    actions: &[Action],
+    skip: Skip,
+    parameter: &T::Parameter<'_>,
+    depth: usize,
+) -> Result<usize> {
+    let mut end = positions.last().cloned().unwrap_or(0) + 1;
+    for action in actions.iter() {
+        let i = action.position_index as usize;
+        if i >= positions.len() {
+            continue;
+        }
+        let length = buffer.len();
+        lookups.apply_nested(
+            buffer,
+            action.lookup_index,
+            positions[i],
+            skip,
+            parameter,
+            depth + 1,
+        )?;
+        // Lookups such as multiple and ligature substitutions change the number of glyphs, and
+        // the positions that follow are shifted accordingly.
+        let delta = buffer.len() as isize - length as isize;
+        if delta == 0 {
+            continue;
+        }
+        let start = positions[i];
+        for position in positions[i + 1..].iter_mut() {
+            *position = ((*position as isize + delta).max(start as isize + 1)) as usize;
+        }
+        end = ((end as isize + delta).max(start as isize + 1)) as usize;
+    }
+    Ok(end.min(buffer.len()))
+}
+
+fn prepend(position: usize, mut positions: Vec<usize>) -> Vec<usize> {
+    positions.insert(0, position);
+    positions
+}
@@ -1,5 +1,8 @@
 //! The adjustment correction.
 
+use crate::layout::graph::{Graph, Object, Pack};
+use crate::tape::Write;
+use crate::variations::item::Store;
 use crate::Result;
 
 /// A correction.
@@ -31,6 +34,77 @@ table! {
     }
 }
 
+/// An environment for resolving corrections.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Environment<'l> {
+    /// The size in pixels per em if rendering at a particular size.
+    pub size: Option<u16>,
+    /// The number of units per em, which converts device corrections into font units.
+    pub units_per_em: u16,
+    /// The item variation store of the glyph definition if any.
+    pub store: Option<&'l Store>,
+    /// The normalized axis coordinates.
+    pub coordinates: &'l [f32],
+}
+
+impl Correction {
+    /// Compute the delta in an environment.
+    ///
+    /// The delta is in font units. Device corrections apply only when rendering at a particular
+    /// size, and their deltas in pixels are scaled by the number of units per em. Variation
+    /// corrections apply only when there is an item variation store.
+    pub fn delta(&self, environment: &Environment) -> f32 {
+        match self {
+            Self::Device(value) => match environment.size {
+                Some(size) if size > 0 => {
+                    value.delta(size) as f32 * environment.units_per_em as f32 / size as f32
+                }
+                _ => 0.0,
+            },
+            Self::Variation(value) => environment
+                .store
+                .map_or(0.0, |store| value.delta(store, environment.coordinates)),
+        }
+    }
+}
+
+impl Device {
+    /// Return the delta in pixels for a size in pixels per em.
+    ///
+    /// Sizes outside the range of the correction have no delta.
+    pub fn delta(&self, size: u16) -> i16 {
+        if size < self.start_size || size > self.end_size || !(1..=3).contains(&self.format) {
+            return 0;
+        }
+        let bit_count = 1usize << self.format;
+        let per_short_count = 16 / bit_count;
+        let index = (size - self.start_size) as usize;
+        let value = match self.deltas.get(index / per_short_count) {
+            Some(value) => *value as usize,
+            _ => return 0,
+        };
+        let shift = 16 - bit_count * (index % per_short_count + 1);
+        let value = (value >> shift) & ((1 << bit_count) - 1);
+        if value >= 1 << (bit_count - 1) {
+            (value as i16) - (1 << bit_count)
+        } else {
+            value as i16
+        }
+    }
+}
+
+impl Variation {
+    /// Compute the delta given an item variation store at given normalized axis coordinates.
+    ///
+    /// Items absent from the store have no delta.
+    #[inline]
+    pub fn delta(&self, store: &Store, coordinates: &[f32]) -> f32 {
+        store
+            .delta(self.outer_index, self.inner_index, coordinates)
+            .unwrap_or(0.0)
+    }
+}
+
 impl crate::value::Read for Correction {
     fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
         Ok(match tape.peek::<(u32, u16)>()?.1 {
@@ -61,7 +135,7 @@ impl crate::value::Read for Device {
         }
         let count = (end_size - start_size) as usize + 1;
         let bit_count = (1 << format as usize) * count;
-        let short_count = (bit_count + 16 - bit_count % 16) >> 4;
+        let short_count = (bit_count + 15) >> 4;
         let deltas = tape.take_given(short_count)?;
         Ok(Device {
             start_size,
@@ -71,3 +145,23 @@ impl crate::value::Read for Device {
         })
     }
 }
+
+impl Pack for Correction {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        match self {
+            Correction::Device(value) => {
+                object.give(&value.start_size)?;
+                object.give(&value.end_size)?;
+                object.give(&value.format)?;
+                object.give(&value.deltas[..])?;
+            }
+            Correction::Variation(value) => {
+                object.give(&value.outer_index)?;
+                object.give(&value.inner_index)?;
+                object.give(&0x8000u16)?;
+            }
+        }
+        Ok(graph.add(object))
+    }
+}
@@ -2,6 +2,8 @@
 
 use truetype::GlyphID;
 
+use crate::layout::graph::{Graph, Object, Pack};
+use crate::tape::Write;
 use crate::Result;
 
 /// A coverage.
@@ -38,6 +40,7 @@ table! {
 }
 
 table! {
+    @write
     /// A coverage record.
     #[derive(Copy)]
     pub Record { // RangeRecord
@@ -47,6 +50,164 @@ table! {
     }
 }
 
+impl Coverage {
+    /// Return the coverage index of a glyph if covered.
+    pub fn index(&self, glyph_id: GlyphID) -> Option<u16> {
+        match self {
+            Coverage::Format1(value) => value
+                .glyph_ids
+                .binary_search(&glyph_id)
+                .ok()
+                .map(|index| index as u16),
+            Coverage::Format2(value) => value
+                .records
+                .binary_search_by(|record| {
+                    if record.end_glyph_id < glyph_id {
+                        std::cmp::Ordering::Less
+                    } else if record.start_glyph_id > glyph_id {
+                        std::cmp::Ordering::Greater
+                    } else {
+                        std::cmp::Ordering::Equal
+                    }
+                })
+                .ok()
+                .and_then(|i| {
+                    let record = &value.records[i];
+                    record.index.checked_add(glyph_id - record.start_glyph_id)
+                }),
+        }
+    }
+
+    /// Check if a glyph is covered.
+    #[inline]
+    pub fn contains(&self, glyph_id: GlyphID) -> bool {
+        self.index(glyph_id).is_some()
+    }
+
+    /// Iterate over the covered glyphs and their coverage indices.
+    pub fn iter(&self) -> impl Iterator<Item = (GlyphID, u16)> + '_ {
+        let first = match self {
+            Coverage::Format1(value) => Some(
+                value
+                    .glyph_ids
+                    .iter()
+                    .enumerate()
+                    .map(|(i, glyph_id)| (*glyph_id, i as u16)),
+            ),
+            _ => None,
+        };
+        let second = match self {
+            Coverage::Format2(value) => Some(value.records.iter().flat_map(|record| {
+                (record.start_glyph_id..=record.end_glyph_id).filter_map(move |glyph_id| {
+                    let index = record.index.checked_add(glyph_id - record.start_glyph_id)?;
+                    Some((glyph_id, index))
+                })
+            })),
+            _ => None,
+        };
+        first
+            .into_iter()
+            .flatten()
+            .chain(second.into_iter().flatten())
+    }
+
+    /// Return the number of covered glyphs.
+    pub fn len(&self) -> usize {
+        match self {
+            Coverage::Format1(value) => value.glyph_ids.len(),
+            Coverage::Format2(value) => value
+                .records
+                .iter()
+                .map(|record| {
+                    (record.end_glyph_id as usize + 1)
+                        .saturating_sub(record.start_glyph_id as usize)
+                })
+                .sum(),
+        }
+    }
+
+    /// Check if no glyph is covered.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the glyphs covered by either coverage.
+    pub fn union(&self, other: &Coverage) -> Coverage {
+        self.iter()
+            .chain(other.iter())
+            .map(|(glyph_id, _)| glyph_id)
+            .collect()
+    }
+
+    /// Return the glyphs covered by both coverages.
+    pub fn intersection(&self, other: &Coverage) -> Coverage {
+        self.iter()
+            .map(|(glyph_id, _)| glyph_id)
+            .filter(|glyph_id| other.contains(*glyph_id))
+            .collect()
+    }
+
+    /// Create an instance from glyphs sorted in ascending order.
+    pub(crate) fn from_glyph_ids(glyph_ids: &[GlyphID]) -> Self {
+        let mut records: Vec<Record> = vec![];
+        for (i, glyph_id) in glyph_ids.iter().enumerate() {
+            match records.last_mut() {
+                Some(record) if record.end_glyph_id.checked_add(1) == Some(*glyph_id) => {
+                    record.end_glyph_id = *glyph_id;
+                }
+                _ => records.push(Record {
+                    start_glyph_id: *glyph_id,
+                    end_glyph_id: *glyph_id,
+                    index: i as u16,
+                }),
+            }
+        }
+        if 3 * records.len() < glyph_ids.len() {
+            Coverage::Format2(Coverage2 {
+                format: 2,
+                record_count: records.len() as u16,
+                records,
+            })
+        } else {
+            Coverage::Format1(Coverage1 {
+                format: 1,
+                glyph_count: glyph_ids.len() as u16,
+                glyph_ids: glyph_ids.to_vec(),
+            })
+        }
+    }
+
+    /// Return the glyphs in the order of their coverage indices.
+    pub(crate) fn glyph_ids(&self) -> Vec<GlyphID> {
+        match self {
+            Coverage::Format1(value) => value.glyph_ids.clone(),
+            Coverage::Format2(value) => {
+                let mut records = value.records.clone();
+                records.sort_by_key(|record| record.index);
+                records
+                    .iter()
+                    .flat_map(|record| record.start_glyph_id..=record.end_glyph_id)
+                    .collect()
+            }
+        }
+    }
+
+    /// Split the coverage into two halves and return the index of the first glyph of the second.
+    pub(crate) fn split(&self) -> Option<(usize, Coverage, Coverage)> {
+        let glyph_ids = self.glyph_ids();
+        if glyph_ids.len() < 2 {
+            return None;
+        }
+        let middle = glyph_ids.len() / 2;
+        Some((
+            middle,
+            Coverage::from_glyph_ids(&glyph_ids[..middle]),
+            Coverage::from_glyph_ids(&glyph_ids[middle..]),
+        ))
+    }
+}
+
 impl Default for Coverage {
     #[inline]
     fn default() -> Self {
@@ -54,6 +215,16 @@ impl Default for Coverage {
     }
 }
 
+impl FromIterator<GlyphID> for Coverage {
+    /// Create an instance covering the glyphs in ascending order.
+    fn from_iter<T: IntoIterator<Item = GlyphID>>(iterator: T) -> Self {
+        let mut glyph_ids = iterator.into_iter().collect::<Vec<_>>();
+        glyph_ids.sort_unstable();
+        glyph_ids.dedup();
+        Coverage::from_glyph_ids(&glyph_ids)
+    }
+}
+
 impl crate::value::Read for Coverage {
     fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
         Ok(match tape.peek::<u16>()? {
@@ -63,3 +234,22 @@ impl crate::value::Read for Coverage {
         })
     }
 }
+
+impl Pack for Coverage {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        match self {
+            Coverage::Format1(value) => {
+                object.give(&1u16)?;
+                object.give(&(value.glyph_ids.len() as u16))?;
+                object.give(&value.glyph_ids[..])?;
+            }
+            Coverage::Format2(value) => {
+                object.give(&2u16)?;
+                object.give(&(value.records.len() as u16))?;
+                object.give(&value.records[..])?;
+            }
+        }
+        Ok(graph.add(object))
+    }
+}
@@ -1,5 +1,11 @@
+use std::collections::BTreeSet;
+
+use truetype::Tag;
+
 use crate::layout::feature::Variations;
-use crate::layout::{Features, Lookups, Scripts};
+use crate::layout::graph::{Graph, Object, Pack, Subtable};
+use crate::layout::{Feature, Features, Language, Lookups, Script, Scripts};
+use crate::tape::Write;
 use crate::Result;
 
 /// A layout directory.
@@ -19,6 +25,75 @@ pub struct Directory<T> {
     pub variations: Option<Variations>,
 }
 
+impl<T> Directory<T> {
+    /// Return the indices of the lookups of features for a script and language.
+    ///
+    /// The default script is used if the script is not present, and the default language is
+    /// used if the language is not given or not present. The required feature of the language is
+    /// always included. The indices are sorted and deduplicated, which is the order the lookups
+    /// are to be applied in.
+    pub fn lookup_indices<I>(
+        &self,
+        script: Script,
+        language: Option<Language>,
+        features: I,
+    ) -> Vec<u16>
+    where
+        I: IntoIterator<Item = Feature>,
+    {
+        let record = match self
+            .scripts
+            .get(script)
+            .or_else(|| self.scripts.get(Script::Default))
+        {
+            Some(record) => record,
+            _ => return vec![],
+        };
+        let record = match language
+            .and_then(|language| record.get(language))
+            .or(record.default_language.as_ref())
+        {
+            Some(record) => record,
+            _ => return vec![],
+        };
+        let tags = features.into_iter().map(Tag::from).collect::<BTreeSet<_>>();
+        let mut indices = BTreeSet::new();
+        if let Some(record) = self
+            .features
+            .records
+            .get(record.required_feature_index as usize)
+        {
+            indices.extend(record.lookup_indices.iter().cloned());
+        }
+        for index in record.feature_indices.iter().map(|index| *index as usize) {
+            match (
+                self.features.headers.get(index),
+                self.features.records.get(index),
+            ) {
+                (Some(header), Some(record)) if tags.contains(&header.tag) => {
+                    indices.extend(record.lookup_indices.iter().cloned());
+                }
+                _ => {}
+            }
+        }
+        indices.into_iter().collect()
+    }
+
+    /// Replace the features with the ones in effect at given normalized axis coordinates and
+    /// drop the feature variations.
+    pub fn substitute(&mut self, coordinates: &[f32]) {
+        if let Some(variations) = self.variations.take() {
+            self.features.records = variations
+                .substitute(&self.features, coordinates)
+                .into_iter()
+                .cloned()
+                .collect();
+        }
+        self.minor_version = 0;
+        self.variation_offset = 0;
+    }
+}
+
 impl<U> crate::value::Read for Directory<U>
 where
     U: crate::walue::Read<'static, Parameter = u16>,
@@ -39,18 +114,28 @@ where
             _ => 0,
         };
         #[cfg(not(feature = "ignore-incomplete-directories"))]
-        let (scripts, features, lookups) = (
+        let (scripts, features, lookups): (Scripts, Features, Lookups<U>) = (
             jump_take!(@unwrap tape, position, script_offset),
             jump_take!(@unwrap tape, position, feature_offset),
             jump_take!(@unwrap tape, position, lookup_offset),
         );
         #[cfg(feature = "ignore-incomplete-directories")]
-        let (scripts, features, lookups) = (
+        let (scripts, features, lookups): (Scripts, Features, Lookups<U>) = (
             jump_take_maybe!(@unwrap tape, position, script_offset).unwrap_or_default(),
             jump_take_maybe!(@unwrap tape, position, feature_offset).unwrap_or_default(),
             jump_take_maybe!(@unwrap tape, position, lookup_offset).unwrap_or_default(),
         );
-        let variations = jump_take_maybe!(@unwrap tape, position, variation_offset);
+        let variations = if variation_offset > 0 {
+            Some(jump_take_given!(
+                @unwrap
+                tape,
+                position,
+                variation_offset,
+                &features.headers[..]
+            ))
+        } else {
+            None
+        };
         Ok(Directory {
             major_version,
             minor_version,
@@ -65,3 +150,26 @@ where
         })
     }
 }
+
+impl<T: Subtable> Pack for Directory<T> {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        let minor_version = if self.variations.is_some() {
+            1
+        } else {
+            self.minor_version
+        };
+        object.give(&1u16)?;
+        object.give(&minor_version)?;
+        object.link(self.scripts.pack(graph)?)?;
+        object.link(self.features.pack(graph)?)?;
+        object.link(self.lookups.pack(graph)?)?;
+        if minor_version == 1 {
+            match self.variations {
+                Some(ref variations) => object.link_long(variations.pack(graph)?)?,
+                _ => object.give(&0u32)?,
+            }
+        }
+        Ok(graph.add(object))
+    }
+}
@@ -1,6 +1,10 @@
 //! The feature list.
 
-use truetype::Tag;
+use truetype::{q16, Tag};
+
+use crate::layout::graph::{Graph, Object, Pack};
+use crate::tape::Write;
+use crate::Result;
 
 table! {
     @position
@@ -13,7 +17,14 @@ table! {
         },
 
         records (Vec<Record>) |this, tape, position| {
-            jump_take!(tape, position, this.count, i => this.headers[i].offset)
+            Ok(jump_take_given!(
+                @unwrap
+                tape,
+                position,
+                this.count,
+                i => this.headers[i].offset,
+                Some(this.headers[i].tag)
+            ))
         },
     }
 }
@@ -28,34 +39,336 @@ table! {
 }
 
 table! {
-    @position
+    @define
     /// A feature record.
     pub Record { // Feature
         parameter_offset   (u16), // featureParamsOffset
         lookup_index_count (u16), // lookupIndexCount
+        lookup_indices     (Vec<u16>), // lookupListIndices
 
-        lookup_indices (Vec<u16>) |this, tape, _| { // lookupListIndices
-            tape.take_given(this.lookup_index_count as usize)
-        },
+        parameters (Option<Parameters>),
+    }
+}
 
-        parameters (Option<Vec<u8>>) |this, tape, position| {
-            if this.parameter_offset > 0 {
-                tape.jump(position + this.parameter_offset as u64)?;
-                Ok(Some(tape.take_bytes(0)?))
-            } else {
-                Ok(None)
-            }
+/// Feature parameters.
+#[derive(Clone, Debug)]
+pub enum Parameters {
+    /// Parameters of the optical-size feature.
+    Size(Size),
+    /// Parameters of a stylistic-set feature.
+    StylisticSet(StylisticSet),
+    /// Parameters of a character-variant feature.
+    CharacterVariant(CharacterVariant),
+}
+
+table! {
+    @write
+    /// Parameters of the optical-size feature.
+    #[derive(Copy)]
+    pub Size { // FeatureParamsSize
+        design_size       (u16), // designSize
+        subfamily_id      (u16), // subfamilyIdentifier
+        subfamily_name_id (u16), // subfamilyNameID
+        range_start       (u16), // recommendedUsageRangeStart
+        range_end         (u16), // recommendedUsageRangeEnd
+    }
+}
+
+table! {
+    @write
+    /// Parameters of a stylistic-set feature.
+    #[derive(Copy)]
+    pub StylisticSet { // FeatureParamsStylisticSet
+        version    (u16) = { 0 }, // version
+        ui_name_id (u16), // UINameID
+    }
+}
+
+table! {
+    /// Parameters of a character-variant feature.
+    pub CharacterVariant { // FeatureParamsCharacterVariants
+        format                  (u16) = { 0 }, // format
+        label_name_id           (u16), // featUiLabelNameId
+        tooltip_name_id         (u16), // featUiTooltipTextNameId
+        sample_name_id          (u16), // sampleTextNameId
+        parameter_count         (u16), // numNamedParameters
+        first_parameter_name_id (u16), // firstParamUiLabelNameId
+        character_count         (u16), // charCount
+
+        characters (Vec<u32>) |this, tape| { // character
+            (0..this.character_count)
+                .map(|_| {
+                    let data = tape.take_bytes(3)?;
+                    Ok(u32::from_be_bytes([0, data[0], data[1], data[2]]))
+                })
+                .collect()
         },
     }
 }
 
 table! {
+    @define
     /// Feature variations.
-    #[derive(Copy)]
     pub Variations { // FeatureVariations
-        major_version (u16) = { 1 }, // MajorVersion
-        minor_version (u16) = { 0 }, // MinorVersion
-        count         (u32), // FeatureVariationRecordsCount
+        major_version (u16                 ), // MajorVersion
+        minor_version (u16                 ), // MinorVersion
+        count         (u32                 ), // FeatureVariationRecordsCount
+        headers       (Vec<VariationHeader>), // FeatureVariationRecords
+        records       (Vec<VariationRecord>),
+    }
+}
+
+table! {
+    /// A feature-variation header.
+    #[derive(Copy)]
+    pub VariationHeader { // FeatureVariationRecord
+        condition_offset    (u32), // ConditionSetOffset
+        substitution_offset (u32), // FeatureTableSubstitutionOffset
+    }
+}
+
+table! {
+    @define
+    /// A feature-variation record.
+    pub VariationRecord {
+        conditions    (Option<Conditions>   ),
+        substitutions (Option<Substitutions>),
+    }
+}
+
+table! {
+    @position
+    /// Conditions.
+    pub Conditions { // ConditionSet
+        count (u16), // ConditionCount
+
+        offsets (Vec<u32>) |this, tape, _| { // ConditionOffsets
+            tape.take_given(this.count as usize)
+        },
+
+        records (Vec<Condition>) |this, tape, position| {
+            jump_take!(tape, position, this.count, this.offsets)
+        },
+    }
+}
+
+/// A condition.
+#[derive(Clone, Debug)]
+pub enum Condition {
+    /// Format 1.
+    Format1(Condition1),
+}
+
+table! {
+    @write
+    /// A condition in format 1.
+    #[derive(Copy)]
+    pub Condition1 { // ConditionFormat1
+        format     (u16) = { 1 }, // Format
+        axis_index (u16), // AxisIndex
+        min_value  (q16), // FilterRangeMinValue
+        max_value  (q16), // FilterRangeMaxValue
+    }
+}
+
+table! {
+    @define
+    /// Feature substitutions.
+    pub Substitutions { // FeatureTableSubstitution
+        major_version (u16                    ), // MajorVersion
+        minor_version (u16                    ), // MinorVersion
+        count         (u16                    ), // SubstitutionCount
+        headers       (Vec<SubstitutionHeader>), // SubstitutionRecords
+        records       (Vec<Record>            ),
+    }
+}
+
+table! {
+    /// A feature-substitution header.
+    #[derive(Copy)]
+    pub SubstitutionHeader { // FeatureTableSubstitutionRecord
+        feature_index (u16), // FeatureIndex
+        offset        (u32), // AlternateFeatureOffset
+    }
+}
+
+impl Variations {
+    /// Return the feature records in effect at given normalized axis coordinates.
+    ///
+    /// The first record whose conditions are met substitutes its features. If no record
+    /// matches, the features are returned as they are.
+    pub fn substitute<'l>(
+        &'l self,
+        features: &'l Features,
+        coordinates: &[f32],
+    ) -> Vec<&'l Record> {
+        let mut records = features.records.iter().collect::<Vec<_>>();
+        let record = self.records.iter().find(|record| match record.conditions {
+            Some(ref conditions) => conditions.check(coordinates),
+            _ => true,
+        });
+        if let Some(Some(substitutions)) = record.map(|record| &record.substitutions) {
+            for (header, record) in substitutions
+                .headers
+                .iter()
+                .zip(substitutions.records.iter())
+            {
+                if let Some(value) = records.get_mut(header.feature_index as usize) {
+                    *value = record;
+                }
+            }
+        }
+        records
+    }
+}
+
+impl Conditions {
+    /// Check if all conditions are met at given normalized axis coordinates.
+    pub fn check(&self, coordinates: &[f32]) -> bool {
+        self.records.iter().all(|record| record.check(coordinates))
+    }
+}
+
+impl Condition {
+    /// Check if the condition is met at given normalized axis coordinates.
+    ///
+    /// Missing coordinates are taken to be zero.
+    pub fn check(&self, coordinates: &[f32]) -> bool {
+        match self {
+            Self::Format1(value) => {
+                let coordinate = coordinates
+                    .get(value.axis_index as usize)
+                    .cloned()
+                    .unwrap_or(0.0);
+                f32::from(value.min_value) <= coordinate && coordinate <= f32::from(value.max_value)
+            }
+        }
+    }
+}
+
+impl crate::value::Read for Condition {
+    fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
+        Ok(match tape.peek::<u16>()? {
+            1 => Self::Format1(tape.take()?),
+            value => raise!("found an unknown format of the condition ({value})"),
+        })
+    }
+}
+
+impl Parameters {
+    fn read<T: crate::tape::Read>(tape: &mut T, tag: Tag) -> Result<Option<Self>> {
+        Ok(match &*tag {
+            b"size" => Some(Self::Size(tape.take()?)),
+            [b's', b's', b'0'..=b'9', b'0'..=b'9'] => Some(Self::StylisticSet(tape.take()?)),
+            [b'c', b'v', b'0'..=b'9', b'0'..=b'9'] => Some(Self::CharacterVariant(tape.take()?)),
+            _ => None,
+        })
+    }
+}
+
+impl CharacterVariant {
+    /// Iterate over the name identifiers of the named parameters.
+    pub fn parameter_name_ids(&self) -> impl Iterator<Item = u16> {
+        let start = self.first_parameter_name_id;
+        (0..self.parameter_count).map(move |i| start.wrapping_add(i))
+    }
+}
+
+impl crate::walue::Read<'static> for Record {
+    type Parameter = Option<Tag>;
+
+    fn read<T: crate::tape::Read>(tape: &mut T, tag: Self::Parameter) -> Result<Self> {
+        let position = tape.position()?;
+        let parameter_offset = tape.take()?;
+        let lookup_index_count = tape.take()?;
+        let lookup_indices = tape.take_given(lookup_index_count as usize)?;
+        let parameters = match tag {
+            Some(tag) if parameter_offset > 0 => {
+                tape.jump(position + parameter_offset as u64)?;
+                Parameters::read(tape, tag)?
+            }
+            _ => None,
+        };
+        Ok(Self {
+            parameter_offset,
+            lookup_index_count,
+            lookup_indices,
+            parameters,
+        })
+    }
+}
+
+impl<'l> crate::walue::Read<'l> for Variations {
+    type Parameter = &'l [Header];
+
+    fn read<T: crate::tape::Read>(tape: &mut T, headers: Self::Parameter) -> Result<Self> {
+        let position = tape.position()?;
+        let major_version = tape.take()?;
+        let minor_version = tape.take()?;
+        match (major_version, minor_version) {
+            (1, 0) => {}
+            value => raise!("found an unknown version of feature variations {value:?}"),
+        }
+        let count = tape.take()?;
+        let variation_headers: Vec<VariationHeader> = tape.take_given(count as usize)?;
+        let mut records = Vec::with_capacity(variation_headers.len());
+        for header in variation_headers.iter() {
+            let conditions = jump_take_maybe!(@unwrap tape, position, header.condition_offset);
+            let substitutions = if header.substitution_offset > 0 {
+                Some(jump_take_given!(
+                    @unwrap tape,
+                    position,
+                    header.substitution_offset,
+                    headers
+                ))
+            } else {
+                None
+            };
+            records.push(VariationRecord {
+                conditions,
+                substitutions,
+            });
+        }
+        Ok(Self {
+            major_version,
+            minor_version,
+            count,
+            headers: variation_headers,
+            records,
+        })
+    }
+}
+
+impl<'l> crate::walue::Read<'l> for Substitutions {
+    type Parameter = &'l [Header];
+
+    fn read<T: crate::tape::Read>(tape: &mut T, headers: Self::Parameter) -> Result<Self> {
+        let position = tape.position()?;
+        let major_version = tape.take()?;
+        let minor_version = tape.take()?;
+        match (major_version, minor_version) {
+            (1, 0) => {}
+            value => raise!("found an unknown version of feature substitutions {value:?}"),
+        }
+        let count = tape.take()?;
+        let substitution_headers: Vec<SubstitutionHeader> = tape.take_given(count as usize)?;
+        let records = jump_take_given!(
+            @unwrap
+            tape,
+            position,
+            count,
+            i => substitution_headers[i].offset,
+            headers
+                .get(substitution_headers[i].feature_index as usize)
+                .map(|header| header.tag)
+        );
+        Ok(Self {
+            major_version,
+            minor_version,
+            count,
+            headers: substitution_headers,
+            records,
+        })
     }
 }
 
@@ -71,6 +384,108 @@ impl Features {
     }
 }
 
+impl Pack for Features {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&(self.records.len() as u16))?;
+        for (header, record) in self.headers.iter().zip(self.records.iter()) {
+            object.give(&header.tag)?;
+            object.link(record.pack(graph)?)?;
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for Record {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        let parameters = match self.parameters {
+            Some(ref parameters) => Some(parameters.pack(graph)?),
+            _ => None,
+        };
+        object.link_maybe(parameters)?;
+        object.give(&(self.lookup_indices.len() as u16))?;
+        object.give(&self.lookup_indices[..])?;
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for Parameters {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        match self {
+            Self::Size(value) => object.give(value)?,
+            Self::StylisticSet(value) => object.give(value)?,
+            Self::CharacterVariant(value) => {
+                object.give(&0u16)?;
+                object.give(&value.label_name_id)?;
+                object.give(&value.tooltip_name_id)?;
+                object.give(&value.sample_name_id)?;
+                object.give(&value.parameter_count)?;
+                object.give(&value.first_parameter_name_id)?;
+                object.give(&(value.characters.len() as u16))?;
+                for character in value.characters.iter() {
+                    object.give_bytes(&character.to_be_bytes()[1..])?;
+                }
+            }
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for Variations {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&1u16)?;
+        object.give(&0u16)?;
+        object.give(&(self.records.len() as u32))?;
+        for record in self.records.iter() {
+            object.link_long_maybe(pack_maybe(graph, &record.conditions)?)?;
+            object.link_long_maybe(pack_maybe(graph, &record.substitutions)?)?;
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for Conditions {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&(self.records.len() as u16))?;
+        for record in self.records.iter() {
+            object.link_long(record.pack(graph)?)?;
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for Condition {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        match self {
+            Self::Format1(value) => object.give(value)?,
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for Substitutions {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&1u16)?;
+        object.give(&0u16)?;
+        object.give(&(self.records.len() as u16))?;
+        for (header, record) in self.headers.iter().zip(self.records.iter()) {
+            object.give(&header.feature_index)?;
+            object.link_long(record.pack(graph)?)?;
+        }
+        Ok(graph.add(object))
+    }
+}
+
+fn pack_maybe<T: Pack>(graph: &mut Graph, value: &Option<T>) -> Result<Option<usize>> {
+    value.as_ref().map(|value| value.pack(graph)).transpose()
+}
+
 macro_rules! implement {
     ($($tag:literal => $name:literal => $variant:ident,)*) => (
         /// A feature.
@@ -0,0 +1,445 @@
+//! The object graph.
+//!
+//! Layout tables are written by packing them into a graph of objects connected by offsets, which
+//! are recomputed once the objects have been placed.
+
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::layout::{ChainedContext, Class, Context, Correction, Coverage, Directory};
+use crate::layout::{Features, Lookups, Scripts};
+use crate::Result;
+
+const ROUND_COUNT: usize = 16;
+
+/// A type that can be packed into a graph.
+pub trait Pack {
+    /// Pack the value and return the index of the resulting object.
+    fn pack(&self, graph: &mut Graph) -> Result<usize>;
+}
+
+/// A type of lookup subtables.
+pub trait Subtable: Pack + Clone + Sized {
+    /// The lookup type of extension subtables.
+    const EXTENSION: u16;
+
+    /// Split the subtable into two if possible.
+    fn split(&self) -> Option<(Self, Self)>;
+}
+
+/// A graph.
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    objects: Vec<Object>,
+    indices: HashMap<Object, usize>,
+    lookups: Vec<(usize, u16)>,
+}
+
+/// An object of a graph.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Object {
+    /// The data with links zeroed out.
+    pub data: Vec<u8>,
+    /// The links to other objects.
+    pub links: Vec<Link>,
+}
+
+/// A link of an object.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Link {
+    /// The position of the offset in the data of the object.
+    pub position: usize,
+    /// The size of the offset in bytes.
+    pub size: usize,
+    /// The index of the target object.
+    pub target: usize,
+}
+
+impl Graph {
+    /// Add an object and return its index.
+    ///
+    /// Objects with the same data and links are stored once and shared.
+    pub fn add(&mut self, object: Object) -> usize {
+        if let Some(index) = self.indices.get(&object) {
+            return *index;
+        }
+        let index = self.objects.len();
+        self.indices.insert(object.clone(), index);
+        self.objects.push(object);
+        index
+    }
+
+    /// Add an object representing a lookup whose subtables can be moved behind extensions of a
+    /// given lookup type and return its index.
+    pub fn add_lookup(&mut self, object: Object, extension: u16) -> usize {
+        let index = self.add(object);
+        if !self.lookups.iter().any(|(other, _)| *other == index) {
+            self.lookups.push((index, extension));
+        }
+        index
+    }
+
+    /// Return an object.
+    #[inline]
+    pub fn get(&self, index: usize) -> &Object {
+        &self.objects[index]
+    }
+
+    /// Return the number of objects.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Check if the graph is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Return the total size of the objects reachable from a root.
+    pub fn size(&self, root: usize) -> usize {
+        self.order(root)
+            .into_iter()
+            .map(|index| self.objects[index].data.len())
+            .sum()
+    }
+
+    /// Order the objects reachable from a root so that no offset overflows.
+    ///
+    /// The objects are first placed depth first. If some offsets overflow, the objects are
+    /// placed in the order of their distance from the root, shared objects behind overflowing
+    /// offsets are duplicated, and lookups are promoted to extensions, in that order, until the
+    /// overflows are resolved. The function returns `None` if they cannot be resolved.
+    pub fn arrange(&mut self, root: usize) -> Option<Vec<usize>> {
+        let order = self.order(root);
+        if self.overflows(&order).is_empty() {
+            return Some(order);
+        }
+        let order = self.order_by_distance(root);
+        if self.overflows(&order).is_empty() {
+            return Some(order);
+        }
+        if let Some(order) = self.duplicate(root) {
+            return Some(order);
+        }
+        if !self.promote(root) {
+            return None;
+        }
+        let order = self.order_by_distance(root);
+        if self.overflows(&order).is_empty() {
+            return Some(order);
+        }
+        self.duplicate(root)
+    }
+
+    /// Write the objects reachable from a root.
+    pub fn write<T: crate::tape::Write>(&mut self, root: usize, tape: &mut T) -> Result<()> {
+        match self.arrange(root) {
+            Some(order) => self.write_given(&order, tape),
+            _ => raise!("found overflowing offsets that cannot be resolved"),
+        }
+    }
+
+    /// Write objects in a given order.
+    pub fn write_given<T: crate::tape::Write>(&self, order: &[usize], tape: &mut T) -> Result<()> {
+        let positions = self.positions(order);
+        let mut data = Vec::with_capacity(self.size_given(order));
+        for index in order.iter() {
+            let object = &self.objects[*index];
+            let start = data.len();
+            data.extend(&object.data);
+            for link in object.links.iter() {
+                let offset = positions[link.target] - positions[*index];
+                let position = start + link.position;
+                match link.size {
+                    2 if offset <= u16::MAX as usize => {
+                        data[position..position + 2].copy_from_slice(&(offset as u16).to_be_bytes())
+                    }
+                    4 if offset <= u32::MAX as usize => {
+                        data[position..position + 4].copy_from_slice(&(offset as u32).to_be_bytes())
+                    }
+                    _ => raise!("found an overflowing offset ({offset})"),
+                }
+            }
+        }
+        tape.give_bytes(&data)
+    }
+
+    fn counts(&self, root: usize) -> Vec<usize> {
+        let mut counts = vec![0usize; self.objects.len()];
+        let mut visited = vec![false; self.objects.len()];
+        let mut stack = vec![root];
+        visited[root] = true;
+        while let Some(index) = stack.pop() {
+            for link in self.objects[index].links.iter() {
+                counts[link.target] += 1;
+                if !visited[link.target] {
+                    visited[link.target] = true;
+                    stack.push(link.target);
+                }
+            }
+        }
+        counts
+    }
+
+    fn duplicate(&mut self, root: usize) -> Option<Vec<usize>> {
+        for _ in 0..ROUND_COUNT {
+            let order = self.order_by_distance(root);
+            let overflows = self.overflows(&order);
+            if overflows.is_empty() {
+                return Some(order);
+            }
+            let mut counts = self.counts(root);
+            let mut duplicated = false;
+            for (index, link) in overflows {
+                let target = self.objects[index].links[link].target;
+                if counts[target] > 1 {
+                    counts[target] -= 1;
+                    let object = self.objects[target].clone();
+                    self.objects.push(object);
+                    self.objects[index].links[link].target = self.objects.len() - 1;
+                    duplicated = true;
+                }
+            }
+            if !duplicated {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn order(&self, root: usize) -> Vec<usize> {
+        let mut counts = self.counts(root);
+        let mut order = Vec::with_capacity(self.objects.len());
+        let mut stack = vec![root];
+        while let Some(index) = stack.pop() {
+            order.push(index);
+            for link in self.objects[index].links.iter().rev() {
+                counts[link.target] -= 1;
+                if counts[link.target] == 0 {
+                    stack.push(link.target);
+                }
+            }
+        }
+        order
+    }
+
+    fn order_by_distance(&self, root: usize) -> Vec<usize> {
+        // The distance has two components. The first one is the space, which changes for each
+        // 32-bit offset so that the objects behind it are kept together, and the second one is
+        // the number of bytes from the start of the space.
+        let mut distances = vec![(u64::MAX, u64::MAX); self.objects.len()];
+        let mut heap = BinaryHeap::from([Reverse(((0, 0), root))]);
+        let mut space = 0;
+        distances[root] = (0, 0);
+        while let Some(Reverse((distance, index))) = heap.pop() {
+            if distance > distances[index] {
+                continue;
+            }
+            let object = &self.objects[index];
+            for link in object.links.iter() {
+                let other = if link.size > 2 {
+                    space += 1;
+                    (space, 0)
+                } else {
+                    (distance.0, distance.1 + object.data.len() as u64)
+                };
+                if other < distances[link.target] {
+                    distances[link.target] = other;
+                    heap.push(Reverse((other, link.target)));
+                }
+            }
+        }
+        let mut counts = self.counts(root);
+        let mut order = Vec::with_capacity(self.objects.len());
+        let mut heap = BinaryHeap::from([Reverse(((0, 0), root))]);
+        while let Some(Reverse((_, index))) = heap.pop() {
+            order.push(index);
+            for link in self.objects[index].links.iter() {
+                counts[link.target] -= 1;
+                if counts[link.target] == 0 {
+                    heap.push(Reverse((distances[link.target], link.target)));
+                }
+            }
+        }
+        order
+    }
+
+    fn overflows(&self, order: &[usize]) -> Vec<(usize, usize)> {
+        let positions = self.positions(order);
+        let mut overflows = vec![];
+        for index in order.iter() {
+            for (i, link) in self.objects[*index].links.iter().enumerate() {
+                let offset = positions[link.target] - positions[*index];
+                if link.size == 2 && offset > u16::MAX as usize {
+                    overflows.push((*index, i));
+                }
+            }
+        }
+        overflows
+    }
+
+    fn positions(&self, order: &[usize]) -> Vec<usize> {
+        let mut positions = vec![0; self.objects.len()];
+        let mut size = 0;
+        for index in order.iter() {
+            positions[*index] = size;
+            size += self.objects[*index].data.len();
+        }
+        positions
+    }
+
+    fn promote(&mut self, root: usize) -> bool {
+        let counts = self.counts(root);
+        let lookups = self
+            .lookups
+            .iter()
+            .filter(|(index, extension)| {
+                let data = &self.objects[*index].data;
+                counts[*index] > 0 && u16::from_be_bytes([data[0], data[1]]) != *extension
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if lookups.is_empty() {
+            return false;
+        }
+        for (index, extension) in lookups {
+            let r#type = [self.objects[index].data[0], self.objects[index].data[1]];
+            self.objects[index].data[..2].copy_from_slice(&extension.to_be_bytes());
+            for i in 0..self.objects[index].links.len() {
+                let target = self.objects[index].links[i].target;
+                let mut object = Object::default();
+                object.data.extend(1u16.to_be_bytes());
+                object.data.extend(r#type);
+                object.links.push(Link {
+                    position: object.data.len(),
+                    size: 4,
+                    target,
+                });
+                object.data.extend(0u32.to_be_bytes());
+                self.objects.push(object);
+                self.objects[index].links[i].target = self.objects.len() - 1;
+            }
+        }
+        true
+    }
+
+    fn size_given(&self, order: &[usize]) -> usize {
+        order
+            .iter()
+            .map(|index| self.objects[*index].data.len())
+            .sum()
+    }
+}
+
+impl Object {
+    /// Write a 16-bit offset to an object.
+    pub fn link(&mut self, target: usize) -> Result<()> {
+        self.links.push(Link {
+            position: self.data.len(),
+            size: 2,
+            target,
+        });
+        crate::tape::Write::give(self, &0u16)
+    }
+
+    /// Write a 32-bit offset to an object.
+    pub fn link_long(&mut self, target: usize) -> Result<()> {
+        self.links.push(Link {
+            position: self.data.len(),
+            size: 4,
+            target,
+        });
+        crate::tape::Write::give(self, &0u32)
+    }
+
+    /// Write a 32-bit offset to an object if present or a null offset otherwise.
+    pub fn link_long_maybe(&mut self, target: Option<usize>) -> Result<()> {
+        match target {
+            Some(target) => self.link_long(target),
+            _ => crate::tape::Write::give(self, &0u32),
+        }
+    }
+
+    /// Write a 16-bit offset to an object if present or a null offset otherwise.
+    pub fn link_maybe(&mut self, target: Option<usize>) -> Result<()> {
+        match target {
+            Some(target) => self.link(target),
+            _ => crate::tape::Write::give(self, &0u16),
+        }
+    }
+}
+
+impl std::io::Write for Object {
+    #[inline]
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        self.data.extend(data);
+        Ok(data.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+macro_rules! implement {
+    ($($type:ty,)*) => {
+        $(
+            impl crate::value::Write for $type {
+                fn write<T: crate::tape::Write>(&self, tape: &mut T) -> Result<()> {
+                    let mut graph = Graph::default();
+                    let root = self.pack(&mut graph)?;
+                    graph.write(root, tape)
+                }
+            }
+        )*
+    };
+}
+
+implement! {
+    ChainedContext,
+    Class,
+    Context,
+    Correction,
+    Coverage,
+    Features,
+    Scripts,
+    crate::tables::glyph_definition::GlyphDefinition,
+    crate::tables::glyph_positioning::Type,
+    crate::tables::glyph_substitution::Type,
+}
+
+impl<U: Subtable> crate::value::Write for Directory<U> {
+    fn write<T: crate::tape::Write>(&self, tape: &mut T) -> Result<()> {
+        let mut directory = Cow::Borrowed(self);
+        loop {
+            let mut graph = Graph::default();
+            let root = directory.pack(&mut graph)?;
+            if let Some(order) = graph.arrange(root) {
+                return graph.write_given(&order, tape);
+            }
+            if !directory.to_mut().lookups.split()? {
+                raise!("found overflowing offsets that cannot be resolved");
+            }
+        }
+    }
+}
+
+impl<U: Subtable> crate::value::Write for Lookups<U> {
+    fn write<T: crate::tape::Write>(&self, tape: &mut T) -> Result<()> {
+        let mut lookups = Cow::Borrowed(self);
+        loop {
+            let mut graph = Graph::default();
+            let root = lookups.pack(&mut graph)?;
+            if let Some(order) = graph.arrange(root) {
+                return graph.write_given(&order, tape);
+            }
+            if !lookups.to_mut().split()? {
+                raise!("found overflowing offsets that cannot be resolved");
+            }
+        }
+    }
+}
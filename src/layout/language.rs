@@ -2,6 +2,10 @@
 
 use truetype::Tag;
 
+use crate::layout::graph::{Graph, Object, Pack};
+use crate::tape::Write;
+use crate::Result;
+
 table! {
     /// A language-system header.
     pub Header { // LangSysRecord
@@ -23,6 +27,17 @@ table! {
     }
 }
 
+impl Pack for Record {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&0u16)?;
+        object.give(&self.required_feature_index)?;
+        object.give(&(self.feature_indices.len() as u16))?;
+        object.give(&self.feature_indices[..])?;
+        Ok(graph.add(object))
+    }
+}
+
 macro_rules! implement {
     ($(
         $(#[$attribute:meta])*
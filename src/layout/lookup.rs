@@ -1,5 +1,10 @@
 //! The lookup list.
 
+use crate::layout::buffer::Buffer;
+use crate::layout::graph::{Graph, Object, Pack, Subtable};
+use crate::layout::skip::Skip;
+use crate::tables::glyph_definition::GlyphDefinition;
+use crate::tape::Write;
 use crate::Result;
 
 /// A lookup list.
@@ -37,6 +42,31 @@ flags! {
     }
 }
 
+/// The maximum depth of lookups invoked from contextual lookups.
+const NESTING_LIMIT: usize = 64;
+
+/// A subtable that can be applied to a glyph buffer.
+pub trait Apply: Sized {
+    /// A parameter of the application.
+    type Parameter<'l>;
+
+    /// Check if the subtable is applied from the end of the buffer to the start.
+    fn is_reversed(&self) -> bool {
+        false
+    }
+
+    /// Apply the subtable at a position and return the position to continue from if applied.
+    fn apply(
+        &self,
+        lookups: &Lookups<Self>,
+        buffer: &mut Buffer,
+        position: usize,
+        skip: Skip,
+        parameter: &Self::Parameter<'_>,
+        depth: usize,
+    ) -> Result<Option<usize>>;
+}
+
 impl<T> Default for Lookups<T> {
     #[inline]
     fn default() -> Self {
@@ -91,3 +121,159 @@ where
         })
     }
 }
+
+impl Flags {
+    /// Return the class of the marks to attach to or zero if all marks are attached to.
+    ///
+    /// The class is stored in the high byte and refers to the mark class definition of the glyph
+    /// definition.
+    #[inline]
+    pub fn mark_attachment_class(&self) -> u16 {
+        self.0 >> 8
+    }
+}
+
+impl<T: Apply> Lookups<T> {
+    /// Apply lookups one after another.
+    ///
+    /// The glyph definition, if any, determines which glyphs the lookups skip. The number of
+    /// operations and the number of glyphs are limited in proportion to the initial number of
+    /// glyphs.
+    pub(crate) fn apply(
+        &self,
+        buffer: &mut Buffer,
+        indices: &[u16],
+        definition: Option<&GlyphDefinition>,
+        parameter: &T::Parameter<'_>,
+    ) -> Result<()> {
+        buffer.limit();
+        for index in indices.iter() {
+            let record = self.get(*index)?;
+            let skip = Skip::new(record, definition);
+            if record.tables.iter().any(Apply::is_reversed) {
+                for position in (0..buffer.len()).rev() {
+                    if !skip.skips(&buffer.glyphs[position]) {
+                        record.apply(self, buffer, position, skip, parameter, 0)?;
+                    }
+                }
+                continue;
+            }
+            let mut position = 0;
+            while position < buffer.len() {
+                if skip.skips(&buffer.glyphs[position]) {
+                    position += 1;
+                    continue;
+                }
+                position = match record.apply(self, buffer, position, skip, parameter, 0)? {
+                    Some(next) => next,
+                    _ => position + 1,
+                };
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply a lookup at a position on behalf of a contextual lookup with the given filter.
+    pub(crate) fn apply_nested(
+        &self,
+        buffer: &mut Buffer,
+        index: u16,
+        position: usize,
+        skip: Skip,
+        parameter: &T::Parameter<'_>,
+        depth: usize,
+    ) -> Result<bool> {
+        if depth > NESTING_LIMIT {
+            raise!("found too deeply nested lookups");
+        }
+        let record = self.get(index)?;
+        let skip = skip.with(record);
+        if position >= buffer.len() || skip.skips(&buffer.glyphs[position]) {
+            return Ok(false);
+        }
+        Ok(record
+            .apply(self, buffer, position, skip, parameter, depth)?
+            .is_some())
+    }
+
+    fn get(&self, index: u16) -> Result<&Record<T>> {
+        match self.records.get(index as usize) {
+            Some(record) => Ok(record),
+            _ => raise!("found an unknown lookup ({index})"),
+        }
+    }
+}
+
+impl<T: Apply> Record<T> {
+    fn apply(
+        &self,
+        lookups: &Lookups<T>,
+        buffer: &mut Buffer,
+        position: usize,
+        skip: Skip,
+        parameter: &T::Parameter<'_>,
+        depth: usize,
+    ) -> Result<Option<usize>> {
+        for table in self.tables.iter() {
+            buffer.count()?;
+            let result = table.apply(lookups, buffer, position, skip, parameter, depth)?;
+            if result.is_some() {
+                return Ok(result);
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<T: Subtable> Lookups<T> {
+    /// Split the subtables whose offsets cannot fit and return whether any has been split.
+    pub(crate) fn split(&mut self) -> Result<bool> {
+        let mut split = false;
+        for record in self.records.iter_mut() {
+            let mut tables = Vec::with_capacity(record.tables.len());
+            for table in record.tables.drain(..) {
+                let mut graph = Graph::default();
+                let root = table.pack(&mut graph)?;
+                if graph.size(root) > u16::MAX as usize {
+                    if let Some((first, second)) = table.split() {
+                        tables.push(first);
+                        tables.push(second);
+                        split = true;
+                        continue;
+                    }
+                }
+                tables.push(table);
+            }
+            record.table_count = tables.len() as u16;
+            record.tables = tables;
+        }
+        Ok(split)
+    }
+}
+
+impl<T: Subtable> Pack for Lookups<T> {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&(self.records.len() as u16))?;
+        for record in self.records.iter() {
+            object.link(record.pack(graph)?)?;
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl<T: Subtable> Pack for Record<T> {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&self.r#type)?;
+        object.give(&self.flags)?;
+        object.give(&(self.tables.len() as u16))?;
+        for table in self.tables.iter() {
+            object.link(table.pack(graph)?)?;
+        }
+        if self.flags.has_mark_filtering() {
+            object.give(&self.mark_filtering_set.unwrap_or(0))?;
+        }
+        Ok(graph.add_lookup(object, T::EXTENSION))
+    }
+}
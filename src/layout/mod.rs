@@ -2,17 +2,21 @@
 //!
 //! [1]: https://learn.microsoft.com/en-us/typography/opentype/spec/chapter2
 
+pub mod buffer;
 pub mod class;
 pub mod context;
 pub mod correction;
 pub mod coverage;
 pub mod feature;
+pub mod graph;
 pub mod language;
 pub mod lookup;
 pub mod script;
+pub mod skip;
 
 mod directory;
 
+pub use buffer::{Attachment, Buffer, Glyph};
 pub use class::Class;
 pub use context::{ChainedContext, Context};
 pub use correction::Correction;
@@ -22,3 +26,4 @@ pub use feature::{Feature, Features};
 pub use language::Language;
 pub use lookup::Lookups;
 pub use script::{Script, Scripts};
+pub use skip::Skip;
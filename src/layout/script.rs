@@ -2,7 +2,10 @@
 
 use truetype::Tag;
 
+use crate::layout::graph::{Graph, Object, Pack};
 use crate::layout::language;
+use crate::tape::Write;
+use crate::Result;
 
 table! {
     @position
@@ -74,6 +77,39 @@ impl Record {
     }
 }
 
+impl Pack for Scripts {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&(self.records.len() as u16))?;
+        for (header, record) in self.headers.iter().zip(self.records.iter()) {
+            object.give(&header.tag)?;
+            object.link(record.pack(graph)?)?;
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for Record {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        let default_language = match self.default_language {
+            Some(ref record) => Some(record.pack(graph)?),
+            _ => None,
+        };
+        object.link_maybe(default_language)?;
+        object.give(&(self.language_records.len() as u16))?;
+        let iterator = self
+            .language_headers
+            .iter()
+            .zip(self.language_records.iter());
+        for (header, record) in iterator {
+            object.give(&header.tag)?;
+            object.link(record.pack(graph)?)?;
+        }
+        Ok(graph.add(object))
+    }
+}
+
 macro_rules! implement {
     ($($tag:literal => $name:literal => $variant:ident,)*) => (
         /// A script.
@@ -0,0 +1,107 @@
+//! The skipping of glyphs.
+
+use truetype::GlyphID;
+
+use crate::layout::buffer::Glyph;
+use crate::layout::coverage::Coverage;
+use crate::layout::lookup::{Flags, Record};
+use crate::tables::glyph_definition::GlyphDefinition;
+
+/// A filter of the glyphs a lookup is to skip.
+///
+/// The classes of glyphs are taken from the glyph definition if it has a class definition and
+/// from the glyphs themselves otherwise.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Skip<'l> {
+    /// The flags of the lookup.
+    pub flags: Flags,
+
+    definition: Option<&'l GlyphDefinition>,
+    marks: Option<&'l Coverage>,
+}
+
+impl<'l> Skip<'l> {
+    /// Create an instance for a lookup.
+    pub fn new<T>(record: &Record<T>, definition: Option<&'l GlyphDefinition>) -> Self {
+        let marks = match (definition, record.mark_filtering_set) {
+            (Some(definition), Some(index)) if record.flags.has_mark_filtering() => definition
+                .marks
+                .as_ref()
+                .and_then(|marks| marks.coverages.get(index as usize)),
+            _ => None,
+        };
+        Self {
+            flags: record.flags,
+            definition,
+            marks,
+        }
+    }
+
+    /// Return the class of a glyph.
+    ///
+    /// Classes 1, 2, 3, and 4 stand for base glyphs, ligatures, marks, and components,
+    /// respectively, and zero stands for unknown.
+    pub fn class(&self, glyph: &Glyph) -> u16 {
+        match self
+            .definition
+            .and_then(|definition| definition.glyph_class.as_ref())
+        {
+            Some(class) => class.get(glyph.glyph_id),
+            _ => glyph.class,
+        }
+    }
+
+    /// Check if a glyph is to be skipped.
+    pub fn skips(&self, glyph: &Glyph) -> bool {
+        match self.class(glyph) {
+            1 => self.flags.should_ignore_base_glyphs(),
+            2 => self.flags.should_ignore_ligature(),
+            3 => self.skips_mark(glyph.glyph_id),
+            _ => false,
+        }
+    }
+
+    /// Iterate over the positions of the glyphs following a position that are not skipped.
+    pub fn forward<'m>(
+        &'m self,
+        glyphs: &'m [Glyph],
+        position: usize,
+    ) -> impl Iterator<Item = usize> + 'm {
+        (position + 1..glyphs.len()).filter(move |&i| !self.skips(&glyphs[i]))
+    }
+
+    /// Iterate over the positions of the glyphs preceding a position that are not skipped going
+    /// backward.
+    pub fn backward<'m>(
+        &'m self,
+        glyphs: &'m [Glyph],
+        position: usize,
+    ) -> impl Iterator<Item = usize> + 'm {
+        (0..position.min(glyphs.len()))
+            .rev()
+            .filter(move |&i| !self.skips(&glyphs[i]))
+    }
+
+    /// Create an instance for another lookup with the same glyph definition.
+    pub(crate) fn with<T>(&self, record: &Record<T>) -> Self {
+        Self::new(record, self.definition)
+    }
+
+    fn skips_mark(&self, glyph_id: GlyphID) -> bool {
+        if self.flags.should_ignore_marks() {
+            return true;
+        }
+        if self.flags.has_mark_filtering() {
+            return !self.marks.is_some_and(|marks| marks.contains(glyph_id));
+        }
+        match self.flags.mark_attachment_class() {
+            0 => false,
+            value => {
+                self.definition
+                    .and_then(|definition| definition.mark_class.as_ref())
+                    .map_or(0, |class| class.get(glyph_id))
+                    != value
+            }
+        }
+    }
+}
@@ -43,22 +43,29 @@ pub extern crate truetype;
 )]
 extern crate typeface;
 
+pub mod collection;
 pub mod layout;
 pub mod tables;
 pub mod variations;
+pub mod woff;
+pub mod woff2;
 
+mod builder;
 mod file;
 mod font;
+mod instancer;
 mod table;
 
 pub use typeface::{tape, value, walue, Error, Result};
 
+pub use builder::Builder;
 pub use file::File;
 pub use font::Font;
+pub use instancer::Instancer;
 pub use table::Table;
 
 /// Check if a tag is recognized.
 #[inline]
 pub fn accept(tag: &truetype::Tag) -> bool {
-    matches!(&tag.0, b"ttcf") || truetype::accept(tag)
+    matches!(&tag.0, b"ttcf" | b"wOFF" | b"wOF2") || truetype::accept(tag)
 }
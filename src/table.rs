@@ -6,7 +6,8 @@ use truetype::tables::{
 use truetype::{self, Tag};
 
 use crate::tables::{
-    ColorPalettes, FontVariations, GlyphDefinition, GlyphPositioning, GlyphSubstitution,
+    AxisVariations, ColorPalettes, Compact2, FontVariations, GlyphDefinition, GlyphPositioning,
+    GlyphSubstitution, GlyphVariations, HorizontalVariations, MetricVariations, VerticalVariations,
 };
 
 /// A type representing a font table.
@@ -28,14 +29,20 @@ macro_rules! implement {
 
 implement! {
     b"CFF " => FontSet,
+    b"CFF2" => Compact2,
     b"CPAL" => ColorPalettes,
     b"GDEF" => GlyphDefinition,
     b"GPOS" => GlyphPositioning,
     b"GSUB" => GlyphSubstitution,
+    b"HVAR" => HorizontalVariations,
+    b"MVAR" => MetricVariations,
     b"OS/2" => WindowsMetrics,
+    b"VVAR" => VerticalVariations,
+    b"avar" => AxisVariations,
     b"cmap" => CharacterMapping,
     b"fvar" => FontVariations,
     b"glyf" => GlyphData,
+    b"gvar" => GlyphVariations,
     b"head" => FontHeader,
     b"hhea" => HorizontalHeader,
     b"hmtx" => HorizontalMetrics,
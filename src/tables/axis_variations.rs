@@ -0,0 +1,128 @@
+//! The [axis-variations table][1].
+//!
+//! [1]: https://learn.microsoft.com/en-us/typography/opentype/spec/avar
+
+use truetype::q16;
+
+use crate::variations::item::{Mapping, Store};
+
+table! {
+    @position
+    /// An axis-variations table.
+    pub AxisVariations {
+        major_version (u16), // majorVersion
+        minor_version (u16) = { 0 }, // minorVersion
+        reserved      (u16), // reserved
+        axis_count    (u16), // axisCount
+
+        records (Vec<Segments>) |this, tape, _| { // axisSegmentMaps
+            tape.take_given(this.axis_count as usize)
+        },
+
+        mapping_offset (u32) |this, tape, _| { // axisIndexMapOffset
+            match this.major_version {
+                1 => Ok(0),
+                2 => tape.take(),
+                value => raise!("found an unknown version of the axis-variations table ({value})"),
+            }
+        },
+
+        store_offset (u32) |this, tape, _| { // itemVariationStoreOffset
+            match this.major_version {
+                2 => tape.take(),
+                _ => Ok(0),
+            }
+        },
+
+        mapping (Option<Mapping>) |this, tape, position| {
+            jump_take_maybe!(tape, position, this.mapping_offset)
+        },
+
+        store (Option<Store>) |this, tape, position| {
+            jump_take_maybe!(tape, position, this.store_offset)
+        },
+    }
+}
+
+table! {
+    /// Segments of an axis.
+    pub Segments { // SegmentMaps
+        count (u16), // positionMapCount
+
+        records (Vec<Segment>) |this, tape| { // axisValueMaps
+            tape.take_given(this.count as usize)
+        },
+    }
+}
+
+table! {
+    /// A segment of an axis.
+    #[derive(Copy)]
+    pub Segment { // AxisValueMap
+        from (q16), // fromCoordinate
+        to   (q16), // toCoordinate
+    }
+}
+
+impl AxisVariations {
+    /// Remap normalized axis coordinates.
+    ///
+    /// The coordinates are first mapped segment by segment and then, for version 2.0, adjusted
+    /// by the deltas of the item variation store computed at the coordinates mapped so far.
+    pub fn map(&self, coordinates: &[f32]) -> Vec<f32> {
+        let mut values = coordinates
+            .iter()
+            .enumerate()
+            .map(|(i, value)| match self.records.get(i) {
+                Some(record) => crate::variations::quantize(record.map(*value)),
+                _ => *value,
+            })
+            .collect::<Vec<_>>();
+        if let Some(ref store) = self.store {
+            let deltas = (0..values.len())
+                .map(|i| {
+                    let (outer, inner) = match self.mapping {
+                        Some(ref mapping) => mapping.get(i as u32).unwrap_or((0xFFFF, 0xFFFF)),
+                        _ => (0, i as u16),
+                    };
+                    store.delta(outer, inner, &values).unwrap_or(0.0)
+                })
+                .collect::<Vec<_>>();
+            for (value, delta) in values.iter_mut().zip(deltas) {
+                *value = crate::variations::quantize((*value + delta / 16384.0).clamp(-1.0, 1.0));
+            }
+        }
+        values
+    }
+}
+
+impl Segments {
+    /// Remap a normalized coordinate.
+    pub fn map(&self, value: f32) -> f32 {
+        let (first, last) = match (self.records.first(), self.records.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return value,
+        };
+        let (from, to) = (f32::from(first.from), f32::from(first.to));
+        if self.records.len() == 1 || value <= from {
+            return value - from + to;
+        }
+        let (from, to) = (f32::from(last.from), f32::from(last.to));
+        if value >= from {
+            return value - from + to;
+        }
+        for pair in self.records.windows(2) {
+            let (start, end) = (&pair[0], &pair[1]);
+            let (start_from, end_from) = (f32::from(start.from), f32::from(end.from));
+            if value == end_from {
+                return f32::from(end.to);
+            }
+            if start_from <= value && value < end_from {
+                let (start_to, end_to) = (f32::from(start.to), f32::from(end.to));
+                return start_to
+                    + (end_to - start_to) * (value - start_from) / (end_from - start_from);
+            }
+        }
+        value
+    }
+}
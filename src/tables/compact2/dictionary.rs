@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use postscript::compact1::Number;
+
+use crate::tables::compact2::STACK_LIMIT;
+use crate::tape::Read;
+use crate::variations::item::Store;
+use crate::Result;
+
+/// A collection of operations.
+#[derive(Clone, Debug, Default)]
+pub struct Operations(pub HashMap<Operator, Vec<Operand>>);
+
+dereference! { Operations::0 => HashMap<Operator, Vec<Operand>> }
+
+/// An operand.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Operand {
+    /// The default value.
+    pub value: Number,
+    /// The deltas of the value for the regions of the variation data in use.
+    pub deltas: Vec<Number>,
+}
+
+impl Operations {
+    /// Return the operands of an operation.
+    #[inline]
+    pub fn get(&self, operator: Operator) -> Option<&[Operand]> {
+        match self.0.get(&operator) {
+            Some(operands) => Some(operands),
+            _ => operator.default(),
+        }
+    }
+
+    /// Return the operand of an operation that takes a single integer.
+    pub fn get_integer(&self, operator: Operator) -> Option<i32> {
+        match self.get(operator)?.first()?.value {
+            Number::Integer(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Return the operands of an operation that takes a pair of integers.
+    pub fn get_integers(&self, operator: Operator) -> Option<(i32, i32)> {
+        match self.get(operator)? {
+            [Operand {
+                value: Number::Integer(first),
+                ..
+            }, Operand {
+                value: Number::Integer(second),
+                ..
+            }] => Some((*first, *second)),
+            _ => None,
+        }
+    }
+}
+
+impl Operand {
+    /// Compute the value given the scalars of the regions of the variation data in use.
+    pub fn blend(&self, scalars: &[f32]) -> f32 {
+        self.deltas
+            .iter()
+            .zip(scalars)
+            .fold(float(self.value), |value, (delta, scalar)| {
+                value + float(*delta) * scalar
+            })
+    }
+}
+
+impl From<Number> for Operand {
+    #[inline]
+    fn from(value: Number) -> Self {
+        Self {
+            value,
+            deltas: vec![],
+        }
+    }
+}
+
+impl<'l> crate::walue::Read<'l> for Operations {
+    type Parameter = (usize, Option<&'l Store>);
+
+    fn read<T: crate::tape::Read>(tape: &mut T, (size, store): Self::Parameter) -> Result<Self> {
+        let data = tape.take_bytes(size)?;
+        let mut tape = Cursor::new(&data[..]);
+        let mut operations = HashMap::new();
+        let mut operands: Vec<Operand> = vec![];
+        let mut index = 0;
+        while (Read::position(&mut tape)? as usize) < size {
+            let code = match tape.peek::<u8>()? {
+                0x1c | 0x1d | 0x1e | 0x20..=0xfe => {
+                    if operands.len() == STACK_LIMIT {
+                        raise!("found too many operands in a dictionary");
+                    }
+                    operands.push(Operand::from(tape.take::<Number>()?));
+                    continue;
+                }
+                0x0c => tape.take::<u16>()?,
+                _ => tape.take::<u8>()? as u16,
+            };
+            match Operator::from(code)? {
+                Operator::Blend => {
+                    let count = match operands.pop() {
+                        Some(Operand {
+                            value: Number::Integer(value),
+                            ..
+                        }) if value >= 0 => value as usize,
+                        _ => raise!("found a malformed blend operator in a dictionary"),
+                    };
+                    let region_count = match store.and_then(|store| store.records.get(index)) {
+                        Some(record) => record.region_count as usize,
+                        _ => raise!("found no variation data for a dictionary"),
+                    };
+                    let total = count * (region_count + 1);
+                    if operands.len() < total {
+                        raise!("expected more operands in a dictionary");
+                    }
+                    let mut values = operands.split_off(operands.len() - total);
+                    let deltas = values.split_off(count);
+                    for (i, value) in values.into_iter().enumerate() {
+                        operands.push(Operand {
+                            value: value.value,
+                            deltas: deltas[i * region_count..(i + 1) * region_count]
+                                .iter()
+                                .map(|delta| delta.value)
+                                .collect(),
+                        });
+                    }
+                }
+                operator => {
+                    if operator == Operator::VSIndex {
+                        index = match operands.first() {
+                            Some(Operand {
+                                value: Number::Integer(value),
+                                ..
+                            }) if *value >= 0 => *value as usize,
+                            _ => raise!("found a malformed variation-store index"),
+                        };
+                    }
+                    operations.insert(operator, std::mem::take(&mut operands));
+                }
+            }
+        }
+        Ok(Operations(operations))
+    }
+}
+
+macro_rules! default(
+    ([$($operand:expr),+ $(,)?]) => ({
+        const OPERANDS: &[Operand] = &[$(Operand { value: $operand, deltas: Vec::new() }),+];
+        Some(OPERANDS)
+    });
+    ([]) => (None);
+);
+
+macro_rules! operator {
+    (pub $name:ident { $($code:literal => $variant:ident $default:tt,)+ }) => (
+        /// An operator.
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        pub enum $name { $($variant,)+ }
+
+        impl $name {
+            #[doc(hidden)]
+            pub fn from(code: u16) -> Result<Self> {
+                use self::$name::*;
+
+                Ok(match code {
+                    $($code => $variant,)+
+                    code => raise!("found an unknown operator ({code})"),
+                })
+            }
+
+            /// Return the code.
+            pub fn code(&self) -> u16 {
+                use self::$name::*;
+
+                match *self {
+                    $($variant => $code,)+
+                }
+            }
+
+            /// Return the default operands.
+            pub fn default(&self) -> Option<&'static [Operand]> {
+                use self::$name::*;
+
+                match *self {
+                    $($variant => default!($default),)+
+                }
+            }
+        }
+    );
+}
+
+operator! {
+    pub Operator {
+        0x06 => BlueValues [],
+        0x07 => OtherBlues [],
+        0x08 => FamilyBlues [],
+        0x09 => FamilyOtherBlues [],
+        0x0a => StdHW [],
+        0x0b => StdVW [],
+        0x11 => CharStrings [],
+        0x12 => Private [],
+        0x13 => Subrs [],
+        0x16 => VSIndex [Number::Integer(0)],
+        0x17 => Blend [],
+        0x18 => VStore [],
+        0x0c07 => FontMatrix [
+            Number::Real(0.001),
+            Number::Real(0.0),
+            Number::Real(0.0),
+            Number::Real(0.001),
+            Number::Real(0.0),
+            Number::Real(0.0),
+        ],
+        0x0c09 => BlueScale [Number::Real(0.039625)],
+        0x0c0a => BlueShift [Number::Integer(7)],
+        0x0c0b => BlueFuzz [Number::Integer(1)],
+        0x0c0c => StemSnapH [],
+        0x0c0d => StemSnapV [],
+        0x0c11 => LanguageGroup [Number::Integer(0)],
+        0x0c12 => ExpansionFactor [Number::Real(0.06)],
+        0x0c24 => FDArray [],
+        0x0c25 => FDSelect [],
+    }
+}
+
+#[inline]
+fn float(number: Number) -> f32 {
+    match number {
+        Number::Integer(value) => value as f32,
+        Number::Real(value) => value,
+    }
+}
@@ -0,0 +1,234 @@
+//! The [compact-font-format table of version 2][1].
+//!
+//! [1]: https://learn.microsoft.com/en-us/typography/opentype/spec/cff2
+
+mod dictionary;
+mod program;
+
+pub use dictionary::{Operand, Operations, Operator};
+pub use program::{Contour, Outline, Segment};
+
+use postscript::compact1::{Offset, OffsetSize};
+use truetype::GlyphID;
+
+use crate::tape::Read;
+use crate::variations::item::Store;
+use crate::Result;
+
+/// The maximum number of operands on the stack.
+const STACK_LIMIT: usize = 513;
+
+table! {
+    @position
+    /// A compact-font-format table of version 2.
+    pub Compact2 { // CFF2
+        major_version       (u8 ) = { 2 }, // majorVersion
+        minor_version       (u8 ), // minorVersion
+        header_size         (u8 ), // headerSize
+        top_dictionary_size (u16), // topDictLength
+
+        top_dictionary (Operations) |this, tape, position| { // TopDICTData
+            tape.jump(position + this.header_size as u64)?;
+            tape.take_given((this.top_dictionary_size as usize, None))
+        },
+
+        global_subroutines (Index) |_, tape, _| { // GlobalSubrINDEX
+            tape.take()
+        },
+
+        character_strings (Index) |this, tape, position| { // CharStringINDEX
+            match this.top_dictionary.get_integer(Operator::CharStrings) {
+                Some(offset) if offset > 0 => {
+                    tape.jump(position + offset as u64)?;
+                    tape.take()
+                }
+                _ => raise!("found no character strings"),
+            }
+        },
+
+        store (Option<Store>) |this, tape, position| { // VariationStore
+            match this.top_dictionary.get_integer(Operator::VStore) {
+                Some(offset) if offset > 0 => {
+                    // The store is preceded by its length.
+                    tape.jump(position + offset as u64 + 2)?;
+                    Ok(Some(tape.take()?))
+                }
+                _ => Ok(None),
+            }
+        },
+
+        dictionaries (Vec<Operations>) |this, tape, position| { // FontDICTINDEX
+            let index: Index = match this.top_dictionary.get_integer(Operator::FDArray) {
+                Some(offset) if offset > 0 => jump_take!(@unwrap tape, position, offset),
+                _ => raise!("found no font dictionaries"),
+            };
+            index
+                .iter()
+                .map(|data| {
+                    std::io::Cursor::new(data).take_given((data.len(), this.store.as_ref()))
+                })
+                .collect()
+        },
+
+        selector (Option<Selector>) |this, tape, position| { // FDSelect
+            match this.top_dictionary.get_integer(Operator::FDSelect) {
+                Some(offset) if offset > 0 => {
+                    tape.jump(position + offset as u64)?;
+                    Ok(Some(tape.take_given(this.character_strings.count)?))
+                }
+                _ => Ok(None),
+            }
+        },
+
+        privates (Vec<Private>) |this, tape, position| { // PrivateDICT
+            let mut values = Vec::with_capacity(this.dictionaries.len());
+            for dictionary in this.dictionaries.iter() {
+                let (size, offset) = match dictionary.get_integers(Operator::Private) {
+                    Some((size, offset)) if size >= 0 && offset >= 0 => (size, offset),
+                    _ => raise!("found no private dictionary"),
+                };
+                let position = position + offset as u64;
+                tape.jump(position)?;
+                let operations: Operations =
+                    tape.take_given((size as usize, this.store.as_ref()))?;
+                let subroutines = match operations.get_integer(Operator::Subrs) {
+                    Some(offset) if offset > 0 => jump_take!(@unwrap tape, position, offset),
+                    _ => Index::default(),
+                };
+                values.push(Private {
+                    operations,
+                    subroutines,
+                });
+            }
+            Ok(values)
+        },
+    }
+}
+
+table! {
+    @define
+    /// An index.
+    pub Index { // INDEX
+        count       (u32         ), // count
+        offset_size (OffsetSize  ), // offSize
+        offsets     (Vec<Offset> ), // offset
+        data        (Vec<Vec<u8>>), // data
+    }
+}
+
+dereference! { Index::data => [Vec<u8>] }
+
+/// A private dictionary with its subroutines.
+#[derive(Clone, Debug, Default)]
+pub struct Private {
+    /// The operations.
+    pub operations: Operations,
+    /// The local subroutines.
+    pub subroutines: Index,
+}
+
+/// A mapping of glyphs to font dictionaries.
+#[derive(Clone, Debug)]
+pub enum Selector {
+    /// Format 0.
+    Format0(Vec<u8>),
+    /// Format 3.
+    Format3(Vec<(u32, u16)>, u32),
+    /// Format 4.
+    Format4(Vec<(u32, u16)>, u32),
+}
+
+impl Compact2 {
+    /// Return the index of the font dictionary of a glyph.
+    pub fn dictionary_index(&self, glyph_id: GlyphID) -> Option<usize> {
+        match self.selector {
+            Some(ref selector) => selector.get(glyph_id),
+            _ if !self.dictionaries.is_empty() => Some(0),
+            _ => None,
+        }
+    }
+
+    /// Return the number of glyphs.
+    #[inline]
+    pub fn glyph_count(&self) -> usize {
+        self.character_strings.len()
+    }
+}
+
+impl Selector {
+    /// Return the index of the font dictionary of a glyph.
+    pub fn get(&self, glyph_id: GlyphID) -> Option<usize> {
+        let glyph_id = glyph_id as u32;
+        let (ranges, sentinel) = match self {
+            Self::Format0(values) => return values.get(glyph_id as usize).map(|&i| i as usize),
+            Self::Format3(ranges, sentinel) | Self::Format4(ranges, sentinel) => {
+                (ranges, *sentinel)
+            }
+        };
+        if glyph_id >= sentinel {
+            return None;
+        }
+        let i = ranges.partition_point(|&(first, _)| first <= glyph_id);
+        ranges
+            .get(i.checked_sub(1)?)
+            .map(|&(_, index)| index as usize)
+    }
+}
+
+impl crate::value::Read for Index {
+    fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
+        let count = tape.take::<u32>()?;
+        if count == 0 {
+            return Ok(Default::default());
+        }
+        let offset_size = tape.take::<OffsetSize>()?;
+        let mut offsets = vec![];
+        for _ in 0..(count as usize + 1) {
+            offsets.push(tape.take_given::<Offset>(offset_size)?);
+        }
+        if offsets[0] != Offset(1) {
+            raise!("found a malformed index");
+        }
+        let mut data = vec![];
+        for i in 0..(count as usize) {
+            if offsets[i] > offsets[i + 1] {
+                raise!("found a malformed index");
+            }
+            let size = (offsets[i + 1].0 - offsets[i].0) as usize;
+            data.push(tape.take_bytes(size)?);
+        }
+        Ok(Self {
+            count,
+            offset_size,
+            offsets,
+            data,
+        })
+    }
+}
+
+impl crate::walue::Read<'static> for Selector {
+    type Parameter = u32;
+
+    fn read<T: crate::tape::Read>(tape: &mut T, glyph_count: u32) -> Result<Self> {
+        Ok(match tape.take::<u8>()? {
+            0 => Self::Format0(tape.take_given(glyph_count as usize)?),
+            3 => {
+                let count = tape.take::<u16>()?;
+                let mut ranges = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    ranges.push((tape.take::<u16>()? as u32, tape.take::<u8>()? as u16));
+                }
+                Self::Format3(ranges, tape.take::<u16>()? as u32)
+            }
+            4 => {
+                let count = tape.take::<u32>()?;
+                let mut ranges = vec![];
+                for _ in 0..count {
+                    ranges.push((tape.take::<u32>()?, tape.take::<u16>()?));
+                }
+                Self::Format4(ranges, tape.take::<u32>()?)
+            }
+            value => raise!("found an unknown format of the font-dictionary selector ({value})"),
+        })
+    }
+}
@@ -0,0 +1,418 @@
+use std::io::Cursor;
+
+use truetype::GlyphID;
+
+use crate::tables::compact2::{Compact2, Operator, STACK_LIMIT};
+use crate::tape::Read;
+use crate::variations::item::Store;
+use crate::Result;
+
+/// The maximum depth of subroutines calling other subroutines.
+const NESTING_LIMIT: usize = 10;
+
+/// An outline evaluated at given normalized axis coordinates.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Outline {
+    /// The contours, which are implicitly closed.
+    pub contours: Vec<Contour>,
+}
+
+/// A contour.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Contour {
+    /// The starting point.
+    pub start: (f32, f32),
+    /// The segments.
+    pub segments: Vec<Segment>,
+}
+
+/// A segment of a contour.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Segment {
+    /// A line to a point.
+    Linear((f32, f32)),
+    /// A cubic Bézier curve with two control points to a point.
+    Cubic((f32, f32), (f32, f32), (f32, f32)),
+}
+
+struct Machine<'l> {
+    global: &'l [Vec<u8>],
+    local: &'l [Vec<u8>],
+    store: Option<&'l Store>,
+    coordinates: &'l [f32],
+    index: u16,
+    scalars: Option<Vec<f32>>,
+    stack: Vec<f32>,
+    stems: usize,
+    point: (f32, f32),
+    outline: Outline,
+}
+
+impl Outline {
+    /// Return the bounding box as the minimum and maximum coordinates.
+    ///
+    /// Curves are bounded by their control points. Empty outlines have no bounding box.
+    pub fn bounding_box(&self) -> Option<((f32, f32), (f32, f32))> {
+        self.contours
+            .iter()
+            .flat_map(|contour| {
+                std::iter::once(contour.start).chain(contour.segments.iter().flat_map(|segment| {
+                    match *segment {
+                        Segment::Linear(a) => vec![a],
+                        Segment::Cubic(a, b, c) => vec![a, b, c],
+                    }
+                }))
+            })
+            .fold(None, |bounds, (x, y)| match bounds {
+                Some(((min_x, min_y), (max_x, max_y))) => Some((
+                    (f32::min(min_x, x), f32::min(min_y, y)),
+                    (f32::max(max_x, x), f32::max(max_y, y)),
+                )),
+                _ => Some(((x, y), (x, y))),
+            })
+    }
+}
+
+impl Compact2 {
+    /// Evaluate the character string of a glyph at given normalized axis coordinates.
+    ///
+    /// The blend operators are resolved using the item variation store, and the hints are
+    /// skipped.
+    pub fn outline(&self, glyph_id: GlyphID, coordinates: &[f32]) -> Result<Outline> {
+        let code = match self.character_strings.get(glyph_id as usize) {
+            Some(code) => code,
+            _ => raise!("found no character string for glyph {glyph_id}"),
+        };
+        let private = match self
+            .dictionary_index(glyph_id)
+            .and_then(|index| self.privates.get(index))
+        {
+            Some(private) => private,
+            _ => raise!("found no private dictionary for glyph {glyph_id}"),
+        };
+        let index = private
+            .operations
+            .get_integer(Operator::VSIndex)
+            .unwrap_or(0);
+        let mut machine = Machine {
+            global: &self.global_subroutines,
+            local: &private.subroutines,
+            store: self.store.as_ref(),
+            coordinates,
+            index: index as u16,
+            scalars: None,
+            stack: vec![],
+            stems: 0,
+            point: (0.0, 0.0),
+            outline: Outline::default(),
+        };
+        machine.run(code, 0)?;
+        Ok(machine.outline)
+    }
+}
+
+impl Machine<'_> {
+    fn run(&mut self, code: &[u8], depth: usize) -> Result<()> {
+        if depth > NESTING_LIMIT {
+            raise!("found too deeply nested subroutines");
+        }
+        let mut tape = Cursor::new(code);
+        while (Read::position(&mut tape)? as usize) < code.len() {
+            let code = tape.take::<u8>()?;
+            match code {
+                0x1c | 0x20..=0xff => {
+                    if self.stack.len() == STACK_LIMIT {
+                        raise!("found too many operands in a character string");
+                    }
+                    let value = match code {
+                        0x1c => tape.take::<i16>()? as f32,
+                        0x20..=0xf6 => (code as i32 - 139) as f32,
+                        0xf7..=0xfa => {
+                            ((code as i32 - 247) * 256 + tape.take::<u8>()? as i32 + 108) as f32
+                        }
+                        0xfb..=0xfe => {
+                            (-(code as i32 - 251) * 256 - tape.take::<u8>()? as i32 - 108) as f32
+                        }
+                        _ => tape.take::<i32>()? as f32 / 65536.0,
+                    };
+                    self.stack.push(value);
+                    continue;
+                }
+                // hstem, vstem, hstemhm, vstemhm
+                0x01 | 0x03 | 0x12 | 0x17 => {
+                    self.stems += self.stack.len() / 2;
+                }
+                // vmoveto
+                0x04 => {
+                    let dy = self.argument(self.stack.len().saturating_sub(1))?;
+                    self.move_to((0.0, dy));
+                }
+                // rlineto
+                0x05 => {
+                    let s = std::mem::take(&mut self.stack);
+                    for i in (0..s.len() / 2 * 2).step_by(2) {
+                        self.line_to((s[i], s[i + 1]));
+                    }
+                }
+                // hlineto, vlineto
+                0x06 | 0x07 => {
+                    let s = std::mem::take(&mut self.stack);
+                    let mut horizontal = code == 0x06;
+                    for value in s {
+                        self.line_to(if horizontal {
+                            (value, 0.0)
+                        } else {
+                            (0.0, value)
+                        });
+                        horizontal = !horizontal;
+                    }
+                }
+                // rrcurveto
+                0x08 => {
+                    let s = std::mem::take(&mut self.stack);
+                    for i in (0..s.len() / 6 * 6).step_by(6) {
+                        self.curve_to(&s[i..i + 6]);
+                    }
+                }
+                // callsubr, callgsubr
+                0x0a | 0x1d => {
+                    let address = match self.stack.pop() {
+                        Some(value) => value as i32,
+                        _ => raise!("expected an operand"),
+                    };
+                    let subroutines = if code == 0x0a {
+                        self.local
+                    } else {
+                        self.global
+                    };
+                    let subroutine = address
+                        .checked_add(bias(subroutines.len()))
+                        .and_then(|i| usize::try_from(i).ok())
+                        .and_then(|i| subroutines.get(i));
+                    let subroutine = match subroutine {
+                        Some(subroutine) => subroutine,
+                        _ => raise!("found no subroutine ({address})"),
+                    };
+                    self.run(subroutine, depth + 1)?;
+                    continue;
+                }
+                0x0c => {
+                    let code = tape.take::<u8>()?;
+                    let s = std::mem::take(&mut self.stack);
+                    let count = match code {
+                        0x22 => 7,
+                        0x23 => 13,
+                        0x24 => 9,
+                        0x25 => 11,
+                        code => raise!("found an unknown operator (12 {code})"),
+                    };
+                    if s.len() < count {
+                        raise!("expected more operands");
+                    }
+                    match code {
+                        // hflex
+                        0x22 => {
+                            self.curve_to(&[s[0], 0.0, s[1], s[2], s[3], 0.0]);
+                            self.curve_to(&[s[4], 0.0, s[5], -s[2], s[6], 0.0]);
+                        }
+                        // flex
+                        0x23 => {
+                            self.curve_to(&s[0..6]);
+                            self.curve_to(&s[6..12]);
+                        }
+                        // hflex1
+                        0x24 => {
+                            self.curve_to(&[s[0], s[1], s[2], s[3], s[4], 0.0]);
+                            self.curve_to(&[s[5], 0.0, s[6], s[7], s[8], -(s[1] + s[3] + s[7])]);
+                        }
+                        // flex1
+                        _ => {
+                            let dx = (0..5).map(|i| s[2 * i]).sum::<f32>();
+                            let dy = (0..5).map(|i| s[2 * i + 1]).sum::<f32>();
+                            let (dx6, dy6) = if dx.abs() > dy.abs() {
+                                (s[10], -dy)
+                            } else {
+                                (-dx, s[10])
+                            };
+                            self.curve_to(&s[0..6]);
+                            self.curve_to(&[s[6], s[7], s[8], s[9], dx6, dy6]);
+                        }
+                    }
+                    continue;
+                }
+                // vsindex
+                0x0f => {
+                    self.index = match self.stack.pop() {
+                        Some(value) if value >= 0.0 => value as u16,
+                        _ => raise!("found a malformed variation-store index"),
+                    };
+                    self.scalars = None;
+                }
+                // blend
+                0x10 => {
+                    self.blend()?;
+                    continue;
+                }
+                // hintmask, cntrmask
+                0x13 | 0x14 => {
+                    self.stems += self.stack.len() / 2;
+                    tape.take_bytes(self.stems.div_ceil(8))?;
+                }
+                // rmoveto
+                0x15 => {
+                    let length = self.stack.len();
+                    let (dx, dy) = (
+                        self.argument(length.saturating_sub(2))?,
+                        self.argument(length.saturating_sub(1))?,
+                    );
+                    self.move_to((dx, dy));
+                }
+                // hmoveto
+                0x16 => {
+                    let dx = self.argument(self.stack.len().saturating_sub(1))?;
+                    self.move_to((dx, 0.0));
+                }
+                // rcurveline
+                0x18 => {
+                    let s = std::mem::take(&mut self.stack);
+                    let length = s.len();
+                    if length < 8 {
+                        raise!("expected more operands");
+                    }
+                    for i in (0..(length - 2) / 6 * 6).step_by(6) {
+                        self.curve_to(&s[i..i + 6]);
+                    }
+                    self.line_to((s[length - 2], s[length - 1]));
+                }
+                // rlinecurve
+                0x19 => {
+                    let s = std::mem::take(&mut self.stack);
+                    let length = s.len();
+                    if length < 8 {
+                        raise!("expected more operands");
+                    }
+                    for i in (0..(length - 6) / 2 * 2).step_by(2) {
+                        self.line_to((s[i], s[i + 1]));
+                    }
+                    self.curve_to(&s[length - 6..]);
+                }
+                // vvcurveto, hhcurveto
+                0x1a | 0x1b => {
+                    let s = std::mem::take(&mut self.stack);
+                    let (mut i, mut first) = if s.len() % 4 == 1 {
+                        (1, s[0])
+                    } else {
+                        (0, 0.0)
+                    };
+                    while i + 4 <= s.len() {
+                        if code == 0x1a {
+                            self.curve_to(&[first, s[i], s[i + 1], s[i + 2], 0.0, s[i + 3]]);
+                        } else {
+                            self.curve_to(&[s[i], first, s[i + 1], s[i + 2], s[i + 3], 0.0]);
+                        }
+                        first = 0.0;
+                        i += 4;
+                    }
+                }
+                // vhcurveto, hvcurveto
+                0x1e | 0x1f => {
+                    let s = std::mem::take(&mut self.stack);
+                    let (mut i, mut horizontal) = (0, code == 0x1f);
+                    while i + 4 <= s.len() {
+                        let last = if s.len() - i == 5 { s[i + 4] } else { 0.0 };
+                        if horizontal {
+                            self.curve_to(&[s[i], 0.0, s[i + 1], s[i + 2], last, s[i + 3]]);
+                        } else {
+                            self.curve_to(&[0.0, s[i], s[i + 1], s[i + 2], s[i + 3], last]);
+                        }
+                        horizontal = !horizontal;
+                        i += 4;
+                    }
+                }
+                code => raise!("found an unknown operator ({code})"),
+            }
+            self.stack.clear();
+        }
+        Ok(())
+    }
+
+    fn argument(&self, i: usize) -> Result<f32> {
+        match self.stack.get(i) {
+            Some(value) => Ok(*value),
+            _ => raise!("expected more operands"),
+        }
+    }
+
+    fn blend(&mut self) -> Result<()> {
+        let count = match self.stack.pop() {
+            Some(value) if value >= 0.0 => value as usize,
+            _ => raise!("found a malformed blend operator"),
+        };
+        if self.scalars.is_none() {
+            self.scalars = match self
+                .store
+                .and_then(|store| store.scalars(self.index, self.coordinates))
+            {
+                Some(scalars) => Some(scalars),
+                _ => raise!("found no variation data ({})", self.index),
+            };
+        }
+        let scalars = self.scalars.as_deref().unwrap_or_default();
+        let total = count * (scalars.len() + 1);
+        if self.stack.len() < total {
+            raise!("expected more operands");
+        }
+        let start = self.stack.len() - total;
+        for i in 0..count {
+            let offset = start + count + i * scalars.len();
+            let delta = self.stack[offset..offset + scalars.len()]
+                .iter()
+                .zip(scalars)
+                .map(|(delta, scalar)| delta * scalar)
+                .sum::<f32>();
+            self.stack[start + i] += delta;
+        }
+        self.stack.truncate(start + count);
+        Ok(())
+    }
+
+    fn move_to(&mut self, (dx, dy): (f32, f32)) {
+        self.point = (self.point.0 + dx, self.point.1 + dy);
+        self.outline.contours.push(Contour {
+            start: self.point,
+            segments: vec![],
+        });
+    }
+
+    fn line_to(&mut self, (dx, dy): (f32, f32)) {
+        self.point = (self.point.0 + dx, self.point.1 + dy);
+        self.push(Segment::Linear(self.point));
+    }
+
+    fn curve_to(&mut self, values: &[f32]) {
+        let first = (self.point.0 + values[0], self.point.1 + values[1]);
+        let second = (first.0 + values[2], first.1 + values[3]);
+        self.point = (second.0 + values[4], second.1 + values[5]);
+        self.push(Segment::Cubic(first, second, self.point));
+    }
+
+    fn push(&mut self, segment: Segment) {
+        if self.outline.contours.is_empty() {
+            self.outline.contours.push(Contour::default());
+        }
+        if let Some(contour) = self.outline.contours.last_mut() {
+            contour.segments.push(segment);
+        }
+    }
+}
+
+#[inline]
+fn bias(count: usize) -> i32 {
+    if count < 1240 {
+        107
+    } else if count < 33900 {
+        1131
+    } else {
+        32768
+    }
+}
@@ -2,9 +2,13 @@
 //!
 //! [1]: https://learn.microsoft.com/en-us/typography/opentype/spec/fvar
 
-use truetype::tables::names::NameID;
+use std::collections::HashMap;
+
+use truetype::tables::names::{NameID, Names};
 use truetype::{q32, Result, Tag};
 
+use crate::tables::AxisVariations;
+
 table! {
     @define
     /// A font-variations table.
@@ -50,10 +54,32 @@ table! {
         subfamily_name_id  (NameID       ), // subfamilyNameID
         flags              (InstanceFlags), // flags
         coordinates        (Vec<q32>     ), // coordinates
-        postscript_name_id (NameID       ), // postScriptNameID
+        postscript_name_id (Option<NameID>), // postScriptNameID
     }
 }
 
+/// A named instance.
+#[derive(Clone, Debug, Default)]
+pub struct Instance {
+    /// The subfamily name.
+    pub subfamily_name: Option<String>,
+    /// The PostScript name.
+    pub postscript_name: Option<String>,
+    /// The coordinates on the axes that are not hidden.
+    pub coordinates: Vec<Coordinate>,
+}
+
+/// A coordinate of a named instance.
+#[derive(Clone, Debug, Default)]
+pub struct Coordinate {
+    /// The tag of the axis.
+    pub tag: Tag,
+    /// The name of the axis.
+    pub name: Option<String>,
+    /// The user coordinate.
+    pub value: f32,
+}
+
 flags! {
     /// Axis flags.
     pub AxisFlags(u16) {
@@ -69,6 +95,92 @@ flags! {
     }
 }
 
+impl FontVariations {
+    /// Normalize user coordinates given per axis.
+    ///
+    /// The axes without coordinates take their default values. The coordinates are then
+    /// remapped by the axis variations if given.
+    pub fn normalize<T>(&self, values: T, axis_variations: Option<&AxisVariations>) -> Vec<f32>
+    where
+        T: IntoIterator<Item = (Tag, f32)>,
+    {
+        let mut coordinates = vec![0.0; self.axis_records.len()];
+        for (tag, value) in values {
+            for (coordinate, record) in coordinates.iter_mut().zip(self.axis_records.iter()) {
+                if record.tag == tag {
+                    *coordinate = record.normalize(value);
+                }
+            }
+        }
+        match axis_variations {
+            Some(axis_variations) => axis_variations.map(&coordinates),
+            _ => coordinates,
+        }
+    }
+
+    /// Return the named instances with names taken from a naming table.
+    ///
+    /// Names in English are preferred over names in other languages.
+    pub fn instances(&self, names: &Names) -> Vec<Instance> {
+        let language_tags = names.language_tags().collect::<Vec<_>>();
+        let mut strings = HashMap::new();
+        for ((_, _, language_id, name_id), value) in names.iter() {
+            if let Some(value) = value {
+                let tag = language_id.tag(&language_tags).unwrap_or_default();
+                if tag == "en" || tag.starts_with("en-") {
+                    strings.insert(name_id, value);
+                } else {
+                    strings.entry(name_id).or_insert(value);
+                }
+            }
+        }
+        self.instance_records
+            .iter()
+            .map(|record| Instance {
+                subfamily_name: strings.get(&record.subfamily_name_id).cloned(),
+                postscript_name: record
+                    .postscript_name_id
+                    .filter(|name_id| u16::from(*name_id) != 0xFFFF)
+                    .and_then(|name_id| strings.get(&name_id).cloned()),
+                coordinates: self
+                    .axis_records
+                    .iter()
+                    .zip(record.coordinates.iter())
+                    .filter(|(axis, _)| !axis.flags.is_hidden())
+                    .map(|(axis, value)| Coordinate {
+                        tag: axis.tag,
+                        name: strings.get(&axis.name_id).cloned(),
+                        value: f32::from(*value),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+impl AxisRecord {
+    /// Normalize a user coordinate.
+    ///
+    /// The coordinate is clamped to the range of the axis and mapped to the range from -1 to 1
+    /// with the default value mapped to 0.
+    pub fn normalize(&self, value: f32) -> f32 {
+        let (min, default, max) = (
+            f32::from(self.min_value),
+            f32::from(self.default_value),
+            f32::from(self.max_value),
+        );
+        let value = value.max(min).min(max);
+        let value = if value < default {
+            (value - default) / (default - min)
+        } else if value > default {
+            (value - default) / (max - default)
+        } else {
+            0.0
+        };
+        crate::variations::quantize(value)
+    }
+}
+
 impl crate::value::Read for FontVariations {
     fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
         let mut position = tape.position()?;
@@ -84,7 +196,7 @@ impl crate::value::Read for FontVariations {
         let instance_records = (0..header.instance_count as u64)
             .map(|i| {
                 tape.jump(position + i * header.instance_size as u64)?;
-                tape.take_given(header.axis_count)
+                tape.take_given((header.axis_count, header.instance_size))
             })
             .collect::<Result<Vec<_>>>()?;
         Ok(Self {
@@ -96,14 +208,21 @@ impl crate::value::Read for FontVariations {
 }
 
 impl crate::walue::Read<'static> for InstanceRecord {
-    type Parameter = u16;
+    type Parameter = (u16, u16);
 
-    fn read<T: crate::tape::Read>(tape: &mut T, axis_count: Self::Parameter) -> Result<Self> {
+    fn read<T: crate::tape::Read>(
+        tape: &mut T,
+        (axis_count, instance_size): Self::Parameter,
+    ) -> Result<Self> {
         Ok(Self {
             subfamily_name_id: tape.take()?,
             flags: tape.take()?,
             coordinates: tape.take_given(axis_count as usize)?,
-            postscript_name_id: tape.take()?,
+            postscript_name_id: if instance_size as usize >= 4 * axis_count as usize + 6 {
+                Some(tape.take()?)
+            } else {
+                None
+            },
         })
     }
 }
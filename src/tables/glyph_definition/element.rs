@@ -1,4 +1,8 @@
+use crate::layout::correction::Environment;
+use crate::layout::graph::{Graph, Object, Pack};
 use crate::layout::{Correction, Coverage};
+use crate::tape::Write;
+use crate::variations::item::Store;
 use crate::Result;
 
 table! {
@@ -130,9 +134,38 @@ table! {
     }
 }
 
+impl Caret {
+    /// Compute the coordinate in an environment unless given by a contour point.
+    pub fn resolve(&self, environment: &Environment) -> Option<f32> {
+        match self {
+            Self::Format1(value) => Some(value.coordinate as f32),
+            Self::Format2(_) => None,
+            Self::Format3(value) => {
+                Some(value.coordinate as f32 + value.correction.delta(environment))
+            }
+        }
+    }
+
+    /// Resolve the variation correction at given normalized axis coordinates into a plain
+    /// coordinate.
+    ///
+    /// Carets in format 3 with variation corrections are turned into carets in format 1.
+    pub fn instantiate(&mut self, store: &Store, coordinates: &[f32]) {
+        if let Self::Format3(value) = self {
+            if let Correction::Variation(ref variation) = value.correction {
+                let delta = variation.delta(store, coordinates);
+                *self = Self::Format1(Caret1 {
+                    format: 1,
+                    coordinate: (value.coordinate as f32 + delta).round() as i16,
+                });
+            }
+        }
+    }
+}
+
 impl crate::value::Read for Caret {
     fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
-        Ok(match tape.peek::<u32>()? {
+        Ok(match tape.peek::<u16>()? {
             1 => Self::Format1(tape.take()?),
             2 => Self::Format2(tape.take()?),
             3 => Self::Format3(tape.take()?),
@@ -140,3 +173,69 @@ impl crate::value::Read for Caret {
         })
     }
 }
+
+impl Pack for Attachments {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.link(self.coverage.pack(graph)?)?;
+        object.give(&(self.records.len() as u16))?;
+        for record in self.records.iter() {
+            let mut other = Object::default();
+            other.give(&(record.indices.len() as u16))?;
+            other.give(&record.indices[..])?;
+            object.link(graph.add(other))?;
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for Caret {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        match self {
+            Self::Format1(value) => {
+                object.give(&1u16)?;
+                object.give(&value.coordinate)?;
+            }
+            Self::Format2(value) => {
+                object.give(&2u16)?;
+                object.give(&value.index)?;
+            }
+            Self::Format3(value) => {
+                object.give(&3u16)?;
+                object.give(&value.coordinate)?;
+                object.link(value.correction.pack(graph)?)?;
+            }
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for Ligatures {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.link(self.coverage.pack(graph)?)?;
+        object.give(&(self.records.len() as u16))?;
+        for record in self.records.iter() {
+            let mut other = Object::default();
+            other.give(&(record.carets.len() as u16))?;
+            for caret in record.carets.iter() {
+                other.link(caret.pack(graph)?)?;
+            }
+            object.link(graph.add(other))?;
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for Marks {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&1u16)?;
+        object.give(&(self.coverages.len() as u16))?;
+        for coverage in self.coverages.iter() {
+            object.link_long(coverage.pack(graph)?)?;
+        }
+        Ok(graph.add(object))
+    }
+}
@@ -6,7 +6,9 @@ mod element;
 
 pub use element::*;
 
+use crate::layout::graph::{Graph, Object, Pack};
 use crate::layout::Class;
+use crate::tape::Write;
 use crate::variations::item::Store;
 use crate::Result;
 
@@ -74,7 +76,7 @@ table! {
             jump_take_maybe!(
                 tape,
                 position,
-                field!(this.header => mark_offset(0), Header::{Version13})
+                field!(this.header => variation_offset(0), Header::{Version13})
             )
         },
     }
@@ -133,6 +135,37 @@ table! {
     }
 }
 
+impl GlyphDefinition {
+    /// Resolve the variation corrections of the ligature carets at given normalized axis
+    /// coordinates into plain coordinates and drop the item variation store.
+    pub fn instantiate(&mut self, coordinates: &[f32]) {
+        let store = match self.variations.take() {
+            Some(store) => store,
+            _ => return,
+        };
+        if let Some(ref mut ligatures) = self.ligatures {
+            for caret in ligatures
+                .records
+                .iter_mut()
+                .flat_map(|record| record.carets.iter_mut())
+            {
+                caret.instantiate(&store, coordinates);
+            }
+        }
+        if let Header::Version13(value) = self.header {
+            self.header = Header::Version12(Header12 {
+                major_version: 1,
+                minor_version: 2,
+                glyph_class_offset: value.glyph_class_offset,
+                attachment_offset: value.attachment_offset,
+                ligature_offset: value.ligature_offset,
+                mark_class_offset: value.mark_class_offset,
+                mark_offset: value.mark_offset,
+            });
+        }
+    }
+}
+
 impl Default for Header {
     #[inline]
     fn default() -> Self {
@@ -150,3 +183,31 @@ impl crate::value::Read for Header {
         })
     }
 }
+
+impl Pack for GlyphDefinition {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        let minor_version: u16 = match (&self.header, &self.marks, &self.variations) {
+            (Header::Version13(..), _, _) | (_, _, Some(..)) => 3,
+            (Header::Version12(..), _, _) | (_, Some(..), _) => 2,
+            _ => 0,
+        };
+        object.give(&1u16)?;
+        object.give(&minor_version)?;
+        object.link_maybe(pack_maybe(graph, &self.glyph_class)?)?;
+        object.link_maybe(pack_maybe(graph, &self.attachments)?)?;
+        object.link_maybe(pack_maybe(graph, &self.ligatures)?)?;
+        object.link_maybe(pack_maybe(graph, &self.mark_class)?)?;
+        if minor_version >= 2 {
+            object.link_maybe(pack_maybe(graph, &self.marks)?)?;
+        }
+        if minor_version >= 3 {
+            object.link_long_maybe(pack_maybe(graph, &self.variations)?)?;
+        }
+        Ok(graph.add(object))
+    }
+}
+
+fn pack_maybe<T: Pack>(graph: &mut Graph, value: &Option<T>) -> Result<Option<usize>> {
+    value.as_ref().map(|value| value.pack(graph)).transpose()
+}
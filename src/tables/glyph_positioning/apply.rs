@@ -0,0 +1,304 @@
+use crate::layout::buffer::{Attachment, Buffer, Glyph};
+use crate::layout::correction::Environment;
+use crate::layout::lookup::{Apply, Lookups};
+use crate::layout::skip::Skip;
+use crate::tables::glyph_definition::GlyphDefinition;
+use crate::tables::glyph_positioning::{
+    Anchor, CursiveAttachment, GlyphPositioning, MarkToBaseAttachment, MarkToLigatureAttachment,
+    MarkToMarkAttachment, PairAdjustment, Single, SingleAdjustment, Type,
+};
+use crate::Result;
+
+impl GlyphPositioning {
+    /// Apply lookups to a glyph buffer.
+    ///
+    /// The lookups adjust the advances and offsets of the glyphs, which are expected to hold the
+    /// default advances beforehand. Once all lookups are applied, the offsets of attached glyphs
+    /// are resolved relative to the glyphs they are attached to, assuming the glyphs are laid out
+    /// from left to right. The glyph definition, if any, classifies the glyphs for the lookup
+    /// flags and mark attachments. The environment resolves the corrections of anchors and
+    /// values.
+    pub fn apply(
+        &self,
+        buffer: &mut Buffer,
+        lookup_indices: &[u16],
+        definition: Option<&GlyphDefinition>,
+        environment: &Environment,
+    ) -> Result<()> {
+        self.lookups
+            .apply(buffer, lookup_indices, definition, environment)?;
+        buffer.resolve();
+        Ok(())
+    }
+}
+
+impl Apply for Type {
+    type Parameter<'l> = Environment<'l>;
+
+    fn apply(
+        &self,
+        lookups: &Lookups<Self>,
+        buffer: &mut Buffer,
+        position: usize,
+        skip: Skip,
+        parameter: &Environment,
+        depth: usize,
+    ) -> Result<Option<usize>> {
+        Ok(match self {
+            Self::SingleAdjustment(value) => value.apply(buffer, position, parameter),
+            Self::PairAdjustment(value) => value.apply(buffer, position, skip, parameter),
+            Self::CursiveAttachment(value) => value.apply(buffer, position, skip, parameter),
+            Self::MarkToBaseAttachment(value) => value.apply(buffer, position, skip, parameter),
+            Self::MarkToLigatureAttachment(value) => value.apply(buffer, position, skip, parameter),
+            Self::MarkToMarkAttachment(value) => value.apply(buffer, position, skip, parameter),
+            Self::ContextualPositioning(value) => {
+                return value.apply(lookups, buffer, position, skip, parameter, depth);
+            }
+            Self::ChainedContextualPositioning(value) => {
+                return value.apply(lookups, buffer, position, skip, parameter, depth);
+            }
+            Self::ExtensionPositioning(value) => {
+                return value
+                    .table
+                    .apply(lookups, buffer, position, skip, parameter, depth);
+            }
+        })
+    }
+}
+
+impl SingleAdjustment {
+    fn apply(
+        &self,
+        buffer: &mut Buffer,
+        position: usize,
+        environment: &Environment,
+    ) -> Option<usize> {
+        let glyph = &mut buffer.glyphs[position];
+        match self {
+            Self::Format1(value) => {
+                value.coverage.index(glyph.glyph_id)?;
+                adjust(glyph, &value.value, environment);
+            }
+            Self::Format2(value) => {
+                let index = value.coverage.index(glyph.glyph_id)?;
+                adjust(glyph, value.values.get(index as usize)?, environment);
+            }
+        }
+        Some(position + 1)
+    }
+}
+
+impl PairAdjustment {
+    fn apply(
+        &self,
+        buffer: &mut Buffer,
+        position: usize,
+        skip: Skip,
+        environment: &Environment,
+    ) -> Option<usize> {
+        let next = buffer.next(position, skip)?;
+        let (glyph1_id, glyph2_id) = (
+            buffer.glyphs[position].glyph_id,
+            buffer.glyphs[next].glyph_id,
+        );
+        let (value1, value2, value2_flags) = match self {
+            Self::Format1(value) => {
+                let index = value.coverage.index(glyph1_id)?;
+                let records = &value.records.get(index as usize)?.records;
+                let i = records
+                    .binary_search_by_key(&glyph2_id, |record| record.glyph2_id)
+                    .ok()?;
+                (&records[i].value1, &records[i].value2, value.value2_flags)
+            }
+            Self::Format2(value) => {
+                value.coverage.index(glyph1_id)?;
+                let record = value
+                    .records
+                    .get(value.class1.get(glyph1_id) as usize)?
+                    .records
+                    .get(value.class2.get(glyph2_id) as usize)?;
+                (&record.value1, &record.value2, value.value2_flags)
+            }
+        };
+        if let Some(value) = value1 {
+            adjust(&mut buffer.glyphs[position], value, environment);
+        }
+        if let Some(value) = value2 {
+            adjust(&mut buffer.glyphs[next], value, environment);
+        }
+        // The second glyph can start another pair unless it has been adjusted itself.
+        if value2_flags.0 == 0 {
+            Some(next)
+        } else {
+            Some(next + 1)
+        }
+    }
+}
+
+impl CursiveAttachment {
+    fn apply(
+        &self,
+        buffer: &mut Buffer,
+        position: usize,
+        skip: Skip,
+        environment: &Environment,
+    ) -> Option<usize> {
+        let index = self.coverage.index(buffer.glyphs[position].glyph_id)?;
+        let (exit_x, exit_y) = self
+            .connections
+            .get(index as usize)?
+            .end_anchor
+            .as_ref()?
+            .resolve(environment);
+        let next = buffer.next(position, skip)?;
+        let index = self.coverage.index(buffer.glyphs[next].glyph_id)?;
+        let (entry_x, entry_y) = self
+            .connections
+            .get(index as usize)?
+            .start_anchor
+            .as_ref()?
+            .resolve(environment);
+        let glyph = &mut buffer.glyphs[position];
+        glyph.x_advance = exit_x.round() as i32 + glyph.x_offset;
+        let glyph = &mut buffer.glyphs[next];
+        let delta = entry_x.round() as i32 + glyph.x_offset;
+        glyph.x_advance -= delta;
+        glyph.x_offset -= delta;
+        let (child, parent, y_offset) = if skip.flags.is_right_to_left() {
+            (position, next, (entry_y - exit_y).round() as i32)
+        } else {
+            (next, position, (exit_y - entry_y).round() as i32)
+        };
+        // An attachment in the opposite direction would create a cycle.
+        if buffer.glyphs[parent].attachment == Some(Attachment::Cursive(child)) {
+            buffer.glyphs[parent].attachment = None;
+        }
+        let glyph = &mut buffer.glyphs[child];
+        glyph.y_offset = y_offset;
+        glyph.attachment = Some(Attachment::Cursive(parent));
+        Some(next)
+    }
+}
+
+impl MarkToBaseAttachment {
+    fn apply(
+        &self,
+        buffer: &mut Buffer,
+        position: usize,
+        skip: Skip,
+        environment: &Environment,
+    ) -> Option<usize> {
+        let index = self.mark_coverage.index(buffer.glyphs[position].glyph_id)?;
+        let mark = self.marks.records.get(index as usize)?;
+        let base = (0..position)
+            .rev()
+            .find(|&i| !skip.skips(&buffer.glyphs[i]) && skip.class(&buffer.glyphs[i]) != 3)?;
+        let index = self.base_coverage.index(buffer.glyphs[base].glyph_id)?;
+        let anchor = self
+            .bases
+            .records
+            .get(index as usize)?
+            .anchors
+            .get(mark.class_id as usize)?
+            .as_ref()?;
+        attach(buffer, position, base, &mark.anchor, anchor, environment)
+    }
+}
+
+impl MarkToLigatureAttachment {
+    fn apply(
+        &self,
+        buffer: &mut Buffer,
+        position: usize,
+        skip: Skip,
+        environment: &Environment,
+    ) -> Option<usize> {
+        let index = self.mark_coverage.index(buffer.glyphs[position].glyph_id)?;
+        let mark = self.marks.records.get(index as usize)?;
+        let ligature = (0..position)
+            .rev()
+            .find(|&i| !skip.skips(&buffer.glyphs[i]) && skip.class(&buffer.glyphs[i]) != 3)?;
+        let index = self
+            .ligature_coverage
+            .index(buffer.glyphs[ligature].glyph_id)?;
+        let components = &self.ligatures.records.get(index as usize)?.components;
+        if components.is_empty() {
+            return None;
+        }
+        // The mark goes to the component it has been placed after during the substitution or to
+        // the last one otherwise.
+        let (glyph, other) = (&buffer.glyphs[position], &buffer.glyphs[ligature]);
+        let component = if glyph.ligature_id != 0
+            && glyph.ligature_id == other.ligature_id
+            && glyph.component_index > 0
+        {
+            (glyph.component_index as usize).min(components.len()) - 1
+        } else {
+            components.len() - 1
+        };
+        let anchor = components[component]
+            .anchors
+            .get(mark.class_id as usize)?
+            .as_ref()?;
+        attach(
+            buffer,
+            position,
+            ligature,
+            &mark.anchor,
+            anchor,
+            environment,
+        )
+    }
+}
+
+impl MarkToMarkAttachment {
+    fn apply(
+        &self,
+        buffer: &mut Buffer,
+        position: usize,
+        skip: Skip,
+        environment: &Environment,
+    ) -> Option<usize> {
+        let index = self
+            .mark1_coverage
+            .index(buffer.glyphs[position].glyph_id)?;
+        let mark = self.mark1s.records.get(index as usize)?;
+        let other = buffer.previous(position, skip)?;
+        let index = self.mark2_coverage.index(buffer.glyphs[other].glyph_id)?;
+        let anchor = self
+            .mark2s
+            .records
+            .get(index as usize)?
+            .anchors
+            .get(mark.class_id as usize)?
+            .as_ref()?;
+        attach(buffer, position, other, &mark.anchor, anchor, environment)
+    }
+}
+
+fn adjust(glyph: &mut Glyph, value: &Single, environment: &Environment) {
+    let (x_placement, y_placement, x_advance, y_advance) = value.resolve(environment);
+    glyph.x_offset += x_placement.round() as i32;
+    glyph.y_offset += y_placement.round() as i32;
+    glyph.x_advance += x_advance.round() as i32;
+    glyph.y_advance += y_advance.round() as i32;
+}
+
+fn attach(
+    buffer: &mut Buffer,
+    position: usize,
+    other: usize,
+    anchor: &Anchor,
+    other_anchor: &Anchor,
+    environment: &Environment,
+) -> Option<usize> {
+    let ((x, y), (other_x, other_y)) = (
+        anchor.resolve(environment),
+        other_anchor.resolve(environment),
+    );
+    let glyph = &mut buffer.glyphs[position];
+    glyph.x_offset = (other_x - x).round() as i32;
+    glyph.y_offset = (other_y - y).round() as i32;
+    glyph.attachment = Some(Attachment::Mark(other));
+    Some(position + 1)
+}
@@ -1,6 +1,10 @@
 use truetype::GlyphID;
 
+use crate::layout::correction::Environment;
+use crate::layout::graph::{Graph, Object, Pack};
 use crate::layout::Correction;
+use crate::tape::Write;
+use crate::variations::item::Store;
 use crate::Result;
 
 /// An anchor.
@@ -236,6 +240,46 @@ impl Default for Anchor {
     }
 }
 
+impl Anchor {
+    /// Compute the coordinates in an environment.
+    ///
+    /// The contour points of anchors in format 2 are not taken into account.
+    pub fn resolve(&self, environment: &Environment) -> (f32, f32) {
+        match self {
+            Self::Format1(value) => (value.x as f32, value.y as f32),
+            Self::Format2(value) => (value.x as f32, value.y as f32),
+            Self::Format3(value) => (
+                value.x as f32 + delta(&value.x_correction, environment),
+                value.y as f32 + delta(&value.y_correction, environment),
+            ),
+        }
+    }
+
+    /// Resolve the variation corrections at given normalized axis coordinates into plain
+    /// coordinates.
+    ///
+    /// Anchors in format 3 without corrections left are turned into anchors in format 1.
+    pub fn instantiate(&mut self, store: &Store, coordinates: &[f32]) {
+        if let Self::Format3(value) = self {
+            instantiate(&mut value.x, &mut value.x_correction, store, coordinates);
+            instantiate(&mut value.y, &mut value.y_correction, store, coordinates);
+            if value.x_correction.is_none() {
+                value.x_correction_offset = 0;
+            }
+            if value.y_correction.is_none() {
+                value.y_correction_offset = 0;
+            }
+            if value.x_correction.is_none() && value.y_correction.is_none() {
+                *self = Self::Format1(Anchor1 {
+                    format: 1,
+                    x: value.x,
+                    y: value.y,
+                });
+            }
+        }
+    }
+}
+
 impl crate::value::Read for Anchor {
     fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
         Ok(match tape.peek::<u16>()? {
@@ -470,6 +514,53 @@ impl crate::walue::Read<'static> for Connection {
     }
 }
 
+impl Single {
+    /// Compute the horizontal placement, vertical placement, horizontal advance, and vertical
+    /// advance in an environment.
+    pub fn resolve(&self, environment: &Environment) -> (f32, f32, f32, f32) {
+        macro_rules! resolve(
+            ($value:ident, $correction:ident) => (
+                self.$value.unwrap_or(0) as f32 + delta(&self.$correction, environment)
+            );
+        );
+        (
+            resolve!(x_placement, x_placement_correction),
+            resolve!(y_placement, y_placement_correction),
+            resolve!(x_advance, x_advance_correction),
+            resolve!(y_advance, y_advance_correction),
+        )
+    }
+
+    /// Resolve the variation corrections at given normalized axis coordinates into plain
+    /// values.
+    ///
+    /// The values having variation corrections become present even if they were absent.
+    pub fn instantiate(&mut self, store: &Store, coordinates: &[f32]) {
+        macro_rules! instantiate(
+            ($value:ident, $offset:ident, $correction:ident) => (
+                if let Some(Correction::Variation(_)) = self.$correction {
+                    let mut value = self.$value.unwrap_or(0);
+                    instantiate(&mut value, &mut self.$correction, store, coordinates);
+                    self.$value = Some(value);
+                    self.$offset = None;
+                }
+            );
+        );
+        instantiate!(
+            x_placement,
+            x_placement_correction_offset,
+            x_placement_correction
+        );
+        instantiate!(
+            y_placement,
+            y_placement_correction_offset,
+            y_placement_correction
+        );
+        instantiate!(x_advance, x_advance_correction_offset, x_advance_correction);
+        instantiate!(y_advance, y_advance_correction_offset, y_advance_correction);
+    }
+}
+
 impl crate::walue::Read<'static> for Single {
     type Parameter = (u64, Flags);
 
@@ -523,3 +614,216 @@ impl crate::walue::Read<'static> for Single {
         })
     }
 }
+
+impl Pack for Anchor {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        match self {
+            Anchor::Format1(value) => {
+                object.give(&1u16)?;
+                object.give(&value.x)?;
+                object.give(&value.y)?;
+            }
+            Anchor::Format2(value) => {
+                object.give(&2u16)?;
+                object.give(&value.x)?;
+                object.give(&value.y)?;
+                object.give(&value.index)?;
+            }
+            Anchor::Format3(value) => {
+                object.give(&3u16)?;
+                object.give(&value.x)?;
+                object.give(&value.y)?;
+                object.link_maybe(pack_maybe(graph, &value.x_correction)?)?;
+                object.link_maybe(pack_maybe(graph, &value.y_correction)?)?;
+            }
+        }
+        Ok(graph.add(object))
+    }
+}
+
+macro_rules! implement {
+    ($($type:ident,)*) => {
+        $(
+            impl Pack for $type {
+                fn pack(&self, graph: &mut Graph) -> Result<usize> {
+                    let mut object = Object::default();
+                    object.give(&(self.records.len() as u16))?;
+                    for record in self.records.iter() {
+                        for anchor in record.anchors.iter() {
+                            object.link_maybe(pack_maybe(graph, anchor)?)?;
+                        }
+                    }
+                    Ok(graph.add(object))
+                }
+            }
+        )*
+    };
+}
+
+implement! {
+    Bases,
+    Mark2s,
+}
+
+impl Pack for Ligature {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&(self.components.len() as u16))?;
+        for component in self.components.iter() {
+            for anchor in component.anchors.iter() {
+                object.link_maybe(pack_maybe(graph, anchor)?)?;
+            }
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for Ligatures {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&(self.records.len() as u16))?;
+        for record in self.records.iter() {
+            object.link(record.pack(graph)?)?;
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for Mark1s {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&(self.records.len() as u16))?;
+        for record in self.records.iter() {
+            object.give(&record.class_id)?;
+            object.link(record.anchor.pack(graph)?)?;
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Connection {
+    pub(crate) fn embed(&self, graph: &mut Graph, object: &mut Object) -> Result<()> {
+        object.link_maybe(pack_maybe(graph, &self.start_anchor)?)?;
+        object.link_maybe(pack_maybe(graph, &self.end_anchor)?)
+    }
+}
+
+impl Pair1s {
+    pub(crate) fn pack_given(
+        &self,
+        graph: &mut Graph,
+        (value1_flags, value2_flags): (Flags, Flags),
+    ) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&(self.records.len() as u16))?;
+        for record in self.records.iter() {
+            object.give(&record.glyph2_id)?;
+            embed_maybe(graph, &mut object, &record.value1, value1_flags)?;
+            embed_maybe(graph, &mut object, &record.value2, value2_flags)?;
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pair2s {
+    pub(crate) fn embed(
+        &self,
+        graph: &mut Graph,
+        object: &mut Object,
+        (value1_flags, value2_flags): (Flags, Flags),
+    ) -> Result<()> {
+        for record in self.records.iter() {
+            embed_maybe(graph, object, &record.value1, value1_flags)?;
+            embed_maybe(graph, object, &record.value2, value2_flags)?;
+        }
+        Ok(())
+    }
+}
+
+impl Single {
+    /// Return the flags of the values and corrections that are present.
+    pub(crate) fn flags(&self) -> Flags {
+        let mut value = 0;
+        for (i, present) in [
+            self.x_placement.is_some(),
+            self.y_placement.is_some(),
+            self.x_advance.is_some(),
+            self.y_advance.is_some(),
+            self.x_placement_correction.is_some(),
+            self.y_placement_correction.is_some(),
+            self.x_advance_correction.is_some(),
+            self.y_advance_correction.is_some(),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            if present {
+                value |= 1 << i;
+            }
+        }
+        Flags(value)
+    }
+
+    pub(crate) fn embed(&self, graph: &mut Graph, object: &mut Object, flags: Flags) -> Result<()> {
+        macro_rules! give(
+            ($flag:ident, $field:ident) => (
+                if flags.$flag() {
+                    object.give(&self.$field.unwrap_or(0))?;
+                }
+            );
+        );
+        macro_rules! link(
+            ($flag:ident, $field:ident) => (
+                if flags.$flag() {
+                    object.link_maybe(pack_maybe(graph, &self.$field)?)?;
+                }
+            );
+        );
+        give!(has_x_placement, x_placement);
+        give!(has_y_placement, y_placement);
+        give!(has_x_advance, x_advance);
+        give!(has_y_advance, y_advance);
+        link!(has_x_placement_correction, x_placement_correction);
+        link!(has_y_placement_correction, y_placement_correction);
+        link!(has_x_advance_correction, x_advance_correction);
+        link!(has_y_advance_correction, y_advance_correction);
+        Ok(())
+    }
+}
+
+fn embed_maybe(
+    graph: &mut Graph,
+    object: &mut Object,
+    value: &Option<Single>,
+    flags: Flags,
+) -> Result<()> {
+    match value {
+        Some(value) => value.embed(graph, object, flags),
+        _ => Single::default().embed(graph, object, flags),
+    }
+}
+
+fn pack_maybe<T: Pack>(graph: &mut Graph, value: &Option<T>) -> Result<Option<usize>> {
+    value.as_ref().map(|value| value.pack(graph)).transpose()
+}
+
+#[inline]
+fn delta(correction: &Option<Correction>, environment: &Environment) -> f32 {
+    correction
+        .as_ref()
+        .map_or(0.0, |correction| correction.delta(environment))
+}
+
+fn instantiate(
+    value: &mut i16,
+    correction: &mut Option<Correction>,
+    store: &Store,
+    coordinates: &[f32],
+) {
+    if let Some(Correction::Variation(ref variation)) = correction {
+        let delta = variation.delta(store, coordinates);
+        *value = (*value as f32 + delta).round() as i16;
+        *correction = None;
+    }
+}
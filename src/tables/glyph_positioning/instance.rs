@@ -0,0 +1,174 @@
+use crate::tables::glyph_positioning::{
+    CursiveAttachment, Flags, GlyphPositioning, MarkToBaseAttachment, MarkToLigatureAttachment,
+    MarkToMarkAttachment, PairAdjustment, Single, SingleAdjustment, Type,
+};
+use crate::variations::item::Store;
+
+impl GlyphPositioning {
+    /// Resolve the variation corrections at given normalized axis coordinates into plain values.
+    ///
+    /// The store is the item variation store of the glyph definition. Device corrections are
+    /// left as they are.
+    pub fn instantiate(&mut self, store: &Store, coordinates: &[f32]) {
+        for record in self.lookups.records.iter_mut() {
+            for table in record.tables.iter_mut() {
+                table.instantiate(store, coordinates);
+            }
+        }
+    }
+}
+
+impl Type {
+    fn instantiate(&mut self, store: &Store, coordinates: &[f32]) {
+        match self {
+            Self::SingleAdjustment(value) => value.instantiate(store, coordinates),
+            Self::PairAdjustment(value) => value.instantiate(store, coordinates),
+            Self::CursiveAttachment(value) => value.instantiate(store, coordinates),
+            Self::MarkToBaseAttachment(value) => value.instantiate(store, coordinates),
+            Self::MarkToLigatureAttachment(value) => value.instantiate(store, coordinates),
+            Self::MarkToMarkAttachment(value) => value.instantiate(store, coordinates),
+            Self::ExtensionPositioning(value) => value.table.instantiate(store, coordinates),
+            _ => {}
+        }
+    }
+}
+
+impl SingleAdjustment {
+    fn instantiate(&mut self, store: &Store, coordinates: &[f32]) {
+        match self {
+            Self::Format1(value) => {
+                value.value.instantiate(store, coordinates);
+                value.value_flags = flags(value.value_flags, [&value.value]);
+            }
+            Self::Format2(value) => {
+                for single in value.values.iter_mut() {
+                    single.instantiate(store, coordinates);
+                }
+                value.value_flags = flags(value.value_flags, &value.values);
+            }
+        }
+    }
+}
+
+impl PairAdjustment {
+    fn instantiate(&mut self, store: &Store, coordinates: &[f32]) {
+        macro_rules! instantiate(
+            ($value:ident) => ({
+                let records = $value
+                    .records
+                    .iter_mut()
+                    .flat_map(|record| record.records.iter_mut());
+                for record in records {
+                    instantiate(&mut record.value1, store, coordinates);
+                    instantiate(&mut record.value2, store, coordinates);
+                }
+                let records = $value
+                    .records
+                    .iter()
+                    .flat_map(|record| record.records.iter());
+                $value.value1_flags = flags(
+                    $value.value1_flags,
+                    records.clone().filter_map(|record| record.value1.as_ref()),
+                );
+                $value.value2_flags = flags(
+                    $value.value2_flags,
+                    records.filter_map(|record| record.value2.as_ref()),
+                );
+            });
+        );
+        match self {
+            Self::Format1(value) => instantiate!(value),
+            Self::Format2(value) => instantiate!(value),
+        }
+    }
+}
+
+impl CursiveAttachment {
+    fn instantiate(&mut self, store: &Store, coordinates: &[f32]) {
+        let anchors = self
+            .connections
+            .iter_mut()
+            .flat_map(|record| [&mut record.start_anchor, &mut record.end_anchor]);
+        for anchor in anchors.flatten() {
+            anchor.instantiate(store, coordinates);
+        }
+    }
+}
+
+impl MarkToBaseAttachment {
+    fn instantiate(&mut self, store: &Store, coordinates: &[f32]) {
+        let anchors = self
+            .marks
+            .records
+            .iter_mut()
+            .map(|record| &mut record.anchor)
+            .chain(
+                self.bases
+                    .records
+                    .iter_mut()
+                    .flat_map(|record| record.anchors.iter_mut().flatten()),
+            );
+        for anchor in anchors {
+            anchor.instantiate(store, coordinates);
+        }
+    }
+}
+
+impl MarkToLigatureAttachment {
+    fn instantiate(&mut self, store: &Store, coordinates: &[f32]) {
+        let anchors = self
+            .marks
+            .records
+            .iter_mut()
+            .map(|record| &mut record.anchor)
+            .chain(
+                self.ligatures
+                    .records
+                    .iter_mut()
+                    .flat_map(|record| record.components.iter_mut())
+                    .flat_map(|record| record.anchors.iter_mut().flatten()),
+            );
+        for anchor in anchors {
+            anchor.instantiate(store, coordinates);
+        }
+    }
+}
+
+impl MarkToMarkAttachment {
+    fn instantiate(&mut self, store: &Store, coordinates: &[f32]) {
+        let anchors = self
+            .mark1s
+            .records
+            .iter_mut()
+            .map(|record| &mut record.anchor)
+            .chain(
+                self.mark2s
+                    .records
+                    .iter_mut()
+                    .flat_map(|record| record.anchors.iter_mut().flatten()),
+            );
+        for anchor in anchors {
+            anchor.instantiate(store, coordinates);
+        }
+    }
+}
+
+// The flags of the values are kept, whereas the flags of the corrections are recomputed, since
+// some of the corrections might have been resolved.
+fn flags<'l, T>(flags: Flags, values: T) -> Flags
+where
+    T: IntoIterator<Item = &'l Single>,
+{
+    values
+        .into_iter()
+        .fold(Flags(flags.0 & 0x000F), |flags, value| {
+            Flags(flags.0 | value.flags().0)
+        })
+}
+
+#[inline]
+fn instantiate(value: &mut Option<Single>, store: &Store, coordinates: &[f32]) {
+    if let Some(value) = value {
+        value.instantiate(store, coordinates);
+    }
+}
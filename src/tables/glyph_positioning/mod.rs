@@ -2,11 +2,15 @@
 //!
 //! [1]: https://learn.microsoft.com/en-us/typography/opentype/spec/gpos
 
+mod apply;
 mod element;
+mod instance;
 
 pub use element::*;
 
+use crate::layout::graph::{Graph, Object, Pack, Subtable};
 use crate::layout::{ChainedContext, Class, Context, Coverage, Directory};
+use crate::tape::Write;
 use crate::Result;
 
 /// A glyph positioning.
@@ -253,13 +257,15 @@ table! {
     }
 }
 
-table! {
-    /// An extension positioning.
-    pub ExtensionPositioning { // ExtensionPosFormat1
-        format (u16) = { 1 }, // posFormat
-        r#type (u16), // extensionLookupType
-        offset (u32), // extensionOffset
-    }
+/// An extension positioning.
+#[derive(Clone, Debug)]
+#[rustfmt::skip]
+pub struct ExtensionPositioning { // ExtensionPosFormat1
+    pub format: u16, // posFormat
+    pub r#type: u16, // extensionLookupType
+    pub offset: u32, // extensionOffset
+
+    pub table: Box<Type>,
 }
 
 impl crate::walue::Read<'static> for Type {
@@ -281,6 +287,28 @@ impl crate::walue::Read<'static> for Type {
     }
 }
 
+impl crate::value::Read for ExtensionPositioning {
+    fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
+        let position = tape.position()?;
+        let format = tape.take()?;
+        if format != 1 {
+            raise!("found an unknown format of the extension positioning ({format})");
+        }
+        let r#type = tape.take()?;
+        if r#type == 9 {
+            raise!("found a nested extension positioning");
+        }
+        let offset = tape.take()?;
+        let table = jump_take_given!(@unwrap tape, position, offset, r#type);
+        Ok(Self {
+            format,
+            r#type,
+            offset,
+            table: Box::new(table),
+        })
+    }
+}
+
 impl crate::value::Read for SingleAdjustment {
     fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
         Ok(match tape.peek::<u16>()? {
@@ -300,3 +328,397 @@ impl crate::value::Read for PairAdjustment {
         })
     }
 }
+
+impl Pack for Type {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        match self {
+            Self::SingleAdjustment(value) => value.pack(graph),
+            Self::PairAdjustment(value) => value.pack(graph),
+            Self::CursiveAttachment(value) => value.pack(graph),
+            Self::MarkToBaseAttachment(value) => value.pack(graph),
+            Self::MarkToLigatureAttachment(value) => value.pack(graph),
+            Self::MarkToMarkAttachment(value) => value.pack(graph),
+            Self::ContextualPositioning(value) => value.pack(graph),
+            Self::ChainedContextualPositioning(value) => value.pack(graph),
+            Self::ExtensionPositioning(value) => value.pack(graph),
+        }
+    }
+}
+
+impl Subtable for Type {
+    const EXTENSION: u16 = 9;
+
+    fn split(&self) -> Option<(Self, Self)> {
+        macro_rules! split(
+            ($variant:ident, $value:expr) => (
+                $value.split().map(|(first, second)| {
+                    (Self::$variant(first), Self::$variant(second))
+                })
+            );
+        );
+        match self {
+            Self::SingleAdjustment(value) => split!(SingleAdjustment, value),
+            Self::PairAdjustment(value) => split!(PairAdjustment, value),
+            Self::CursiveAttachment(value) => split!(CursiveAttachment, value),
+            Self::MarkToBaseAttachment(value) => split!(MarkToBaseAttachment, value),
+            Self::MarkToLigatureAttachment(value) => split!(MarkToLigatureAttachment, value),
+            Self::MarkToMarkAttachment(value) => split!(MarkToMarkAttachment, value),
+            Self::ExtensionPositioning(value) => split!(ExtensionPositioning, value),
+            _ => None,
+        }
+    }
+}
+
+impl SingleAdjustment {
+    fn split(&self) -> Option<(Self, Self)> {
+        match self {
+            Self::Format1(value) => {
+                let (_, first, second) = value.coverage.split()?;
+                Some((
+                    Self::Format1(SingleAdjustment1 {
+                        coverage: first,
+                        ..value.clone()
+                    }),
+                    Self::Format1(SingleAdjustment1 {
+                        coverage: second,
+                        ..value.clone()
+                    }),
+                ))
+            }
+            Self::Format2(value) => {
+                let ((first, head), (second, tail)) = halve(&value.coverage, &value.values)?;
+                Some((
+                    Self::Format2(SingleAdjustment2 {
+                        value_count: head.len() as u16,
+                        values: head,
+                        coverage: first,
+                        ..value.clone()
+                    }),
+                    Self::Format2(SingleAdjustment2 {
+                        value_count: tail.len() as u16,
+                        values: tail,
+                        coverage: second,
+                        ..value.clone()
+                    }),
+                ))
+            }
+        }
+    }
+}
+
+impl PairAdjustment {
+    fn split(&self) -> Option<(Self, Self)> {
+        match self {
+            Self::Format1(value) => {
+                let ((first, head), (second, tail)) = halve(&value.coverage, &value.records)?;
+                Some((
+                    Self::Format1(PairAdjustment1 {
+                        record_count: head.len() as u16,
+                        record_offsets: vec![0; head.len()],
+                        records: head,
+                        coverage: first,
+                        ..value.clone()
+                    }),
+                    Self::Format1(PairAdjustment1 {
+                        record_count: tail.len() as u16,
+                        record_offsets: vec![0; tail.len()],
+                        records: tail,
+                        coverage: second,
+                        ..value.clone()
+                    }),
+                ))
+            }
+            Self::Format2(value) => {
+                // The second half takes the upper classes, which get renumbered starting from one,
+                // since class zero is reserved for all the glyphs that are not listed.
+                let middle = value.records.len() / 2;
+                if middle == 0 {
+                    return None;
+                }
+                let mut pairs = value.class1.iter().collect::<Vec<_>>();
+                pairs.sort_unstable();
+                let (head, tail): (Vec<_>, Vec<_>) = value
+                    .coverage
+                    .glyph_ids()
+                    .into_iter()
+                    .partition(|glyph_id| (value.class1.get(*glyph_id) as usize) < middle);
+                if head.is_empty() || tail.is_empty() {
+                    return None;
+                }
+                let class2_count = value.records[0].records.len();
+                let mut records = vec![Pair2s {
+                    records: vec![Default::default(); class2_count],
+                }];
+                records.extend(value.records[middle..].iter().cloned());
+                let (first, second): (Vec<_>, Vec<_>) = pairs
+                    .into_iter()
+                    .partition(|(_, class)| (*class as usize) < middle);
+                let second = second
+                    .into_iter()
+                    .map(|(glyph_id, class)| (glyph_id, class - middle as u16 + 1))
+                    .collect::<Vec<_>>();
+                Some((
+                    Self::Format2(PairAdjustment2 {
+                        class1_count: middle as u16,
+                        records: value.records[..middle].to_vec(),
+                        coverage: Coverage::from_glyph_ids(&head),
+                        class1: Class::from_pairs(&first),
+                        ..value.clone()
+                    }),
+                    Self::Format2(PairAdjustment2 {
+                        class1_count: records.len() as u16,
+                        records,
+                        coverage: Coverage::from_glyph_ids(&tail),
+                        class1: Class::from_pairs(&second),
+                        ..value.clone()
+                    }),
+                ))
+            }
+        }
+    }
+}
+
+impl CursiveAttachment {
+    fn split(&self) -> Option<(Self, Self)> {
+        let ((first, head), (second, tail)) = halve(&self.coverage, &self.connections)?;
+        Some((
+            Self {
+                connection_count: head.len() as u16,
+                connections: head,
+                coverage: first,
+                ..self.clone()
+            },
+            Self {
+                connection_count: tail.len() as u16,
+                connections: tail,
+                coverage: second,
+                ..self.clone()
+            },
+        ))
+    }
+}
+
+impl MarkToBaseAttachment {
+    fn split(&self) -> Option<(Self, Self)> {
+        let ((first, head), (second, tail)) = halve(&self.base_coverage, &self.bases.records)?;
+        Some((
+            Self {
+                base_coverage: first,
+                bases: Bases {
+                    count: head.len() as u16,
+                    records: head,
+                },
+                ..self.clone()
+            },
+            Self {
+                base_coverage: second,
+                bases: Bases {
+                    count: tail.len() as u16,
+                    records: tail,
+                },
+                ..self.clone()
+            },
+        ))
+    }
+}
+
+impl MarkToLigatureAttachment {
+    fn split(&self) -> Option<(Self, Self)> {
+        let ((first, head), (second, tail)) =
+            halve(&self.ligature_coverage, &self.ligatures.records)?;
+        Some((
+            Self {
+                ligature_coverage: first,
+                ligatures: Ligatures {
+                    count: head.len() as u16,
+                    offsets: vec![0; head.len()],
+                    records: head,
+                },
+                ..self.clone()
+            },
+            Self {
+                ligature_coverage: second,
+                ligatures: Ligatures {
+                    count: tail.len() as u16,
+                    offsets: vec![0; tail.len()],
+                    records: tail,
+                },
+                ..self.clone()
+            },
+        ))
+    }
+}
+
+impl MarkToMarkAttachment {
+    fn split(&self) -> Option<(Self, Self)> {
+        let ((first, head), (second, tail)) = halve(&self.mark2_coverage, &self.mark2s.records)?;
+        Some((
+            Self {
+                mark2_coverage: first,
+                mark2s: Mark2s {
+                    count: head.len() as u16,
+                    records: head,
+                },
+                ..self.clone()
+            },
+            Self {
+                mark2_coverage: second,
+                mark2s: Mark2s {
+                    count: tail.len() as u16,
+                    records: tail,
+                },
+                ..self.clone()
+            },
+        ))
+    }
+}
+
+impl Pack for SingleAdjustment {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        match self {
+            Self::Format1(value) => {
+                object.give(&1u16)?;
+                object.link(value.coverage.pack(graph)?)?;
+                object.give(&value.value_flags)?;
+                value.value.embed(graph, &mut object, value.value_flags)?;
+            }
+            Self::Format2(value) => {
+                object.give(&2u16)?;
+                object.link(value.coverage.pack(graph)?)?;
+                object.give(&value.value_flags)?;
+                object.give(&(value.values.len() as u16))?;
+                for single in value.values.iter() {
+                    single.embed(graph, &mut object, value.value_flags)?;
+                }
+            }
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for PairAdjustment {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        match self {
+            Self::Format1(value) => {
+                let flags = (value.value1_flags, value.value2_flags);
+                object.give(&1u16)?;
+                object.link(value.coverage.pack(graph)?)?;
+                object.give(&value.value1_flags)?;
+                object.give(&value.value2_flags)?;
+                object.give(&(value.records.len() as u16))?;
+                for record in value.records.iter() {
+                    object.link(record.pack_given(graph, flags)?)?;
+                }
+            }
+            Self::Format2(value) => {
+                let flags = (value.value1_flags, value.value2_flags);
+                let class2_count = match value.records.first() {
+                    Some(record) => record.records.len() as u16,
+                    _ => value.class2_count,
+                };
+                object.give(&2u16)?;
+                object.link(value.coverage.pack(graph)?)?;
+                object.give(&value.value1_flags)?;
+                object.give(&value.value2_flags)?;
+                object.link(value.class1.pack(graph)?)?;
+                object.link(value.class2.pack(graph)?)?;
+                object.give(&(value.records.len() as u16))?;
+                object.give(&class2_count)?;
+                for record in value.records.iter() {
+                    record.embed(graph, &mut object, flags)?;
+                }
+            }
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for CursiveAttachment {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&1u16)?;
+        object.link(self.coverage.pack(graph)?)?;
+        object.give(&(self.connections.len() as u16))?;
+        for connection in self.connections.iter() {
+            connection.embed(graph, &mut object)?;
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for MarkToBaseAttachment {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&1u16)?;
+        object.link(self.mark_coverage.pack(graph)?)?;
+        object.link(self.base_coverage.pack(graph)?)?;
+        object.give(&self.mark_class_count)?;
+        object.link(self.marks.pack(graph)?)?;
+        object.link(self.bases.pack(graph)?)?;
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for MarkToLigatureAttachment {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&1u16)?;
+        object.link(self.mark_coverage.pack(graph)?)?;
+        object.link(self.ligature_coverage.pack(graph)?)?;
+        object.give(&self.mark_class_count)?;
+        object.link(self.marks.pack(graph)?)?;
+        object.link(self.ligatures.pack(graph)?)?;
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for MarkToMarkAttachment {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&1u16)?;
+        object.link(self.mark1_coverage.pack(graph)?)?;
+        object.link(self.mark2_coverage.pack(graph)?)?;
+        object.give(&self.mark_class_count)?;
+        object.link(self.mark1s.pack(graph)?)?;
+        object.link(self.mark2s.pack(graph)?)?;
+        Ok(graph.add(object))
+    }
+}
+
+impl ExtensionPositioning {
+    fn split(&self) -> Option<(Self, Self)> {
+        let (first, second) = self.table.split()?;
+        Some((
+            Self {
+                table: Box::new(first),
+                ..self.clone()
+            },
+            Self {
+                table: Box::new(second),
+                ..self.clone()
+            },
+        ))
+    }
+}
+
+impl Pack for ExtensionPositioning {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&1u16)?;
+        object.give(&self.r#type)?;
+        object.link_long(self.table.pack(graph)?)?;
+        Ok(graph.add(object))
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn halve<T: Clone>(
+    coverage: &Coverage,
+    values: &[T],
+) -> Option<((Coverage, Vec<T>), (Coverage, Vec<T>))> {
+    let (middle, first, second) = coverage.split()?;
+    let (head, tail) = values.split_at(middle.min(values.len()));
+    Some(((first, head.to_vec()), (second, tail.to_vec())))
+}
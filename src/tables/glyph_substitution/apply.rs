@@ -0,0 +1,186 @@
+use crate::layout::buffer::{Buffer, Glyph};
+use crate::layout::lookup::{Apply, Lookups};
+use crate::layout::skip::Skip;
+use crate::tables::glyph_definition::GlyphDefinition;
+use crate::tables::glyph_substitution::{
+    AlternateSubstitution, GlyphSubstitution, LigatureSubstitution, MultipleSubstitution,
+    ReverseChainedContextualSubstibution, SingleSubstitution, Type,
+};
+use crate::Result;
+
+impl GlyphSubstitution {
+    /// Apply lookups to a glyph buffer.
+    ///
+    /// The lookups are applied one after another in the given order. Alternate substitutions
+    /// take the alternate with the given index and leave the glyph intact if there is no such
+    /// alternate. The glyph definition, if any, classifies the glyphs for the lookup flags.
+    pub fn apply(
+        &self,
+        buffer: &mut Buffer,
+        lookup_indices: &[u16],
+        definition: Option<&GlyphDefinition>,
+        alternate_index: usize,
+    ) -> Result<()> {
+        self.lookups
+            .apply(buffer, lookup_indices, definition, &alternate_index)
+    }
+}
+
+impl Apply for Type {
+    type Parameter<'l> = usize;
+
+    fn is_reversed(&self) -> bool {
+        match self {
+            Self::ExtensionSubstitution(value) => value.table.is_reversed(),
+            Self::ReverseChainedContextualSubstibution(_) => true,
+            _ => false,
+        }
+    }
+
+    fn apply(
+        &self,
+        lookups: &Lookups<Self>,
+        buffer: &mut Buffer,
+        position: usize,
+        skip: Skip,
+        alternate_index: &usize,
+        depth: usize,
+    ) -> Result<Option<usize>> {
+        Ok(match self {
+            Self::SingleSubstitution(value) => value.apply(buffer, position),
+            Self::MultipleSubstitution(value) => value.apply(buffer, position),
+            Self::AlternateSubstitution(value) => value.apply(buffer, position, *alternate_index),
+            Self::LigatureSubstitution(value) => value.apply(buffer, position, skip),
+            Self::ContextualSubstitution(value) => {
+                return value.apply(lookups, buffer, position, skip, alternate_index, depth);
+            }
+            Self::ChainedContextualSubstitution(value) => {
+                return value.apply(lookups, buffer, position, skip, alternate_index, depth);
+            }
+            Self::ExtensionSubstitution(value) => {
+                return value
+                    .table
+                    .apply(lookups, buffer, position, skip, alternate_index, depth);
+            }
+            Self::ReverseChainedContextualSubstibution(value) => {
+                value.apply(buffer, position, skip)
+            }
+        })
+    }
+}
+
+impl SingleSubstitution {
+    fn apply(&self, buffer: &mut Buffer, position: usize) -> Option<usize> {
+        let glyph = &mut buffer.glyphs[position];
+        glyph.glyph_id = match self {
+            Self::Format1(value) => {
+                value.coverage.index(glyph.glyph_id)?;
+                glyph.glyph_id.wrapping_add(value.delta_glyph_id as u16)
+            }
+            Self::Format2(value) => {
+                let index = value.coverage.index(glyph.glyph_id)?;
+                *value.glyph_ids.get(index as usize)?
+            }
+        };
+        Some(position + 1)
+    }
+}
+
+impl MultipleSubstitution {
+    fn apply(&self, buffer: &mut Buffer, position: usize) -> Option<usize> {
+        let glyph = buffer.glyphs[position];
+        let index = self.coverage.index(glyph.glyph_id)?;
+        let record = self.records.get(index as usize)?;
+        buffer.glyphs.splice(
+            position..position + 1,
+            record.glyph_ids.iter().map(|glyph_id| Glyph {
+                glyph_id: *glyph_id,
+                ..glyph
+            }),
+        );
+        Some(position + record.glyph_ids.len())
+    }
+}
+
+impl AlternateSubstitution {
+    fn apply(&self, buffer: &mut Buffer, position: usize, alternate_index: usize) -> Option<usize> {
+        let glyph = &mut buffer.glyphs[position];
+        let index = self.coverage.index(glyph.glyph_id)?;
+        glyph.glyph_id = *self
+            .records
+            .get(index as usize)?
+            .glyph_ids
+            .get(alternate_index)?;
+        Some(position + 1)
+    }
+}
+
+impl LigatureSubstitution {
+    fn apply(&self, buffer: &mut Buffer, position: usize, skip: Skip) -> Option<usize> {
+        let index = self.coverage.index(buffer.glyphs[position].glyph_id)?;
+        let (ligature, positions) =
+            self.records
+                .get(index as usize)?
+                .records
+                .iter()
+                .find_map(|record| {
+                    let positions = buffer.match_forward(
+                        position,
+                        skip,
+                        record.glyph_ids.len(),
+                        |i, glyph_id| record.glyph_ids[i] == glyph_id,
+                    )?;
+                    Some((record, positions))
+                })?;
+        let last = positions.last().cloned().unwrap_or(position);
+        let cluster = buffer.glyphs[position..=last]
+            .iter()
+            .map(|glyph| glyph.cluster)
+            .min()
+            .unwrap_or(0);
+        let ligature_id = buffer.allocate_ligature();
+        // The glyphs skipped in between the components, such as marks, stay and are attached to
+        // the component preceding them.
+        let mut component_index = 0;
+        for i in position..=last {
+            let glyph = &mut buffer.glyphs[i];
+            glyph.cluster = cluster;
+            if i == position || positions.contains(&i) {
+                component_index += 1;
+            } else {
+                glyph.ligature_id = ligature_id;
+                glyph.component_index = component_index;
+            }
+        }
+        // The ligature is classified as such for the lookups that follow unless the glyph
+        // definition classifies it otherwise.
+        let glyph = &mut buffer.glyphs[position];
+        glyph.glyph_id = ligature.glyph_id;
+        glyph.class = 2;
+        glyph.ligature_id = ligature_id;
+        glyph.component_index = 0;
+        for i in positions.iter().rev() {
+            buffer.glyphs.remove(*i);
+        }
+        Some(last + 1 - positions.len())
+    }
+}
+
+impl ReverseChainedContextualSubstibution {
+    fn apply(&self, buffer: &mut Buffer, position: usize, skip: Skip) -> Option<usize> {
+        let index = self.coverage.index(buffer.glyphs[position].glyph_id)?;
+        let glyph_id = *self.glyph_ids.get(index as usize)?;
+        let backward = &self.backward_coverages;
+        if !buffer.match_backward(position, skip, backward.len(), |i, glyph_id| {
+            backward[i].contains(glyph_id)
+        }) {
+            return None;
+        }
+        let forward = &self.forward_coverages;
+        buffer.match_forward(position, skip, forward.len(), |i, glyph_id| {
+            forward[i].contains(glyph_id)
+        })?;
+        buffer.glyphs[position].glyph_id = glyph_id;
+        Some(position + 1)
+    }
+}
@@ -1,5 +1,9 @@
 use truetype::GlyphID;
 
+use crate::layout::graph::{Graph, Object, Pack};
+use crate::tape::Write;
+use crate::Result;
+
 table! {
     /// Alternates.
     pub Alternates { // AlternateSet
@@ -52,3 +56,44 @@ table! {
         },
     }
 }
+
+impl Pack for Ligature {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&self.glyph_id)?;
+        object.give(&(self.glyph_ids.len() as u16 + 1))?;
+        object.give(&self.glyph_ids[..])?;
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for Ligatures {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&(self.records.len() as u16))?;
+        for record in self.records.iter() {
+            object.link(record.pack(graph)?)?;
+        }
+        Ok(graph.add(object))
+    }
+}
+
+macro_rules! implement {
+    ($($type:ident,)*) => {
+        $(
+            impl Pack for $type {
+                fn pack(&self, graph: &mut Graph) -> Result<usize> {
+                    let mut object = Object::default();
+                    object.give(&(self.glyph_ids.len() as u16))?;
+                    object.give(&self.glyph_ids[..])?;
+                    Ok(graph.add(object))
+                }
+            }
+        )*
+    };
+}
+
+implement! {
+    Alternates,
+    Sequence,
+}
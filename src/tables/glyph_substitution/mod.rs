@@ -2,11 +2,14 @@
 //!
 //! [1]: https://learn.microsoft.com/en-gb/typography/opentype/spec/gsub
 
+mod apply;
 mod element;
 
 use truetype::GlyphID;
 
+use crate::layout::graph::{Graph, Object, Pack, Subtable};
 use crate::layout::{ChainedContext, Context, Coverage, Directory};
+use crate::tape::Write;
 use crate::Result;
 
 pub use element::*;
@@ -134,13 +137,15 @@ table! {
     }
 }
 
-table! {
-    /// An extension substitution.
-    pub ExtensionSubstitution { // ExtensionSubstFormat1
-        format (u16) = { 1 }, // SubstFormat
-        r#type (u16), // ExtensionLookupType
-        offset (u32), // ExtensionOffset
-    }
+/// An extension substitution.
+#[derive(Clone, Debug)]
+#[rustfmt::skip]
+pub struct ExtensionSubstitution { // ExtensionSubstFormat1
+    pub format: u16, // SubstFormat
+    pub r#type: u16, // ExtensionLookupType
+    pub offset: u32, // ExtensionOffset
+
+    pub table: Box<Type>,
 }
 
 table! {
@@ -199,6 +204,28 @@ impl crate::walue::Read<'static> for Type {
     }
 }
 
+impl crate::value::Read for ExtensionSubstitution {
+    fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
+        let position = tape.position()?;
+        let format = tape.take()?;
+        if format != 1 {
+            raise!("found an unknown format of the extension substitution ({format})");
+        }
+        let r#type = tape.take()?;
+        if r#type == 7 {
+            raise!("found a nested extension substitution");
+        }
+        let offset = tape.take()?;
+        let table = jump_take_given!(@unwrap tape, position, offset, r#type);
+        Ok(Self {
+            format,
+            r#type,
+            offset,
+            table: Box::new(table),
+        })
+    }
+}
+
 impl crate::value::Read for SingleSubstitution {
     fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
         Ok(match tape.peek::<u16>()? {
@@ -208,3 +235,193 @@ impl crate::value::Read for SingleSubstitution {
         })
     }
 }
+
+impl Pack for Type {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        match self {
+            Self::SingleSubstitution(value) => value.pack(graph),
+            Self::MultipleSubstitution(value) => value.pack(graph),
+            Self::AlternateSubstitution(value) => value.pack(graph),
+            Self::LigatureSubstitution(value) => value.pack(graph),
+            Self::ContextualSubstitution(value) => value.pack(graph),
+            Self::ChainedContextualSubstitution(value) => value.pack(graph),
+            Self::ExtensionSubstitution(value) => value.pack(graph),
+            Self::ReverseChainedContextualSubstibution(value) => value.pack(graph),
+        }
+    }
+}
+
+impl Subtable for Type {
+    const EXTENSION: u16 = 7;
+
+    fn split(&self) -> Option<(Self, Self)> {
+        macro_rules! split(
+            ($variant:ident, $value:expr) => (
+                $value.split().map(|(first, second)| {
+                    (Self::$variant(first), Self::$variant(second))
+                })
+            );
+        );
+        match self {
+            Self::SingleSubstitution(value) => split!(SingleSubstitution, value),
+            Self::MultipleSubstitution(value) => split!(MultipleSubstitution, value),
+            Self::AlternateSubstitution(value) => split!(AlternateSubstitution, value),
+            Self::LigatureSubstitution(value) => split!(LigatureSubstitution, value),
+            Self::ExtensionSubstitution(value) => split!(ExtensionSubstitution, value),
+            _ => None,
+        }
+    }
+}
+
+impl SingleSubstitution {
+    fn split(&self) -> Option<(Self, Self)> {
+        match self {
+            Self::Format1(value) => {
+                let (_, first, second) = value.coverage.split()?;
+                Some((
+                    Self::Format1(SingleSubstitution1 {
+                        coverage: first,
+                        ..value.clone()
+                    }),
+                    Self::Format1(SingleSubstitution1 {
+                        coverage: second,
+                        ..value.clone()
+                    }),
+                ))
+            }
+            Self::Format2(value) => {
+                let (middle, first, second) = value.coverage.split()?;
+                let middle = middle.min(value.glyph_ids.len());
+                let (head, tail) = value.glyph_ids.split_at(middle);
+                Some((
+                    Self::Format2(SingleSubstitution2 {
+                        glyph_count: head.len() as u16,
+                        glyph_ids: head.to_vec(),
+                        coverage: first,
+                        ..value.clone()
+                    }),
+                    Self::Format2(SingleSubstitution2 {
+                        glyph_count: tail.len() as u16,
+                        glyph_ids: tail.to_vec(),
+                        coverage: second,
+                        ..value.clone()
+                    }),
+                ))
+            }
+        }
+    }
+}
+
+impl Pack for SingleSubstitution {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        match self {
+            Self::Format1(value) => {
+                object.give(&1u16)?;
+                object.link(value.coverage.pack(graph)?)?;
+                object.give(&value.delta_glyph_id)?;
+            }
+            Self::Format2(value) => {
+                object.give(&2u16)?;
+                object.link(value.coverage.pack(graph)?)?;
+                object.give(&(value.glyph_ids.len() as u16))?;
+                object.give(&value.glyph_ids[..])?;
+            }
+        }
+        Ok(graph.add(object))
+    }
+}
+
+macro_rules! implement {
+    ($($type:ident,)*) => {
+        $(
+            impl Pack for $type {
+                fn pack(&self, graph: &mut Graph) -> Result<usize> {
+                    let mut object = Object::default();
+                    object.give(&1u16)?;
+                    object.link(self.coverage.pack(graph)?)?;
+                    object.give(&(self.records.len() as u16))?;
+                    for record in self.records.iter() {
+                        object.link(record.pack(graph)?)?;
+                    }
+                    Ok(graph.add(object))
+                }
+            }
+
+            impl $type {
+                fn split(&self) -> Option<(Self, Self)> {
+                    let (middle, first, second) = self.coverage.split()?;
+                    let middle = middle.min(self.records.len());
+                    let (head, tail) = self.records.split_at(middle);
+                    Some((
+                        Self {
+                            coverage_offset: 0,
+                            record_count: head.len() as u16,
+                            record_offsets: vec![0; head.len()],
+                            coverage: first,
+                            records: head.to_vec(),
+                            ..self.clone()
+                        },
+                        Self {
+                            coverage_offset: 0,
+                            record_count: tail.len() as u16,
+                            record_offsets: vec![0; tail.len()],
+                            coverage: second,
+                            records: tail.to_vec(),
+                            ..self.clone()
+                        },
+                    ))
+                }
+            }
+        )*
+    };
+}
+
+implement! {
+    AlternateSubstitution,
+    LigatureSubstitution,
+    MultipleSubstitution,
+}
+
+impl ExtensionSubstitution {
+    fn split(&self) -> Option<(Self, Self)> {
+        let (first, second) = self.table.split()?;
+        Some((
+            Self {
+                table: Box::new(first),
+                ..self.clone()
+            },
+            Self {
+                table: Box::new(second),
+                ..self.clone()
+            },
+        ))
+    }
+}
+
+impl Pack for ExtensionSubstitution {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&1u16)?;
+        object.give(&self.r#type)?;
+        object.link_long(self.table.pack(graph)?)?;
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for ReverseChainedContextualSubstibution {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&1u16)?;
+        object.link(self.coverage.pack(graph)?)?;
+        for coverages in [&self.backward_coverages, &self.forward_coverages] {
+            object.give(&(coverages.len() as u16))?;
+            for coverage in coverages.iter() {
+                object.link(coverage.pack(graph)?)?;
+            }
+        }
+        object.give(&(self.glyph_ids.len() as u16))?;
+        object.give(&self.glyph_ids[..])?;
+        Ok(graph.add(object))
+    }
+}
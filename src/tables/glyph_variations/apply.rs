@@ -0,0 +1,309 @@
+use truetype::tables::glyph_data::{Arguments, Description, Options};
+use truetype::tables::{GlyphData, HorizontalMetrics};
+use truetype::GlyphID;
+
+use crate::tables::glyph_variations::{GlyphVariations, Points};
+use crate::Result;
+
+/// The maximum depth of components referring to other composite glyphs.
+const NESTING_LIMIT: usize = 64;
+
+/// An outline varied at given normalized axis coordinates.
+#[derive(Clone, Debug, Default)]
+pub struct Outline {
+    /// The points.
+    pub points: Vec<(f32, f32)>,
+    /// The flags indicating if the points are on the curve.
+    pub on_curve: Vec<bool>,
+    /// The indices of the last points of the contours.
+    pub end_points: Vec<usize>,
+    /// The left, right, top, and bottom phantom points.
+    pub phantom_points: [(f32, f32); 4],
+}
+
+impl Outline {
+    /// Return the advance width.
+    #[inline]
+    pub fn advance_width(&self) -> f32 {
+        self.phantom_points[1].0 - self.phantom_points[0].0
+    }
+
+    /// Return the delta of the advance height.
+    ///
+    /// The vertical phantom points start at zero, so only the change of the advance height is
+    /// known without vertical metrics.
+    #[inline]
+    pub fn advance_height_delta(&self) -> f32 {
+        self.phantom_points[2].1 - self.phantom_points[3].1
+    }
+
+    /// Return the left side bearing.
+    ///
+    /// Glyphs without points have no left side bearing.
+    pub fn left_side_bearing(&self) -> f32 {
+        self.points
+            .iter()
+            .map(|point| point.0)
+            .reduce(f32::min)
+            .map_or(0.0, |value| value - self.phantom_points[0].0)
+    }
+}
+
+impl GlyphVariations {
+    /// Vary the outline of a glyph at given normalized axis coordinates.
+    ///
+    /// Composite glyphs are flattened with their components varied and transformed. The
+    /// horizontal phantom points are placed according to the horizontal metrics, and the
+    /// vertical ones start at zero, so that only their deltas matter.
+    pub fn apply(
+        &self,
+        glyph_data: &GlyphData,
+        metrics: &HorizontalMetrics,
+        glyph_id: GlyphID,
+        coordinates: &[f32],
+    ) -> Result<Outline> {
+        self.apply_given(glyph_data, metrics, glyph_id, coordinates, 0)
+    }
+
+    /// Compute the deltas of the points of a glyph at given normalized axis coordinates.
+    ///
+    /// The points are the original ones followed by the four phantom points. The deltas of the
+    /// points left untouched by a variation are inferred from the neighboring points on the
+    /// same contour; the contours are given by the indices of their last points.
+    pub fn deltas(
+        &self,
+        glyph_id: GlyphID,
+        points: &[(f32, f32)],
+        end_points: &[usize],
+        coordinates: &[f32],
+    ) -> Vec<(f32, f32)> {
+        let mut values = vec![(0.0, 0.0); points.len()];
+        let record = match self.records.get(glyph_id as usize) {
+            Some(Some(record)) => record,
+            _ => return values,
+        };
+        for (header, deltas) in record.headers.iter().zip(record.deltas.iter()) {
+            let scalar = match self.region(header) {
+                Some(region) => region.scalar(coordinates),
+                _ => continue,
+            };
+            if scalar == 0.0 {
+                continue;
+            }
+            let pairs = deltas
+                .x
+                .iter()
+                .zip(deltas.y.iter())
+                .map(|(x, y)| (*x as f32, *y as f32));
+            let mut touched = vec![None; points.len()];
+            match deltas.points {
+                Points::All => {
+                    for (value, pair) in touched.iter_mut().zip(pairs) {
+                        *value = Some(pair);
+                    }
+                }
+                Points::Some(ref indices) => {
+                    for (index, pair) in indices.iter().zip(pairs) {
+                        if let Some(value) = touched.get_mut(*index as usize) {
+                            *value = Some(pair);
+                        }
+                    }
+                    touched = infer(points, end_points, &touched);
+                }
+            }
+            for (value, delta) in values.iter_mut().zip(touched) {
+                if let Some((x, y)) = delta {
+                    value.0 += scalar * x;
+                    value.1 += scalar * y;
+                }
+            }
+        }
+        values
+    }
+
+    fn apply_given(
+        &self,
+        glyph_data: &GlyphData,
+        metrics: &HorizontalMetrics,
+        glyph_id: GlyphID,
+        coordinates: &[f32],
+        depth: usize,
+    ) -> Result<Outline> {
+        if depth > NESTING_LIMIT {
+            raise!("found too deeply nested components (glyph {glyph_id})");
+        }
+        let glyph = match glyph_data.get(glyph_id as usize) {
+            Some(glyph) => glyph.as_ref(),
+            _ => raise!("found no glyph with identifier {glyph_id}"),
+        };
+        let (advance_width, left_side_bearing) = metrics.get(glyph_id);
+        let left = glyph.map_or(0, |glyph| glyph.min_x) as f32 - left_side_bearing as f32;
+        let phantom_points = [
+            (left, 0.0),
+            (left + advance_width as f32, 0.0),
+            (0.0, 0.0),
+            (0.0, 0.0),
+        ];
+        let mut outline = Outline::default();
+        let mut points = vec![];
+        let mut end_points = vec![];
+        match glyph.map(|glyph| &glyph.description) {
+            Some(Description::Simple(description)) => {
+                let (mut x, mut y) = (0.0, 0.0);
+                for (dx, dy) in description.x.iter().zip(description.y.iter()) {
+                    x += *dx as f32;
+                    y += *dy as f32;
+                    points.push((x, y));
+                }
+                end_points.extend(description.end_points.iter().map(|&i| i as usize));
+                outline.on_curve = description
+                    .flags
+                    .iter()
+                    .map(|flags| flags.is_on_curve())
+                    .collect();
+                outline.end_points = end_points.clone();
+            }
+            Some(Description::Composite(description)) => {
+                points.extend(description.components.iter().map(|component| {
+                    match component.arguments {
+                        Arguments::Offsets(x, y) => (x as f32, y as f32),
+                        Arguments::Indices(..) => (0.0, 0.0),
+                    }
+                }));
+            }
+            _ => {}
+        }
+        let count = points.len();
+        points.extend(phantom_points);
+        let deltas = self.deltas(glyph_id, &points, &end_points, coordinates);
+        for (point, delta) in points.iter_mut().zip(deltas) {
+            point.0 += delta.0;
+            point.1 += delta.1;
+        }
+        outline.phantom_points.copy_from_slice(&points[count..]);
+        points.truncate(count);
+        match glyph.map(|glyph| &glyph.description) {
+            Some(Description::Composite(description)) => {
+                for (component, offset) in description.components.iter().zip(points) {
+                    let mut other = self.apply_given(
+                        glyph_data,
+                        metrics,
+                        component.glyph_id,
+                        coordinates,
+                        depth + 1,
+                    )?;
+                    for point in other.points.iter_mut() {
+                        *point = transform(&component.options, *point);
+                    }
+                    let (x, y) = match component.arguments {
+                        Arguments::Offsets(..) if component.flags.is_offset_scaled() => {
+                            transform(&component.options, offset)
+                        }
+                        Arguments::Offsets(..) => offset,
+                        Arguments::Indices(i, j) => {
+                            match (outline.points.get(i as usize), other.points.get(j as usize)) {
+                                (Some(first), Some(second)) => {
+                                    (first.0 - second.0, first.1 - second.1)
+                                }
+                                _ => raise!("found malformed point indices (glyph {glyph_id})"),
+                            }
+                        }
+                    };
+                    let start = outline.points.len();
+                    outline
+                        .points
+                        .extend(other.points.iter().map(|point| (point.0 + x, point.1 + y)));
+                    outline.on_curve.extend(other.on_curve);
+                    outline
+                        .end_points
+                        .extend(other.end_points.iter().map(|i| i + start));
+                    if component.flags.should_use_metrics() {
+                        outline.phantom_points = other.phantom_points;
+                    }
+                }
+            }
+            _ => outline.points = points,
+        }
+        Ok(outline)
+    }
+}
+
+// Infer the deltas of untouched points from the nearest touched points preceding and following
+// them on the same contour.
+fn infer(
+    points: &[(f32, f32)],
+    end_points: &[usize],
+    touched: &[Option<(f32, f32)>],
+) -> Vec<Option<(f32, f32)>> {
+    let mut values = touched.to_vec();
+    let mut start = 0;
+    for &end in end_points {
+        if end >= points.len() || end < start {
+            break;
+        }
+        let indices = (start..=end)
+            .filter(|&i| touched[i].is_some())
+            .collect::<Vec<_>>();
+        let next = |i: usize| if i == end { start } else { i + 1 };
+        if !indices.is_empty() && indices.len() <= end - start {
+            for (k, &first) in indices.iter().enumerate() {
+                let second = indices[(k + 1) % indices.len()];
+                let (delta1, delta2) = (touched[first].unwrap(), touched[second].unwrap());
+                let mut i = next(first);
+                while i != second {
+                    values[i] = Some((
+                        interpolate(
+                            points[i].0,
+                            (points[first].0, delta1.0),
+                            (points[second].0, delta2.0),
+                        ),
+                        interpolate(
+                            points[i].1,
+                            (points[first].1, delta1.1),
+                            (points[second].1, delta2.1),
+                        ),
+                    ));
+                    i = next(i);
+                }
+            }
+        }
+        start = end + 1;
+    }
+    values
+}
+
+fn interpolate(value: f32, first: (f32, f32), second: (f32, f32)) -> f32 {
+    let ((value1, delta1), (value2, delta2)) = if first.0 <= second.0 {
+        (first, second)
+    } else {
+        (second, first)
+    };
+    if value1 == value2 {
+        if delta1 == delta2 {
+            delta1
+        } else {
+            0.0
+        }
+    } else if value <= value1 {
+        delta1
+    } else if value >= value2 {
+        delta2
+    } else {
+        delta1 + (value - value1) * (delta2 - delta1) / (value2 - value1)
+    }
+}
+
+fn transform(options: &Options, (x, y): (f32, f32)) -> (f32, f32) {
+    match *options {
+        Options::None => (x, y),
+        Options::Scalar(scale) => {
+            let scale = f32::from(scale);
+            (x * scale, y * scale)
+        }
+        Options::Vector(x_scale, y_scale) => (x * f32::from(x_scale), y * f32::from(y_scale)),
+        Options::Matrix(x_scale, scale01, scale10, y_scale) => (
+            x * f32::from(x_scale) + y * f32::from(scale10),
+            x * f32::from(scale01) + y * f32::from(y_scale),
+        ),
+    }
+}
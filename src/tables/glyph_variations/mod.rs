@@ -0,0 +1,312 @@
+//! The [glyph-variations table][1].
+//!
+//! [1]: https://learn.microsoft.com/en-us/typography/opentype/spec/gvar
+
+mod apply;
+
+pub use apply::Outline;
+
+use truetype::q16;
+
+use crate::variations::item::{Axis, Region};
+use crate::Result;
+
+table! {
+    @position
+    /// A glyph-variations table.
+    pub GlyphVariations {
+        major_version       (u16) = { 1 }, // majorVersion
+        minor_version       (u16) = { 0 }, // minorVersion
+        axis_count          (u16), // axisCount
+        shared_tuple_count  (u16), // sharedTupleCount
+        shared_tuple_offset (u32), // sharedTuplesOffset
+        glyph_count         (u16), // glyphCount
+        flags               (u16), // flags
+        data_offset         (u32), // glyphVariationDataArrayOffset
+
+        offsets (Vec<u32>) |this, tape, _| { // glyphVariationDataOffsets
+            let count = this.glyph_count as usize + 1;
+            if this.flags & 1 > 0 {
+                tape.take_given(count)
+            } else {
+                let offsets: Vec<u16> = tape.take_given(count)?;
+                Ok(offsets.into_iter().map(|offset| 2 * offset as u32).collect())
+            }
+        },
+
+        shared_tuples (Vec<Tuple>) |this, tape, position| { // sharedTuples
+            tape.jump(position + this.shared_tuple_offset as u64)?;
+            (0..this.shared_tuple_count)
+                .map(|_| tape.take_given(this.axis_count))
+                .collect()
+        },
+
+        records (Vec<Option<Record>>) |this, tape, position| { // glyphVariationData
+            let position = position + this.data_offset as u64;
+            let mut values = Vec::with_capacity(this.glyph_count as usize);
+            for i in 0..this.glyph_count as usize {
+                if this.offsets[i] >= this.offsets[i + 1] {
+                    values.push(None);
+                    continue;
+                }
+                tape.jump(position + this.offsets[i] as u64)?;
+                values.push(Some(tape.take_given(this.axis_count)?));
+            }
+            Ok(values)
+        },
+    }
+}
+
+table! {
+    @define
+    /// Variations of a glyph.
+    pub Record { // GlyphVariationData
+        count       (u16        ), // tupleVariationCount
+        data_offset (u16        ), // dataOffset
+        headers     (Vec<Header>), // tupleVariationHeaders
+        deltas      (Vec<Deltas>), // serialized data
+    }
+}
+
+table! {
+    @define
+    /// The header of a variation of a glyph.
+    pub Header { // TupleVariationHeader
+        data_size (u16          ), // variationDataSize
+        index     (u16          ), // tupleIndex
+        peak      (Option<Tuple>), // peakTuple
+        start     (Option<Tuple>), // intermediateStartTuple
+        end       (Option<Tuple>), // intermediateEndTuple
+    }
+}
+
+table! {
+    @define
+    /// Coordinates on the axes.
+    pub Tuple { // Tuple
+        values (Vec<q16>), // coordinates
+    }
+}
+
+table! {
+    @define
+    /// Deltas of a variation of a glyph.
+    pub Deltas {
+        points (Points  ), // packed point numbers
+        x      (Vec<i32>), // packed deltas
+        y      (Vec<i32>), // packed deltas
+    }
+}
+
+/// Points that have deltas.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum Points {
+    /// All the points, including the phantom ones.
+    #[default]
+    All,
+    /// The points with given indices.
+    Some(Vec<u16>),
+}
+
+impl GlyphVariations {
+    /// Return the region of a variation of a glyph.
+    ///
+    /// The peak is taken from the shared tuples unless it is embedded, and the intermediate
+    /// region is inferred from the peak unless it is given.
+    pub fn region(&self, header: &Header) -> Option<Region> {
+        let peak = match header.peak {
+            Some(ref peak) => peak,
+            _ => self.shared_tuples.get(header.tuple_index() as usize)?,
+        };
+        let records = peak
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, &peak)| {
+                let value = f32::from(peak);
+                let (start, end) = match (&header.start, &header.end) {
+                    (Some(start), Some(end)) => (
+                        start.values.get(i).cloned().unwrap_or_default(),
+                        end.values.get(i).cloned().unwrap_or_default(),
+                    ),
+                    _ if value < 0.0 => (peak, q16(0)),
+                    _ => (q16(0), peak),
+                };
+                Axis { start, peak, end }
+            })
+            .collect();
+        Some(Region { records })
+    }
+}
+
+impl Record {
+    /// Check if the variations share point numbers.
+    #[inline]
+    pub fn has_shared_points(&self) -> bool {
+        self.count & 0x8000 > 0
+    }
+
+    /// Return the number of variations.
+    #[inline]
+    pub fn variation_count(&self) -> usize {
+        (self.count & 0x0FFF) as usize
+    }
+}
+
+impl Header {
+    /// Check if the peak is embedded.
+    #[inline]
+    pub fn has_embedded_peak(&self) -> bool {
+        self.index & 0x8000 > 0
+    }
+
+    /// Check if the intermediate region is given.
+    #[inline]
+    pub fn has_intermediate_region(&self) -> bool {
+        self.index & 0x4000 > 0
+    }
+
+    /// Check if the variation has its own point numbers.
+    #[inline]
+    pub fn has_private_points(&self) -> bool {
+        self.index & 0x2000 > 0
+    }
+
+    /// Return the index of the shared tuple holding the peak.
+    #[inline]
+    pub fn tuple_index(&self) -> u16 {
+        self.index & 0x0FFF
+    }
+}
+
+impl crate::walue::Read<'static> for Record {
+    type Parameter = u16;
+
+    fn read<T: crate::tape::Read>(tape: &mut T, axis_count: u16) -> Result<Self> {
+        let position = tape.position()?;
+        let count = tape.take()?;
+        let data_offset = tape.take()?;
+        let mut headers: Vec<Header> = Vec::with_capacity((count & 0x0FFF) as usize);
+        for _ in 0..count & 0x0FFF {
+            headers.push(tape.take_given(axis_count)?);
+        }
+        tape.jump(position + data_offset as u64)?;
+        let shared = if count & 0x8000 > 0 {
+            Some(read_points(tape)?)
+        } else {
+            None
+        };
+        let mut deltas = Vec::with_capacity(headers.len());
+        for header in headers.iter() {
+            let end = tape.position()? + header.data_size as u64;
+            let points = match (header.has_private_points(), &shared) {
+                (true, _) => read_points(tape)?,
+                (false, Some(points)) => points.clone(),
+                _ => Points::All,
+            };
+            let mut values = vec![];
+            while tape.position()? < end {
+                read_deltas(tape, &mut values)?;
+            }
+            if tape.position()? > end || values.len() % 2 > 0 {
+                raise!("found malformed deltas of a glyph variation");
+            }
+            let y = values.split_off(values.len() / 2);
+            deltas.push(Deltas {
+                points,
+                x: values,
+                y,
+            });
+        }
+        Ok(Self {
+            count,
+            data_offset,
+            headers,
+            deltas,
+        })
+    }
+}
+
+impl crate::walue::Read<'static> for Header {
+    type Parameter = u16;
+
+    fn read<T: crate::tape::Read>(tape: &mut T, axis_count: u16) -> Result<Self> {
+        let data_size = tape.take()?;
+        let index: u16 = tape.take()?;
+        let peak = if index & 0x8000 > 0 {
+            Some(tape.take_given(axis_count)?)
+        } else {
+            None
+        };
+        let (start, end) = if index & 0x4000 > 0 {
+            (
+                Some(tape.take_given(axis_count)?),
+                Some(tape.take_given(axis_count)?),
+            )
+        } else {
+            (None, None)
+        };
+        Ok(Self {
+            data_size,
+            index,
+            peak,
+            start,
+            end,
+        })
+    }
+}
+
+impl crate::walue::Read<'static> for Tuple {
+    type Parameter = u16;
+
+    #[inline]
+    fn read<T: crate::tape::Read>(tape: &mut T, axis_count: u16) -> Result<Self> {
+        Ok(Self {
+            values: tape.take_given(axis_count as usize)?,
+        })
+    }
+}
+
+fn read_deltas<T: crate::tape::Read>(tape: &mut T, values: &mut Vec<i32>) -> Result<()> {
+    let control = tape.take::<u8>()?;
+    let count = (control & 0x3F) as usize + 1;
+    for _ in 0..count {
+        values.push(match control & 0xC0 {
+            0x80 => 0,
+            0x40 => tape.take::<i16>()? as i32,
+            0xC0 => tape.take::<i32>()?,
+            _ => tape.take::<i8>()? as i32,
+        });
+    }
+    Ok(())
+}
+
+fn read_points<T: crate::tape::Read>(tape: &mut T) -> Result<Points> {
+    let value = tape.take::<u8>()? as usize;
+    let count = if value & 0x80 > 0 {
+        (value & 0x7F) << 8 | tape.take::<u8>()? as usize
+    } else {
+        value
+    };
+    if count == 0 {
+        return Ok(Points::All);
+    }
+    let mut values = Vec::with_capacity(count);
+    let mut point = 0u16;
+    while values.len() < count {
+        let control = tape.take::<u8>()?;
+        for _ in 0..(control & 0x7F) as usize + 1 {
+            let delta = if control & 0x80 > 0 {
+                tape.take::<u16>()?
+            } else {
+                tape.take::<u8>()? as u16
+            };
+            point = point.wrapping_add(delta);
+            values.push(point);
+        }
+    }
+    if values.len() > count {
+        raise!("found malformed point numbers of a glyph variation");
+    }
+    Ok(Points::Some(values))
+}
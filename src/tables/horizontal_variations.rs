@@ -0,0 +1,93 @@
+//! The [horizontal-metrics-variations table][1].
+//!
+//! [1]: https://learn.microsoft.com/en-us/typography/opentype/spec/hvar
+
+use truetype::tables::HorizontalMetrics;
+use truetype::GlyphID;
+
+use crate::variations::item::{Mapping, Store};
+
+table! {
+    @position
+    /// A horizontal-metrics-variations table.
+    pub HorizontalVariations {
+        major_version                     (u16) = { 1 }, // majorVersion
+        minor_version                     (u16) = { 0 }, // minorVersion
+        store_offset                      (u32), // itemVariationStoreOffset
+        advance_width_mapping_offset      (u32), // advanceWidthMappingOffset
+        left_side_bearing_mapping_offset  (u32), // lsbMappingOffset
+        right_side_bearing_mapping_offset (u32), // rsbMappingOffset
+
+        store (Store) |this, tape, position| {
+            jump_take!(tape, position, this.store_offset)
+        },
+
+        advance_width_mapping (Option<Mapping>) |this, tape, position| {
+            jump_take_maybe!(tape, position, this.advance_width_mapping_offset)
+        },
+
+        left_side_bearing_mapping (Option<Mapping>) |this, tape, position| {
+            jump_take_maybe!(tape, position, this.left_side_bearing_mapping_offset)
+        },
+
+        right_side_bearing_mapping (Option<Mapping>) |this, tape, position| {
+            jump_take_maybe!(tape, position, this.right_side_bearing_mapping_offset)
+        },
+    }
+}
+
+impl HorizontalVariations {
+    /// Return the advance width and left side bearing of a glyph at given normalized axis
+    /// coordinates.
+    ///
+    /// The left side bearing is varied only if there is a mapping for it; otherwise, it is left
+    /// as it is, since it is then given by the varied outline of the glyph.
+    pub fn get(
+        &self,
+        metrics: &HorizontalMetrics,
+        glyph_id: GlyphID,
+        coordinates: &[f32],
+    ) -> (f32, f32) {
+        let (advance_width, left_side_bearing) = metrics.get(glyph_id);
+        (
+            advance_width as f32 + self.advance_width_delta(glyph_id, coordinates),
+            left_side_bearing as f32
+                + self
+                    .left_side_bearing_delta(glyph_id, coordinates)
+                    .unwrap_or(0.0),
+        )
+    }
+
+    /// Compute the delta of the advance width of a glyph at given normalized axis coordinates.
+    pub fn advance_width_delta(&self, glyph_id: GlyphID, coordinates: &[f32]) -> f32 {
+        self.store
+            .delta_mapped(
+                self.advance_width_mapping.as_ref(),
+                glyph_id as u32,
+                coordinates,
+            )
+            .unwrap_or(0.0)
+    }
+
+    /// Compute the delta of the left side bearing of a glyph at given normalized axis
+    /// coordinates if there is a mapping for left side bearings.
+    #[inline]
+    pub fn left_side_bearing_delta(&self, glyph_id: GlyphID, coordinates: &[f32]) -> Option<f32> {
+        self.store.delta_mapped(
+            Some(self.left_side_bearing_mapping.as_ref()?),
+            glyph_id as u32,
+            coordinates,
+        )
+    }
+
+    /// Compute the delta of the right side bearing of a glyph at given normalized axis
+    /// coordinates if there is a mapping for right side bearings.
+    #[inline]
+    pub fn right_side_bearing_delta(&self, glyph_id: GlyphID, coordinates: &[f32]) -> Option<f32> {
+        self.store.delta_mapped(
+            Some(self.right_side_bearing_mapping.as_ref()?),
+            glyph_id as u32,
+            coordinates,
+        )
+    }
+}
@@ -0,0 +1,77 @@
+//! The [metrics-variations table][1].
+//!
+//! [1]: https://learn.microsoft.com/en-us/typography/opentype/spec/mvar
+
+use truetype::Tag;
+
+use crate::variations::item::Store;
+
+table! {
+    @position
+    /// A metrics-variations table.
+    pub MetricVariations {
+        major_version (u16) = { 1 }, // majorVersion
+        minor_version (u16) = { 0 }, // minorVersion
+        reserved      (u16), // reserved
+        record_size   (u16), // valueRecordSize
+        record_count  (u16), // valueRecordCount
+        store_offset  (u16), // itemVariationStoreOffset
+
+        records (Vec<Record>) |this, tape, _| { // valueRecords
+            if this.record_count > 0 && this.record_size < 8 {
+                raise!("found a malformed metrics-variations table");
+            }
+            let mut values = Vec::with_capacity(this.record_count as usize);
+            for _ in 0..this.record_count {
+                values.push(tape.take()?);
+                tape.take_bytes(this.record_size as usize - 8)?;
+            }
+            Ok(values)
+        },
+
+        store (Option<Store>) |this, tape, position| {
+            jump_take_maybe!(tape, position, this.store_offset)
+        },
+    }
+}
+
+table! {
+    /// A record of a metrics-variations table.
+    #[derive(Copy)]
+    pub Record { // ValueRecord
+        tag         (Tag), // valueTag
+        outer_index (u16), // deltaSetOuterIndex
+        inner_index (u16), // deltaSetInnerIndex
+    }
+}
+
+impl MetricVariations {
+    /// Compute the delta of a value at given normalized axis coordinates.
+    ///
+    /// The value is identified by a tag, such as `hasc` for the ascender of the horizontal
+    /// header or `xhgt` for the x-height of the OS/2 and Windows metrics. If there is no such
+    /// value, `None` is returned.
+    pub fn delta<T: Into<Tag>>(&self, tag: T, coordinates: &[f32]) -> Option<f32> {
+        let tag = tag.into();
+        let record = self.records.iter().find(|record| record.tag == tag)?;
+        Some(
+            self.store
+                .as_ref()
+                .and_then(|store| store.delta(record.outer_index, record.inner_index, coordinates))
+                .unwrap_or(0.0),
+        )
+    }
+
+    /// Compute the deltas of values at given normalized axis coordinates.
+    ///
+    /// Values that are not present have no delta.
+    pub fn deltas<T, U>(&self, tags: T, coordinates: &[f32]) -> Vec<f32>
+    where
+        T: IntoIterator<Item = U>,
+        U: Into<Tag>,
+    {
+        tags.into_iter()
+            .map(|tag| self.delta(tag, coordinates).unwrap_or(0.0))
+            .collect()
+    }
+}
@@ -1,13 +1,25 @@
 //! The primary tables.
 
+pub mod axis_variations;
 pub mod color_palettes;
+pub mod compact2;
 pub mod font_variations;
 pub mod glyph_definition;
 pub mod glyph_positioning;
 pub mod glyph_substitution;
+pub mod glyph_variations;
+pub mod horizontal_variations;
+pub mod metric_variations;
+pub mod vertical_variations;
 
+pub use axis_variations::AxisVariations;
 pub use color_palettes::ColorPalettes;
+pub use compact2::Compact2;
 pub use font_variations::FontVariations;
 pub use glyph_definition::GlyphDefinition;
 pub use glyph_positioning::GlyphPositioning;
 pub use glyph_substitution::GlyphSubstitution;
+pub use glyph_variations::GlyphVariations;
+pub use horizontal_variations::HorizontalVariations;
+pub use metric_variations::MetricVariations;
+pub use vertical_variations::VerticalVariations;
@@ -0,0 +1,107 @@
+//! The [vertical-metrics-variations table][1].
+//!
+//! [1]: https://learn.microsoft.com/en-us/typography/opentype/spec/vvar
+
+use truetype::GlyphID;
+
+use crate::variations::item::{Mapping, Store};
+
+table! {
+    @position
+    /// A vertical-metrics-variations table.
+    pub VerticalVariations {
+        major_version                      (u16) = { 1 }, // majorVersion
+        minor_version                      (u16) = { 0 }, // minorVersion
+        store_offset                       (u32), // itemVariationStoreOffset
+        advance_height_mapping_offset      (u32), // advanceHeightMappingOffset
+        top_side_bearing_mapping_offset    (u32), // tsbMappingOffset
+        bottom_side_bearing_mapping_offset (u32), // bsbMappingOffset
+        vertical_origin_mapping_offset     (u32), // vOrgMappingOffset
+
+        store (Store) |this, tape, position| {
+            jump_take!(tape, position, this.store_offset)
+        },
+
+        advance_height_mapping (Option<Mapping>) |this, tape, position| {
+            jump_take_maybe!(tape, position, this.advance_height_mapping_offset)
+        },
+
+        top_side_bearing_mapping (Option<Mapping>) |this, tape, position| {
+            jump_take_maybe!(tape, position, this.top_side_bearing_mapping_offset)
+        },
+
+        bottom_side_bearing_mapping (Option<Mapping>) |this, tape, position| {
+            jump_take_maybe!(tape, position, this.bottom_side_bearing_mapping_offset)
+        },
+
+        vertical_origin_mapping (Option<Mapping>) |this, tape, position| {
+            jump_take_maybe!(tape, position, this.vertical_origin_mapping_offset)
+        },
+    }
+}
+
+impl VerticalVariations {
+    /// Return the advance height and top side bearing of a glyph at given normalized axis
+    /// coordinates given the ones of the default instance.
+    ///
+    /// The top side bearing is varied only if there is a mapping for it; otherwise, it is left
+    /// as it is, since it is then given by the varied outline of the glyph.
+    pub fn get(
+        &self,
+        (advance_height, top_side_bearing): (u16, i16),
+        glyph_id: GlyphID,
+        coordinates: &[f32],
+    ) -> (f32, f32) {
+        (
+            advance_height as f32 + self.advance_height_delta(glyph_id, coordinates),
+            top_side_bearing as f32
+                + self
+                    .top_side_bearing_delta(glyph_id, coordinates)
+                    .unwrap_or(0.0),
+        )
+    }
+
+    /// Compute the delta of the advance height of a glyph at given normalized axis coordinates.
+    pub fn advance_height_delta(&self, glyph_id: GlyphID, coordinates: &[f32]) -> f32 {
+        self.store
+            .delta_mapped(
+                self.advance_height_mapping.as_ref(),
+                glyph_id as u32,
+                coordinates,
+            )
+            .unwrap_or(0.0)
+    }
+
+    /// Compute the delta of the top side bearing of a glyph at given normalized axis
+    /// coordinates if there is a mapping for top side bearings.
+    #[inline]
+    pub fn top_side_bearing_delta(&self, glyph_id: GlyphID, coordinates: &[f32]) -> Option<f32> {
+        self.store.delta_mapped(
+            Some(self.top_side_bearing_mapping.as_ref()?),
+            glyph_id as u32,
+            coordinates,
+        )
+    }
+
+    /// Compute the delta of the bottom side bearing of a glyph at given normalized axis
+    /// coordinates if there is a mapping for bottom side bearings.
+    #[inline]
+    pub fn bottom_side_bearing_delta(&self, glyph_id: GlyphID, coordinates: &[f32]) -> Option<f32> {
+        self.store.delta_mapped(
+            Some(self.bottom_side_bearing_mapping.as_ref()?),
+            glyph_id as u32,
+            coordinates,
+        )
+    }
+
+    /// Compute the delta of the vertical origin of a glyph at given normalized axis coordinates
+    /// if there is a mapping for vertical origins.
+    #[inline]
+    pub fn vertical_origin_delta(&self, glyph_id: GlyphID, coordinates: &[f32]) -> Option<f32> {
+        self.store.delta_mapped(
+            Some(self.vertical_origin_mapping.as_ref()?),
+            glyph_id as u32,
+            coordinates,
+        )
+    }
+}
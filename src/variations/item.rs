@@ -1,5 +1,11 @@
 //! The item variation store.
 
+use truetype::q16;
+
+use crate::layout::graph::{Graph, Object, Pack};
+use crate::tape::Write;
+use crate::Result;
+
 table! {
     @position
     /// An item variation store.
@@ -15,6 +21,10 @@ table! {
         records (Vec<Record>) |this, tape, position| {
             jump_take!(tape, position, this.count, this.offsets)
         },
+
+        regions (Regions) |this, tape, position| {
+            jump_take!(tape, position, this.region_offset)
+        },
     }
 }
 
@@ -22,16 +32,264 @@ table! {
     /// A record of an item variation store.
     pub Record { // ItemVariationData
         item_count        (u16), // itemCount
-        short_delta_count (u16), // shortDeltaCount
+        short_delta_count (u16), // wordDeltaCount
         region_count      (u16), // regionCount
 
         region_indices (Vec<u16>) |this, tape| { // regionIndices
             tape.take_given(this.region_count as usize)
         },
 
-        deltas (Vec<u8>) |this, tape| { // deltaSets
-            let per_item_count = this.short_delta_count + this.region_count;
-            tape.take_given(this.item_count as usize * per_item_count as usize)
+        deltas (Vec<Vec<i32>>) |this, tape| { // deltaSets
+            let long = this.short_delta_count & 0x8000 > 0;
+            let word_count = (this.short_delta_count & 0x7FFF).min(this.region_count) as usize;
+            let mut values = Vec::with_capacity(this.item_count as usize);
+            for _ in 0..this.item_count {
+                let mut row = Vec::with_capacity(this.region_count as usize);
+                for i in 0..this.region_count as usize {
+                    row.push(match (long, i < word_count) {
+                        (true, true) => tape.take::<i32>()?,
+                        (true, false) | (false, true) => tape.take::<i16>()? as i32,
+                        (false, false) => tape.take::<i8>()? as i32,
+                    });
+                }
+                values.push(row);
+            }
+            Ok(values)
+        },
+    }
+}
+
+table! {
+    /// A mapping of indices to items of an item variation store.
+    pub Mapping { // DeltaSetIndexMap
+        format       (u8), // format
+        entry_format (u8), // entryFormat
+
+        count (u32) |this, tape| { // mapCount
+            match this.format {
+                0 => Ok(tape.take::<u16>()? as u32),
+                1 => tape.take(),
+                value => raise!("found an unknown format of the delta-set index map ({value})"),
+            }
+        },
+
+        entries (Vec<u32>) |this, tape| { // mapData
+            let size = (((this.entry_format & 0x30) >> 4) + 1) as usize;
+            (0..this.count)
+                .map(|_| {
+                    let data = tape.take_bytes(size)?;
+                    Ok(data.iter().fold(0, |value, byte| value << 8 | *byte as u32))
+                })
+                .collect()
+        },
+    }
+}
+
+table! {
+    /// Regions.
+    pub Regions { // VariationRegionList
+        axis_count   (u16), // axisCount
+        region_count (u16), // regionCount
+
+        records (Vec<Region>) |this, tape| { // variationRegions
+            (0..this.region_count)
+                .map(|_| tape.take_given(this.axis_count))
+                .collect()
         },
     }
 }
+
+table! {
+    @define
+    /// A region.
+    pub Region { // VariationRegion
+        records (Vec<Axis>), // regionAxes
+    }
+}
+
+table! {
+    /// An axis of a region.
+    #[derive(Copy)]
+    pub Axis { // RegionAxisCoordinates
+        start (q16), // startCoord
+        peak  (q16), // peakCoord
+        end   (q16), // endCoord
+    }
+}
+
+impl Store {
+    /// Compute the delta of an item at given normalized axis coordinates.
+    ///
+    /// The item is identified by the index of a record and the index of a row within it. If
+    /// there is no such item, `None` is returned.
+    pub fn delta(&self, outer: u16, inner: u16, coordinates: &[f32]) -> Option<f32> {
+        let record = self.records.get(outer as usize)?;
+        let row = record.deltas.get(inner as usize)?;
+        let mut value = 0.0;
+        for (index, delta) in record.region_indices.iter().zip(row.iter()) {
+            if *delta == 0 {
+                continue;
+            }
+            if let Some(region) = self.regions.records.get(*index as usize) {
+                value += *delta as f32 * region.scalar(coordinates);
+            }
+        }
+        Some(value)
+    }
+
+    /// Compute the delta of an item at given normalized axis coordinates with the item identified
+    /// by an index through a mapping.
+    ///
+    /// Without a mapping, the index is taken to be the index of a row within the first record.
+    /// If the index cannot be mapped, `None` is returned, and items that are not present have no
+    /// delta.
+    pub fn delta_mapped(
+        &self,
+        mapping: Option<&Mapping>,
+        index: u32,
+        coordinates: &[f32],
+    ) -> Option<f32> {
+        let (outer, inner) = match mapping {
+            Some(mapping) => mapping.get(index)?,
+            _ => (0, u16::try_from(index).ok()?),
+        };
+        Some(self.delta(outer, inner, coordinates).unwrap_or(0.0))
+    }
+
+    /// Compute the scalars of the regions of a record at given normalized axis coordinates.
+    ///
+    /// The scalars follow the order of the region indices of the record. If there is no such
+    /// record, `None` is returned.
+    pub fn scalars(&self, outer: u16, coordinates: &[f32]) -> Option<Vec<f32>> {
+        let record = self.records.get(outer as usize)?;
+        Some(
+            record
+                .region_indices
+                .iter()
+                .map(|index| match self.regions.records.get(*index as usize) {
+                    Some(region) => region.scalar(coordinates),
+                    _ => 0.0,
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Mapping {
+    /// Return the indices of the record and the row of the item an index maps to.
+    ///
+    /// Indices beyond the end of the mapping map to the last item.
+    pub fn get(&self, index: u32) -> Option<(u16, u16)> {
+        let entry = *self
+            .entries
+            .get(index as usize)
+            .or_else(|| self.entries.last())?;
+        let bit_count = (self.entry_format & 0x0F) as u32 + 1;
+        Some((
+            (entry >> bit_count) as u16,
+            (entry & ((1 << bit_count) - 1)) as u16,
+        ))
+    }
+}
+
+impl Region {
+    /// Compute the scalar at given normalized axis coordinates.
+    ///
+    /// Missing coordinates are taken to be zero.
+    pub fn scalar(&self, coordinates: &[f32]) -> f32 {
+        let mut scalar = 1.0;
+        for (i, axis) in self.records.iter().enumerate() {
+            let coordinate = coordinates.get(i).cloned().unwrap_or(0.0);
+            scalar *= axis.scalar(coordinate);
+            if scalar == 0.0 {
+                break;
+            }
+        }
+        scalar
+    }
+}
+
+impl Axis {
+    /// Compute the scalar at a given normalized coordinate.
+    pub fn scalar(&self, coordinate: f32) -> f32 {
+        let (start, peak, end) = (
+            f32::from(self.start),
+            f32::from(self.peak),
+            f32::from(self.end),
+        );
+        // Invalid axes and axes spanning zero have no effect.
+        if start > peak || peak > end || (start < 0.0 && end > 0.0) || peak == 0.0 {
+            return 1.0;
+        }
+        if coordinate == peak {
+            1.0
+        } else if coordinate <= start || coordinate >= end {
+            0.0
+        } else if coordinate < peak {
+            (coordinate - start) / (peak - start)
+        } else {
+            (end - coordinate) / (end - peak)
+        }
+    }
+}
+
+impl crate::walue::Read<'static> for Region {
+    type Parameter = u16;
+
+    fn read<T: crate::tape::Read>(tape: &mut T, axis_count: Self::Parameter) -> Result<Self> {
+        Ok(Self {
+            records: tape.take_given(axis_count as usize)?,
+        })
+    }
+}
+
+impl Pack for Store {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&1u16)?;
+        object.link_long(self.regions.pack(graph)?)?;
+        object.give(&(self.records.len() as u16))?;
+        for record in self.records.iter() {
+            object.link_long(record.pack(graph)?)?;
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for Record {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        let long = self.short_delta_count & 0x8000 > 0;
+        let word_count = (self.short_delta_count & 0x7FFF) as usize;
+        object.give(&(self.deltas.len() as u16))?;
+        object.give(&self.short_delta_count)?;
+        object.give(&(self.region_indices.len() as u16))?;
+        object.give(&self.region_indices[..])?;
+        for row in self.deltas.iter() {
+            for (i, value) in row.iter().enumerate() {
+                match (long, i < word_count) {
+                    (true, true) => object.give(value)?,
+                    (true, false) | (false, true) => object.give(&(*value as i16))?,
+                    (false, false) => object.give(&(*value as i8))?,
+                }
+            }
+        }
+        Ok(graph.add(object))
+    }
+}
+
+impl Pack for Regions {
+    fn pack(&self, graph: &mut Graph) -> Result<usize> {
+        let mut object = Object::default();
+        object.give(&self.axis_count)?;
+        object.give(&(self.records.len() as u16))?;
+        for record in self.records.iter() {
+            for axis in record.records.iter() {
+                object.give(&axis.start)?;
+                object.give(&axis.peak)?;
+                object.give(&axis.end)?;
+            }
+        }
+        Ok(graph.add(object))
+    }
+}
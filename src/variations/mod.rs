@@ -3,3 +3,9 @@
 //! [1]: https://learn.microsoft.com/en-us/typography/opentype/spec/otvarcommonformats
 
 pub mod item;
+
+/// Round a normalized coordinate to the precision of a 2.14 fixed-point number.
+#[inline]
+pub(crate) fn quantize(value: f32) -> f32 {
+    (value * 16384.0).round() / 16384.0
+}
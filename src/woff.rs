@@ -0,0 +1,120 @@
+//! The [Web Open Font Format][1].
+//!
+//! [1]: https://www.w3.org/TR/WOFF/
+
+use std::io::Read;
+
+use truetype::Tag;
+
+use crate::{Font, Result};
+
+/// The maximum number of bytes preallocated for decompressed data.
+const CAPACITY_LIMIT: usize = 1 << 20;
+
+table! {
+    @position
+    /// A file.
+    pub File {
+        header (Header),
+
+        records (Vec<Record>) |this, tape, _| { // TableDirectory
+            tape.take_given(this.header.table_count as usize)
+        },
+
+        tables (Vec<Vec<u8>>) |this, tape, position| {
+            this.records
+                .iter()
+                .map(|record| {
+                    tape.jump(position + record.offset as u64)?;
+                    let data = tape.take_bytes(record.size as usize)?;
+                    if record.size < record.original_size {
+                        inflate(&data, record.original_size as usize)
+                    } else if record.size == record.original_size {
+                        Ok(data)
+                    } else {
+                        raise!("found a malformed table record with {:?}", record.tag)
+                    }
+                })
+                .collect()
+        },
+
+        metadata (Option<String>) |this, tape, position| { // ExtendedMetadata
+            if this.header.metadata_offset == 0 {
+                return Ok(None);
+            }
+            tape.jump(position + this.header.metadata_offset as u64)?;
+            let data = tape.take_bytes(this.header.metadata_size as usize)?;
+            let data = inflate(&data, this.header.metadata_original_size as usize)?;
+            match String::from_utf8(data) {
+                Ok(value) => Ok(Some(value)),
+                _ => raise!("found malformed extended metadata"),
+            }
+        },
+
+        private (Option<Vec<u8>>) |this, tape, position| { // PrivateData
+            if this.header.private_offset == 0 {
+                return Ok(None);
+            }
+            tape.jump(position + this.header.private_offset as u64)?;
+            Ok(Some(tape.take_bytes(this.header.private_size as usize)?))
+        },
+    }
+}
+
+table! {
+    /// The header of a file.
+    #[derive(Copy)]
+    pub Header { // WOFFHeader
+        signature              (Tag) = { Tag(*b"wOFF") }, // signature
+        flavor                 (u32), // flavor
+        size                   (u32), // length
+        table_count            (u16), // numTables
+        reserved               (u16) = { 0 }, // reserved
+        sfnt_size              (u32), // totalSfntSize
+        major_version          (u16), // majorVersion
+        minor_version          (u16), // minorVersion
+        metadata_offset        (u32), // metaOffset
+        metadata_size          (u32), // metaLength
+        metadata_original_size (u32), // metaOrigLength
+        private_offset         (u32), // privOffset
+        private_size           (u32), // privLength
+    }
+}
+
+table! {
+    /// A record of a file.
+    #[derive(Copy)]
+    pub Record { // TableDirectoryEntry
+        tag           (Tag), // tag
+        offset        (u32), // offset
+        size          (u32), // compLength
+        original_size (u32), // origLength
+        checksum      (u32), // origChecksum
+    }
+}
+
+impl TryFrom<File> for Font {
+    type Error = crate::Error;
+
+    fn try_from(file: File) -> Result<Self> {
+        let tables = file
+            .records
+            .iter()
+            .zip(file.tables)
+            .map(|(record, data)| (record.tag, record.checksum, data))
+            .collect();
+        let fonts = vec![(file.header.flavor, (0..file.records.len()).collect())];
+        Ok(Font::assemble(fonts, tables)?.remove(0))
+    }
+}
+
+fn inflate(data: &[u8], size: usize) -> Result<Vec<u8>> {
+    let mut buffer = Vec::with_capacity(size.min(CAPACITY_LIMIT));
+    flate2::read::ZlibDecoder::new(data)
+        .take(size as u64 + 1)
+        .read_to_end(&mut buffer)?;
+    if buffer.len() != size {
+        raise!("found malformed compressed data");
+    }
+    Ok(buffer)
+}
@@ -0,0 +1,314 @@
+//! The [Web Open Font Format 2][1].
+//!
+//! [1]: https://www.w3.org/TR/WOFF2/
+
+pub(crate) mod transform;
+
+use std::io::Read;
+
+use truetype::Tag;
+
+use crate::{Font, Result};
+
+/// The maximum number of bytes preallocated for decompressed data.
+const CAPACITY_LIMIT: usize = 1 << 20;
+
+/// A file.
+#[derive(Clone, Debug, Default)]
+pub struct File {
+    /// The header.
+    pub header: Header,
+    /// The records of the tables.
+    pub records: Vec<Record>,
+    /// The collection header if the file contains a collection.
+    pub collection: Option<Collection>,
+    /// The decompressed and reconstructed data of the tables.
+    pub tables: Vec<Vec<u8>>,
+    /// The decompressed extended metadata.
+    pub metadata: Option<String>,
+    /// The private data.
+    pub private: Option<Vec<u8>>,
+}
+
+table! {
+    /// The header of a file.
+    #[derive(Copy)]
+    pub Header { // WOFF2Header
+        signature              (Tag) = { Tag(*b"wOF2") }, // signature
+        flavor                 (u32), // flavor
+        size                   (u32), // length
+        table_count            (u16), // numTables
+        reserved               (u16) = { 0 }, // reserved
+        sfnt_size              (u32), // totalSfntSize
+        compressed_size        (u32), // totalCompressedSize
+        major_version          (u16), // majorVersion
+        minor_version          (u16), // minorVersion
+        metadata_offset        (u32), // metaOffset
+        metadata_size          (u32), // metaLength
+        metadata_original_size (u32), // metaOrigLength
+        private_offset         (u32), // privOffset
+        private_size           (u32), // privLength
+    }
+}
+
+table! {
+    @define
+    /// A record of a file.
+    #[derive(Copy)]
+    pub Record { // TableDirectoryEntry
+        flags          (u8         ), // flags
+        tag            (Tag        ), // tag
+        original_size  (u32        ), // origLength
+        transform_size (Option<u32>), // transformLength
+    }
+}
+
+table! {
+    @define
+    /// A collection of a file.
+    pub Collection { // CollectionHeader
+        version    (u32                  ), // version
+        font_count (u16                  ), // numFonts
+        records    (Vec<CollectionRecord>), // CollectionFontEntry
+    }
+}
+
+table! {
+    @define
+    /// A record of a collection.
+    pub CollectionRecord { // CollectionFontEntry
+        table_count (u16     ), // numTables
+        flavor      (u32     ), // flavor
+        indices     (Vec<u16>), // index
+    }
+}
+
+impl Record {
+    /// Return the transformation version.
+    #[inline]
+    pub fn transform(&self) -> u8 {
+        self.flags >> 6
+    }
+
+    /// Return the size of the data stored in the compressed stream.
+    #[inline]
+    pub fn stored_size(&self) -> u32 {
+        self.transform_size.unwrap_or(self.original_size)
+    }
+}
+
+impl crate::value::Read for File {
+    fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
+        let position = tape.position()?;
+        let header = tape.take::<Header>()?;
+        let records = (0..header.table_count)
+            .map(|_| tape.take())
+            .collect::<Result<Vec<Record>>>()?;
+        let collection = if header.flavor == u32::from(Tag(*b"ttcf")) {
+            let collection = tape.take::<Collection>()?;
+            for record in collection.records.iter() {
+                if record.indices.iter().any(|i| *i >= header.table_count) {
+                    raise!("found a malformed collection record");
+                }
+            }
+            Some(collection)
+        } else {
+            None
+        };
+        let data = tape.take_bytes(header.compressed_size as usize)?;
+        let size = records
+            .iter()
+            .map(|record| record.stored_size() as usize)
+            .sum();
+        let data = decompress(&data, size)?;
+        let mut offset = 0;
+        let mut tables = records
+            .iter()
+            .map(|record| {
+                let size = record.stored_size() as usize;
+                let table = data[offset..][..size].to_vec();
+                offset += size;
+                table
+            })
+            .collect::<Vec<_>>();
+        let fonts: Vec<Vec<usize>> = match collection {
+            Some(ref collection) => collection
+                .records
+                .iter()
+                .map(|record| record.indices.iter().map(|i| *i as usize).collect())
+                .collect(),
+            _ => vec![(0..records.len()).collect()],
+        };
+        let mut done = vec![false; records.len()];
+        for indices in fonts.iter() {
+            transform::reconstruct(&records, &mut tables, indices, &mut done)?;
+        }
+        for (record, table) in records.iter().zip(&tables) {
+            if record.tag == *b"glyf" && record.transform_size.is_some() {
+                continue;
+            }
+            if table.len() != record.original_size as usize {
+                raise!("found a malformed table with {:?}", record.tag);
+            }
+        }
+        let metadata = if header.metadata_offset > 0 {
+            tape.jump(position + header.metadata_offset as u64)?;
+            let data = tape.take_bytes(header.metadata_size as usize)?;
+            let data = decompress(&data, header.metadata_original_size as usize)?;
+            match String::from_utf8(data) {
+                Ok(value) => Some(value),
+                _ => raise!("found malformed extended metadata"),
+            }
+        } else {
+            None
+        };
+        let private = if header.private_offset > 0 {
+            tape.jump(position + header.private_offset as u64)?;
+            Some(tape.take_bytes(header.private_size as usize)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            header,
+            records,
+            collection,
+            tables,
+            metadata,
+            private,
+        })
+    }
+}
+
+impl crate::value::Read for Record {
+    fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
+        let flags = tape.take::<u8>()?;
+        let tag = match flags & 0x3f {
+            0x3f => tape.take()?,
+            index => Tag(TAGS[index as usize]),
+        };
+        let original_size = take_u32(tape)?;
+        let transformed = match &tag.0 {
+            b"glyf" | b"loca" => flags >> 6 != 3,
+            _ => flags >> 6 != 0,
+        };
+        let transform_size = if transformed {
+            Some(take_u32(tape)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            flags,
+            tag,
+            original_size,
+            transform_size,
+        })
+    }
+}
+
+impl crate::value::Read for Collection {
+    fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
+        let version = tape.take()?;
+        if version != 0x00010000 && version != 0x00020000 {
+            raise!("found an unknown version of the collection ({version:#x})");
+        }
+        let font_count = take_u16(tape)?;
+        let records = (0..font_count)
+            .map(|_| tape.take())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            version,
+            font_count,
+            records,
+        })
+    }
+}
+
+impl crate::value::Read for CollectionRecord {
+    fn read<T: crate::tape::Read>(tape: &mut T) -> Result<Self> {
+        let table_count = take_u16(tape)?;
+        let flavor = tape.take()?;
+        let indices = (0..table_count)
+            .map(|_| take_u16(tape))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            table_count,
+            flavor,
+            indices,
+        })
+    }
+}
+
+impl TryFrom<File> for crate::File {
+    type Error = crate::Error;
+
+    fn try_from(file: File) -> Result<Self> {
+        let fonts = match file.collection {
+            Some(ref collection) => collection
+                .records
+                .iter()
+                .map(|record| {
+                    let indices = record.indices.iter().map(|i| *i as usize).collect();
+                    (record.flavor, indices)
+                })
+                .collect(),
+            _ => vec![(file.header.flavor, (0..file.records.len()).collect())],
+        };
+        let tables = file
+            .records
+            .iter()
+            .zip(file.tables)
+            .map(|(record, data)| (record.tag, crate::font::checksum(&record.tag, &data), data))
+            .collect();
+        Ok(Self {
+            fonts: Font::assemble(fonts, tables)?,
+        })
+    }
+}
+
+fn decompress(data: &[u8], size: usize) -> Result<Vec<u8>> {
+    let mut buffer = Vec::with_capacity(size.min(CAPACITY_LIMIT));
+    brotli_decompressor::Decompressor::new(data, 4096)
+        .take(size as u64 + 1)
+        .read_to_end(&mut buffer)?;
+    if buffer.len() != size {
+        raise!("found malformed compressed data");
+    }
+    Ok(buffer)
+}
+
+fn take_u16<T: crate::tape::Read>(tape: &mut T) -> Result<u16> {
+    Ok(match tape.take::<u8>()? {
+        253 => tape.take::<u16>()?,
+        254 => tape.take::<u8>()? as u16 + 253 * 2,
+        255 => tape.take::<u8>()? as u16 + 253,
+        value => value as u16,
+    })
+}
+
+fn take_u32<T: crate::tape::Read>(tape: &mut T) -> Result<u32> {
+    let mut value = 0u32;
+    for i in 0..5 {
+        let byte = tape.take::<u8>()?;
+        if i == 0 && byte == 0x80 {
+            raise!("found a malformed variable-length integer");
+        }
+        if value & 0xfe00_0000 != 0 {
+            raise!("found an overflowing variable-length integer");
+        }
+        value = (value << 7) | (byte & 0x7f) as u32;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    raise!("found an overlong variable-length integer")
+}
+
+const TAGS: [[u8; 4]; 63] = [
+    *b"cmap", *b"head", *b"hhea", *b"hmtx", *b"maxp", *b"name", *b"OS/2", *b"post", *b"cvt ",
+    *b"fpgm", *b"glyf", *b"loca", *b"prep", *b"CFF ", *b"VORG", *b"EBDT", *b"EBLC", *b"gasp",
+    *b"hdmx", *b"kern", *b"LTSH", *b"PCLT", *b"VDMX", *b"vhea", *b"vmtx", *b"BASE", *b"GDEF",
+    *b"GPOS", *b"GSUB", *b"EBSC", *b"JSTF", *b"MATH", *b"CBDT", *b"CBLC", *b"COLR", *b"CPAL",
+    *b"SVG ", *b"sbix", *b"acnt", *b"avar", *b"bdat", *b"bloc", *b"bsln", *b"cvar", *b"fdsc",
+    *b"feat", *b"fmtx", *b"fvar", *b"gvar", *b"hsty", *b"just", *b"lcar", *b"mort", *b"morx",
+    *b"opbd", *b"prop", *b"trak", *b"Zapf", *b"Silf", *b"Glat", *b"Gloc", *b"Feat", *b"Sill",
+];
@@ -0,0 +1,402 @@
+use std::io::Cursor;
+
+use crate::tape::{Read, Write};
+use crate::woff2::{take_u16, Record};
+use crate::Result;
+
+table! {
+    /// A transformed glyph-data table.
+    #[derive(Copy)]
+    pub Glyphs { // TransformedGlyfTable
+        reserved         (u16) = { 0 }, // reserved
+        flags            (u16), // optionFlags
+        glyph_count      (u16), // numGlyphs
+        index_format     (u16), // indexFormat
+        contour_size     (u32), // nContourStreamSize
+        point_size       (u32), // nPointsStreamSize
+        flag_size        (u32), // flagStreamSize
+        glyph_size       (u32), // glyphStreamSize
+        composite_size   (u32), // compositeStreamSize
+        bounding_size    (u32), // bboxStreamSize
+        instruction_size (u32), // instructionStreamSize
+    }
+}
+
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+
+const ON_CURVE_POINT: u8 = 0x01;
+const X_SHORT_VECTOR: u8 = 0x02;
+const Y_SHORT_VECTOR: u8 = 0x04;
+const X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR: u8 = 0x10;
+const Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR: u8 = 0x20;
+const OVERLAP_SIMPLE: u8 = 0x40;
+
+/// Reverse the transformations of the tables of a font.
+pub fn reconstruct(
+    records: &[Record],
+    tables: &mut [Vec<u8>],
+    indices: &[usize],
+    done: &mut [bool],
+) -> Result<()> {
+    let find = |tag: &[u8; 4]| indices.iter().copied().find(|i| records[*i].tag == *tag);
+    let transformed = |i: Option<usize>| i.is_some_and(|i| records[i].transform_size.is_some());
+    let (glyf, loca, hmtx) = (find(b"glyf"), find(b"loca"), find(b"hmtx"));
+    if transformed(glyf) != transformed(loca) {
+        raise!("found inconsistent transformations of glyph data and glyph mapping");
+    }
+    if let (true, Some(glyf), Some(loca)) = (transformed(glyf), glyf, loca) {
+        if !done[glyf] {
+            if !tables[loca].is_empty() {
+                raise!("found a malformed transformed glyph mapping");
+            }
+            let (glyph_data, glyph_mapping) = glyphs(&tables[glyf])?;
+            tables[glyf] = glyph_data;
+            tables[loca] = glyph_mapping;
+            done[glyf] = true;
+            done[loca] = true;
+        }
+    }
+    if let (true, Some(hmtx)) = (transformed(hmtx), hmtx) {
+        if done[hmtx] {
+            return Ok(());
+        }
+        let (glyf, loca) = match (glyf, loca) {
+            (Some(glyf), Some(loca)) => (glyf, loca),
+            _ => raise!("found transformed horizontal metrics without glyph data"),
+        };
+        if !done[glyf] {
+            raise!("found transformed horizontal metrics without transformed glyph data");
+        }
+        let (hhea, maxp) = match (find(b"hhea"), find(b"maxp")) {
+            (Some(hhea), Some(maxp)) => (hhea, maxp),
+            _ => raise!("found transformed horizontal metrics without headers"),
+        };
+        let metric_count = Cursor::new(&tables[hhea]).stay(|tape| {
+            tape.jump(34)?;
+            tape.take::<u16>()
+        })? as usize;
+        let glyph_count = Cursor::new(&tables[maxp]).stay(|tape| {
+            tape.jump(4)?;
+            tape.take::<u16>()
+        })? as usize;
+        let minima = minima(&tables[glyf], &tables[loca], glyph_count)?;
+        tables[hmtx] = metrics(&tables[hmtx], metric_count, &minima)?;
+        done[hmtx] = true;
+    }
+    Ok(())
+}
+
+fn glyphs(data: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    let header = Cursor::new(data).take::<Glyphs>()?;
+    let glyph_count = header.glyph_count as usize;
+    let mut offset = 36;
+    let mut stream = |size: usize| -> Result<&[u8]> {
+        if offset + size > data.len() {
+            raise!("found a malformed transformed glyph data");
+        }
+        offset += size;
+        Ok(&data[offset - size..offset])
+    };
+    let mut contours = Cursor::new(stream(header.contour_size as usize)?);
+    let mut points = Cursor::new(stream(header.point_size as usize)?);
+    let mut flags = Cursor::new(stream(header.flag_size as usize)?);
+    let mut glyphs = Cursor::new(stream(header.glyph_size as usize)?);
+    let mut composites = Cursor::new(stream(header.composite_size as usize)?);
+    let boundings = stream(header.bounding_size as usize)?;
+    let mut instructions = Cursor::new(stream(header.instruction_size as usize)?);
+    let overlaps = if header.flags & 1 != 0 {
+        Some(stream((glyph_count + 7) >> 3)?)
+    } else {
+        None
+    };
+    let bitmap_size = ((glyph_count + 31) >> 5) << 2;
+    if boundings.len() < bitmap_size {
+        raise!("found a malformed transformed glyph data");
+    }
+    let (bitmap, boundings) = boundings.split_at(bitmap_size);
+    let mut boundings = Cursor::new(boundings);
+    let bit = |bitmap: &[u8], i: usize| bitmap[i >> 3] & (0x80 >> (i & 7)) != 0;
+
+    let mut glyph_data = vec![];
+    let mut offsets = Vec::with_capacity(glyph_count + 1);
+    for i in 0..glyph_count {
+        offsets.push(glyph_data.len());
+        let contour_count = contours.take::<i16>()?;
+        let explicit = bit(bitmap, i);
+        match contour_count {
+            0 => {
+                if explicit {
+                    raise!("found an empty glyph with a bounding box");
+                }
+            }
+            -1 => {
+                if !explicit {
+                    raise!("found a composite glyph without a bounding box");
+                }
+                let bounding = boundings.take_bytes(8)?;
+                let start = composites.position() as usize;
+                let mut has_instructions = false;
+                loop {
+                    let flags = composites.take::<u16>()?;
+                    let mut size = 2 + if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+                        4
+                    } else {
+                        2
+                    };
+                    if flags & WE_HAVE_A_SCALE != 0 {
+                        size += 2;
+                    } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+                        size += 4;
+                    } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+                        size += 8;
+                    }
+                    composites.take_bytes(size)?;
+                    has_instructions |= flags & WE_HAVE_INSTRUCTIONS != 0;
+                    if flags & MORE_COMPONENTS == 0 {
+                        break;
+                    }
+                }
+                let end = composites.position() as usize;
+                glyph_data.give(&contour_count)?;
+                glyph_data.give_bytes(&bounding)?;
+                glyph_data.give_bytes(&composites.get_ref()[start..end])?;
+                if has_instructions {
+                    let size = take_u16(&mut glyphs)?;
+                    glyph_data.give(&size)?;
+                    glyph_data.give_bytes(&instructions.take_bytes(size as usize)?)?;
+                }
+            }
+            contour_count if contour_count > 0 => {
+                let mut end_points = Vec::with_capacity(contour_count as usize);
+                let mut point_count = 0usize;
+                for _ in 0..contour_count {
+                    point_count += take_u16(&mut points)? as usize;
+                    if point_count == 0 || point_count > 0xffff {
+                        raise!("found a malformed simple glyph");
+                    }
+                    end_points.push(point_count as u16 - 1);
+                }
+                let mut coordinates = Vec::with_capacity(point_count);
+                let (mut x, mut y) = (0i32, 0i32);
+                for flag in flags.take_bytes(point_count)? {
+                    let (dx, dy) = triplet(flag & 0x7f, &mut glyphs)?;
+                    x += dx;
+                    y += dy;
+                    coordinates.push((flag >> 7 == 0, x, y));
+                }
+                let instruction_size = take_u16(&mut glyphs)?;
+                let bounding = if explicit {
+                    let x_min = boundings.take::<i16>()?;
+                    let y_min = boundings.take::<i16>()?;
+                    let x_max = boundings.take::<i16>()?;
+                    let y_max = boundings.take::<i16>()?;
+                    (x_min, y_min, x_max, y_max)
+                } else {
+                    let (mut x_min, mut y_min) = (i32::MAX, i32::MAX);
+                    let (mut x_max, mut y_max) = (i32::MIN, i32::MIN);
+                    for (_, x, y) in coordinates.iter() {
+                        x_min = x_min.min(*x);
+                        y_min = y_min.min(*y);
+                        x_max = x_max.max(*x);
+                        y_max = y_max.max(*y);
+                    }
+                    (x_min as i16, y_min as i16, x_max as i16, y_max as i16)
+                };
+                glyph_data.give(&contour_count)?;
+                glyph_data.give(&bounding.0)?;
+                glyph_data.give(&bounding.1)?;
+                glyph_data.give(&bounding.2)?;
+                glyph_data.give(&bounding.3)?;
+                glyph_data.give(&end_points[..])?;
+                glyph_data.give(&instruction_size)?;
+                glyph_data.give_bytes(&instructions.take_bytes(instruction_size as usize)?)?;
+                let overlap = overlaps.is_some_and(|overlaps| bit(overlaps, i));
+                encode(&mut glyph_data, &coordinates, overlap)?;
+            }
+            _ => raise!("found a malformed transformed glyph data"),
+        }
+        glyph_data.resize((glyph_data.len() + 3) & !3, 0);
+    }
+    offsets.push(glyph_data.len());
+
+    let mut glyph_mapping = vec![];
+    match header.index_format {
+        0 => {
+            if glyph_data.len() > 2 * 0xffff {
+                raise!("found glyph data too large for short offsets");
+            }
+            for offset in offsets {
+                glyph_mapping.give(&((offset >> 1) as u16))?;
+            }
+        }
+        1 => {
+            for offset in offsets {
+                glyph_mapping.give(&(offset as u32))?;
+            }
+        }
+        value => raise!("found an unknown format of the glyph mapping ({value})"),
+    }
+    Ok((glyph_data, glyph_mapping))
+}
+
+pub(crate) fn encode(
+    data: &mut Vec<u8>,
+    coordinates: &[(bool, i32, i32)],
+    overlap: bool,
+) -> Result<()> {
+    let mut flags = Vec::with_capacity(coordinates.len());
+    let mut xs = vec![];
+    let mut ys = vec![];
+    let (mut x, mut y) = (0, 0);
+    for (i, (on_curve, x_next, y_next)) in coordinates.iter().enumerate() {
+        let mut flag = if *on_curve { ON_CURVE_POINT } else { 0 };
+        if i == 0 && overlap {
+            flag |= OVERLAP_SIMPLE;
+        }
+        for (delta, values, short, same) in [
+            (
+                x_next - x,
+                &mut xs,
+                X_SHORT_VECTOR,
+                X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR,
+            ),
+            (
+                y_next - y,
+                &mut ys,
+                Y_SHORT_VECTOR,
+                Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR,
+            ),
+        ] {
+            if delta == 0 {
+                flag |= same;
+            } else if delta.abs() < 256 {
+                flag |= short;
+                if delta > 0 {
+                    flag |= same;
+                }
+                values.give(&(delta.unsigned_abs() as u8))?;
+            } else {
+                values.give(&(delta as i16))?;
+            }
+        }
+        flags.push(flag);
+        x = *x_next;
+        y = *y_next;
+    }
+    data.give_bytes(&flags)?;
+    data.give_bytes(&xs)?;
+    data.give_bytes(&ys)
+}
+
+fn metrics(data: &[u8], metric_count: usize, minima: &[i16]) -> Result<Vec<u8>> {
+    let tape = &mut Cursor::new(data);
+    let flags = tape.take::<u8>()?;
+    if flags & !0b11 != 0 || flags & 0b11 == 0 {
+        raise!("found malformed transformed horizontal metrics");
+    }
+    if metric_count == 0 || metric_count > minima.len() {
+        raise!("found a malformed horizontal header");
+    }
+    let advances: Vec<u16> = tape.take_given(metric_count)?;
+    let bearings: Vec<i16> = if flags & 0b01 == 0 {
+        tape.take_given(metric_count)?
+    } else {
+        minima[..metric_count].to_vec()
+    };
+    let other_bearings: Vec<i16> = if flags & 0b10 == 0 {
+        tape.take_given(minima.len() - metric_count)?
+    } else {
+        minima[metric_count..].to_vec()
+    };
+    let mut metrics = vec![];
+    for (advance, bearing) in advances.iter().zip(&bearings) {
+        metrics.give(advance)?;
+        metrics.give(bearing)?;
+    }
+    metrics.give(&other_bearings[..])?;
+    Ok(metrics)
+}
+
+fn minima(glyph_data: &[u8], glyph_mapping: &[u8], glyph_count: usize) -> Result<Vec<i16>> {
+    let short = glyph_mapping.len() == 2 * (glyph_count + 1);
+    if !short && glyph_mapping.len() != 4 * (glyph_count + 1) {
+        raise!("found a malformed glyph mapping");
+    }
+    let mapping = &mut Cursor::new(glyph_mapping);
+    let data = &mut Cursor::new(glyph_data);
+    let mut offsets = Vec::with_capacity(glyph_count + 1);
+    for _ in 0..(glyph_count + 1) {
+        offsets.push(if short {
+            2 * mapping.take::<u16>()? as u64
+        } else {
+            mapping.take::<u32>()? as u64
+        });
+    }
+    offsets
+        .windows(2)
+        .map(|window| {
+            if window[0] < window[1] {
+                data.jump(window[0] + 2)?;
+                data.take()
+            } else {
+                Ok(0)
+            }
+        })
+        .collect()
+}
+
+fn triplet<T: Read>(flag: u8, tape: &mut T) -> Result<(i32, i32)> {
+    #[inline]
+    fn sign(flag: u8, value: i32) -> i32 {
+        if flag & 1 != 0 {
+            value
+        } else {
+            -value
+        }
+    }
+
+    let flag = flag as i32;
+    let byte = |tape: &mut T| -> Result<i32> { Ok(tape.take::<u8>()? as i32) };
+    Ok(if flag < 10 {
+        (0, sign(flag as u8, ((flag & 14) << 7) + byte(tape)?))
+    } else if flag < 20 {
+        (sign(flag as u8, (((flag - 10) & 14) << 7) + byte(tape)?), 0)
+    } else if flag < 84 {
+        let b0 = flag - 20;
+        let b1 = byte(tape)?;
+        (
+            sign(flag as u8, 1 + (b0 & 0x30) + (b1 >> 4)),
+            sign((flag >> 1) as u8, 1 + ((b0 & 0x0c) << 2) + (b1 & 0x0f)),
+        )
+    } else if flag < 120 {
+        let b0 = flag - 84;
+        let b1 = byte(tape)?;
+        let b2 = byte(tape)?;
+        (
+            sign(flag as u8, 1 + ((b0 / 12) << 8) + b1),
+            sign((flag >> 1) as u8, 1 + (((b0 % 12) >> 2) << 8) + b2),
+        )
+    } else if flag < 124 {
+        let b1 = byte(tape)?;
+        let b2 = byte(tape)?;
+        let b3 = byte(tape)?;
+        (
+            sign(flag as u8, (b1 << 4) + (b2 >> 4)),
+            sign((flag >> 1) as u8, ((b2 & 0x0f) << 8) + b3),
+        )
+    } else {
+        let b1 = byte(tape)?;
+        let b2 = byte(tape)?;
+        let b3 = byte(tape)?;
+        let b4 = byte(tape)?;
+        (
+            sign(flag as u8, (b1 << 8) + b2),
+            sign((flag >> 1) as u8, (b3 << 8) + b4),
+        )
+    })
+}
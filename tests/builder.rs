@@ -0,0 +1,82 @@
+#[macro_use]
+mod support;
+
+use std::io::Cursor;
+
+use opentype::tables::GlyphSubstitution;
+use opentype::truetype::tables::offsets::Offsets;
+use opentype::truetype::tables::FontHeader;
+use opentype::truetype::Tag;
+use opentype::value::Read as _;
+use opentype::{Builder, File};
+
+#[test]
+fn copy() {
+    let mut tape = setup!(SourceSerifPro);
+    let file = ok!(File::read(&mut tape));
+    let builder = ok!(Builder::from_font(&file[0], &mut tape));
+    let data = ok!(builder.build());
+    assert_eq!(data.len() % 4, 0);
+    assert_eq!(checksum(&data), 0xB1B0AFBA);
+
+    let offsets = ok!(Offsets::read(&mut Cursor::new(&data)));
+    assert_eq!(offsets.header.version, u32::from(Tag(*b"OTTO")));
+    assert_eq!(offsets.header.table_count, 12);
+    assert_eq!(offsets.header.search_range, 8 * 16);
+    assert_eq!(offsets.header.entry_selector, 3);
+    assert_eq!(offsets.header.range_shift, 4 * 16);
+    assert!(offsets
+        .records
+        .windows(2)
+        .all(|records| records[0].tag < records[1].tag));
+    assert!(offsets.records.iter().all(|record| record.offset % 4 == 0));
+
+    let mut tape = Cursor::new(data);
+    let file = ok!(File::read(&mut tape));
+    for record in file[0].offsets.records.iter() {
+        assert_eq!(record.checksum, ok!(record.checksum(&mut tape)));
+    }
+    let table = ok!(ok!(file[0].take::<_, GlyphSubstitution>(&mut tape)));
+    assert_eq!(table.lookups.records.len(), 19);
+}
+
+#[test]
+fn patch() {
+    let mut tape = setup!(OpenSans);
+    let file = ok!(File::read(&mut tape));
+    let mut font_header = ok!(ok!(file[0].take::<_, FontHeader>(&mut tape)));
+    font_header.units_per_em = 1000;
+    let mut builder = ok!(Builder::from_font(&file[0], &mut tape));
+    ok!(builder.add(&font_header));
+    assert!(builder.remove(Tag(*b"GDEF")).is_some());
+    builder.add_bytes(Tag(*b"TEST"), vec![1, 2, 3]);
+    let mut data = vec![];
+    ok!(builder.write(&mut data));
+    assert_eq!(checksum(&data), 0xB1B0AFBA);
+
+    let mut tape = Cursor::new(data);
+    let file = ok!(File::read(&mut tape));
+    let table = ok!(ok!(file[0].take::<_, FontHeader>(&mut tape)));
+    assert_eq!(table.units_per_em, 1000);
+    assert_ne!(table.checksum_adjustment, font_header.checksum_adjustment);
+    let record = ok!(file[0]
+        .offsets
+        .records
+        .iter()
+        .find(|record| record.tag == Tag(*b"TEST")));
+    assert_eq!(record.size, 3);
+    assert_eq!(record.checksum, 0x01020300);
+    assert!(!file[0]
+        .offsets
+        .records
+        .iter()
+        .any(|record| record.tag == Tag(*b"GDEF")));
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |total, chunk| {
+        let mut value = [0; 4];
+        value[..chunk.len()].copy_from_slice(chunk);
+        total.wrapping_add(u32::from_be_bytes(value))
+    })
+}
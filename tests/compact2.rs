@@ -0,0 +1,146 @@
+#[macro_use]
+mod support;
+
+use opentype::postscript::compact1::Number;
+use opentype::tables::compact2::{Compact2, Operator, Segment};
+use opentype::tables::GlyphVariations;
+use opentype::truetype::tables::{
+    FontHeader, GlyphData, GlyphMapping, HorizontalHeader, HorizontalMetrics, MaximumProfile,
+};
+use opentype::value::Read;
+use opentype::File;
+
+#[test]
+fn table() {
+    let table: Compact2 = ok!(Read::read(&mut setup!(AdobeVFPrototypeCFF, "CFF2")));
+    assert_eq!(table.header_size, 5);
+    assert_eq!(table.glyph_count(), 313);
+    assert_eq!(table.global_subroutines.len(), 0);
+    assert_eq!(table.dictionaries.len(), 1);
+    assert!(table.selector.is_none());
+    assert_eq!(table.dictionary_index(42), Some(0));
+    let store = ok!(table.store.as_ref());
+    assert_eq!(store.count, 1);
+    assert_eq!(store.regions.region_count, 5);
+    let private = &table.privates[0];
+    assert_eq!(private.subroutines.len(), 542);
+    assert_eq!(private.operations.get_integer(Operator::VSIndex), Some(0));
+    assert_eq!(
+        private
+            .operations
+            .get(Operator::BlueScale)
+            .map(|operands| operands[0].value),
+        Some(Number::Real(0.0375)),
+    );
+    let operands = ok!(private.operations.get(Operator::StdVW));
+    assert_eq!(operands[0].value, Number::Integer(80));
+    assert_eq!(operands[0].blend(&ok!(store.scalars(0, &[0.0, 0.0]))), 80.0);
+    assert_eq!(
+        operands[0].blend(&ok!(store.scalars(0, &[1.0, 0.0]))),
+        190.0
+    );
+    assert_eq!(
+        operands[0].blend(&ok!(store.scalars(0, &[-1.0, 0.0]))),
+        28.0
+    );
+}
+
+#[test]
+fn blend() {
+    use std::io::Cursor;
+
+    use opentype::tables::compact2::{Operand, Operations};
+    use opentype::variations::item::Store;
+
+    #[rustfmt::skip]
+    let data: Vec<u8> = vec![
+        0, 1, 0, 0, 0, 18, 0, 1, 0, 0, 0, 12,
+        0, 0, 0, 0, 0, 0,
+        0, 1, 0, 0,
+    ];
+    let store: Store = ok!(Read::read(&mut Cursor::new(data)));
+    let data: Vec<u8> = vec![239, 149, 141, 0x17, 0x06];
+    let operations: Operations = ok!(opentype::walue::Read::read(
+        &mut Cursor::new(&data),
+        (data.len(), Some(&store))
+    ));
+    let value = |value| Operand {
+        value: Number::Integer(value),
+        deltas: vec![],
+    };
+    assert_eq!(
+        ok!(operations.get(Operator::BlueValues)),
+        [value(100), value(10)]
+    );
+}
+
+#[test]
+fn outline() {
+    let mut tape = setup!(AdobeVFPrototypeCFF);
+    let file = ok!(File::read(&mut tape));
+    let table = ok!(ok!(file[0].take::<_, Compact2>(&mut tape)));
+    let outline = ok!(table.outline(3, &[0.0, 0.0]));
+    assert_eq!(outline.contours.len(), 2);
+    assert_eq!(
+        outline.bounding_box(),
+        Some(((40.0, 431.0), (310.0, 737.0))),
+    );
+    let outline = ok!(table.outline(3, &[1.0, 0.0]));
+    assert_eq!(
+        outline.bounding_box(),
+        Some(((40.0, 381.0), (444.0, 726.0))),
+    );
+    let outline = ok!(table.outline(3, &[0.5, 0.5]));
+    assert_eq!(
+        outline.bounding_box(),
+        Some(((40.0, 406.0), (376.5, 731.5))),
+    );
+    assert!(outline
+        .contours
+        .iter()
+        .flat_map(|contour| contour.segments.iter())
+        .any(|segment| matches!(segment, Segment::Cubic(..))));
+    assert!(ok!(table.outline(1, &[1.0, 0.0])).contours.is_empty());
+}
+
+#[test]
+fn outlines() {
+    let mut tape = setup!(AdobeVFPrototypeCFF);
+    let file = ok!(File::read(&mut tape));
+    let table = ok!(ok!(file[0].take::<_, Compact2>(&mut tape)));
+
+    let mut other_tape = setup!(AdobeVFPrototypeTTF);
+    let other_file = ok!(File::read(&mut other_tape));
+    let font = &other_file[0];
+    let variations = ok!(ok!(font.take::<_, GlyphVariations>(&mut other_tape)));
+    let font_header = ok!(ok!(font.take::<_, FontHeader>(&mut other_tape)));
+    let header = ok!(ok!(font.take::<_, HorizontalHeader>(&mut other_tape)));
+    let profile = ok!(ok!(font.take::<_, MaximumProfile>(&mut other_tape)));
+    let metrics = ok!(ok!(
+        font.take_given::<_, HorizontalMetrics>(&mut other_tape, (&header, &profile))
+    ));
+    let mapping = ok!(ok!(
+        font.take_given::<_, GlyphMapping>(&mut other_tape, (&font_header, &profile))
+    ));
+    let data = ok!(ok!(
+        font.take_given::<_, GlyphData>(&mut other_tape, &mapping)
+    ));
+
+    for coordinates in [[0.0, 0.0], [1.0, 0.0], [-1.0, 0.0], [0.0, 1.0], [0.5, 0.5]] {
+        for glyph_id in 0..table.glyph_count() as u16 {
+            let outline = ok!(table.outline(glyph_id, &coordinates));
+            let other = ok!(variations.apply(&data, &metrics, glyph_id, &coordinates));
+            let (minimum, maximum) = match outline.bounding_box() {
+                Some(value) => value,
+                _ => {
+                    assert!(other.points.is_empty());
+                    continue;
+                }
+            };
+            for (x, y) in other.points {
+                assert!(minimum.0 - 1.0 <= x && x <= maximum.0 + 1.0);
+                assert!(minimum.1 - 1.0 <= y && y <= maximum.1 + 1.0);
+            }
+        }
+    }
+}
@@ -55,3 +55,44 @@ fn ttf_variable() {
     let file = ok!(File::read(&mut tape));
     let _ = ok!(ok!(file[0].take::<_, GlyphSubstitution>(&mut tape)));
 }
+
+#[test]
+fn ttc_shared() {
+    use std::io::{Cursor, Read};
+
+    use opentype::tables::GlyphDefinition;
+    use opentype::tape::Write;
+    use opentype::truetype::tables::offsets::Offsets;
+    use opentype::truetype::tables::FontHeader;
+    use opentype::value::Read as _;
+
+    let mut data = vec![];
+    ok!(setup!(OpenSans).read_to_end(&mut data));
+    let mut offsets: Offsets = ok!(Offsets::read(&mut Cursor::new(&data)));
+    let shift = 4 + 2 + 2 + 4 + 2 * 4 + 3 * 4;
+    for record in offsets.records.iter_mut() {
+        record.offset += shift;
+    }
+    let mut tape = vec![];
+    ok!(tape.give(b"ttcf"));
+    ok!(tape.give(&2u16));
+    ok!(tape.give(&0u16));
+    ok!(tape.give(&2u32));
+    ok!(tape.give(&shift));
+    ok!(tape.give(&(shift + data.len() as u32)));
+    ok!(tape.give(&[0u32, 0, 0][..]));
+    let mut font = vec![];
+    ok!(font.give(&offsets));
+    tape.extend_from_slice(&font);
+    tape.extend_from_slice(&data[font.len()..]);
+    tape.extend_from_slice(&font);
+
+    let mut tape = Cursor::new(tape);
+    let file = ok!(File::read(&mut tape));
+    assert_eq!(file.len(), 2);
+    for font in file.iter() {
+        let font_header = ok!(ok!(font.take::<_, FontHeader>(&mut tape)));
+        assert_eq!(font_header.units_per_em, 2048);
+        let _ = ok!(ok!(font.take::<_, GlyphDefinition>(&mut tape)));
+    }
+}
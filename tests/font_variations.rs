@@ -2,9 +2,103 @@
 mod support;
 
 use opentype::tables::font_variations::FontVariations;
+use opentype::tables::AxisVariations;
 use opentype::value::Read;
 use truetype::Tag;
 
+#[test]
+fn instances() {
+    use opentype::tables::font_variations::AxisFlags;
+    use opentype::truetype::tables::Names;
+    use opentype::File;
+
+    let mut tape = setup!(AdobeVFPrototypeTTF);
+    let file = ok!(File::read(&mut tape));
+    let mut table: FontVariations = ok!(ok!(file[0].take(&mut tape)));
+    let names: Names = ok!(ok!(file[0].take(&mut tape)));
+    let instances = table.instances(&names);
+    assert_eq!(instances.len(), 8);
+    let instance = &instances[2];
+    assert_eq!(ok!(instance.subfamily_name.as_deref()), "Regular");
+    assert_eq!(
+        ok!(instance.postscript_name.as_deref()),
+        "AdobeVFPrototype-Regular",
+    );
+    let values = instance
+        .coordinates
+        .iter()
+        .map(|value| (value.tag, ok!(value.name.as_deref()), value.value))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        values,
+        [
+            (Tag(*b"wght"), "Weight", 400.0),
+            (Tag(*b"CNTR"), "Contrast", 0.0),
+        ],
+    );
+    let instance = &instances[7];
+    assert_eq!(
+        ok!(instance.subfamily_name.as_deref()),
+        "Black High Contrast"
+    );
+    assert_eq!(instance.coordinates[1].value, 100.0);
+
+    table.axis_records[0].flags = AxisFlags(1);
+    table.instance_records[0].postscript_name_id = None;
+    let instances = table.instances(&names);
+    assert!(instances[0].postscript_name.is_none());
+    assert!(instances
+        .iter()
+        .all(|instance| instance.coordinates.len() == 1
+            && instance.coordinates[0].tag == Tag(*b"CNTR")));
+}
+
+#[test]
+fn normalize() {
+    let table: FontVariations = ok!(Read::read(&mut setup!(AdobeVFPrototypeTTF, "fvar")));
+    let other: AxisVariations = ok!(Read::read(&mut setup!(AdobeVFPrototypeTTF, "avar")));
+    assert_eq!(other.axis_count, 2);
+    assert_eq!(other.records[0].count, 7);
+    assert_eq!(other.records[1].count, 3);
+    let normalize = |values: &[(&[u8; 4], f32)], other: Option<&AxisVariations>| {
+        let values = values.iter().map(|(tag, value)| (Tag(**tag), *value));
+        table
+            .normalize(values, other)
+            .into_iter()
+            .map(|value| (value * 16384.0) as i32)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(normalize(&[], None), [0, 0]);
+    assert_eq!(normalize(&[(b"wght", 200.0)], None), [-16384, 0]);
+    assert_eq!(normalize(&[(b"wght", 300.0)], None), [-7731, 0]);
+    assert_eq!(normalize(&[(b"wght", 1000.0)], None), [16384, 0]);
+    assert_eq!(normalize(&[(b"CNTR", 50.0)], None), [0, 8192]);
+    assert_eq!(normalize(&[(b"wght", 300.0)], Some(&other)), [-9706, 0]);
+    assert_eq!(normalize(&[(b"wght", 650.0)], Some(&other)), [8918, 0]);
+    assert_eq!(normalize(&[(b"wght", 900.0)], Some(&other)), [16384, 0]);
+    assert_eq!(normalize(&[(b"CNTR", 50.0)], Some(&other)), [0, 8192]);
+}
+
+#[test]
+fn normalize_version2() {
+    use std::io::Cursor;
+
+    #[rustfmt::skip]
+    let mut data: Vec<u8> = vec![
+        0, 2, 0, 0, 0, 0, 0, 1,
+        0, 0,
+        0, 0, 0, 0, 0, 0, 0, 18,
+    ];
+    data.extend(support::store(&[0x1000]));
+    let table: AxisVariations = ok!(Read::read(&mut Cursor::new(data)));
+    assert!(table.mapping.is_none());
+    assert!(table.store.is_some());
+    assert_eq!(table.map(&[0.0]), [0.0]);
+    assert_eq!(table.map(&[0.5]), [0.625]);
+    assert_eq!(table.map(&[1.0]), [1.0]);
+    assert_eq!(table.map(&[-0.5]), [-0.5]);
+}
+
 #[test]
 fn table() {
     let table: FontVariations = ok!(Read::read(&mut setup!(AdobeVFPrototypeTTF, "fvar")));
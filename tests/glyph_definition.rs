@@ -26,3 +26,142 @@ fn table() {
         _ => unreachable!(),
     }
 }
+
+#[test]
+fn variations() {
+    let table: GlyphDefinition = ok!(Read::read(&mut setup!(AdobeVFPrototypeTTF, "GDEF")));
+    match &table.header {
+        &Header::Version13(..) => {}
+        _ => unreachable!(),
+    }
+    let store = ok!(table.variations.as_ref());
+    assert_eq!(store.count, 4);
+    let regions = &store.regions;
+    assert_eq!(regions.axis_count, 2);
+    assert_eq!(regions.region_count, 5);
+    let values = regions
+        .records
+        .iter()
+        .map(|record| {
+            record
+                .records
+                .iter()
+                .map(|axis| {
+                    (
+                        f32::from(axis.start),
+                        f32::from(axis.peak),
+                        f32::from(axis.end),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        values,
+        [
+            [(-1.0, -1.0, 0.0), (0.0, 0.0, 0.0)],
+            [(0.0, 1.0, 1.0), (0.0, 0.0, 0.0)],
+            [(0.0, 0.0, 0.0), (0.0, 1.0, 1.0)],
+            [(-1.0, -1.0, 0.0), (0.0, 1.0, 1.0)],
+            [(0.0, 1.0, 1.0), (0.0, 1.0, 1.0)],
+        ],
+    );
+    let scalars = |coordinates: &[f32]| {
+        regions
+            .records
+            .iter()
+            .map(|record| record.scalar(coordinates))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(scalars(&[]), [0.0, 0.0, 0.0, 0.0, 0.0]);
+    assert_eq!(scalars(&[1.0, 0.0]), [0.0, 1.0, 0.0, 0.0, 0.0]);
+    assert_eq!(scalars(&[0.5, 0.5]), [0.0, 0.5, 0.5, 0.0, 0.25]);
+    assert_eq!(scalars(&[-0.25, 1.0]), [0.25, 0.0, 1.0, 0.25, 0.0]);
+}
+
+#[test]
+fn deltas() {
+    use std::io::Cursor;
+
+    use opentype::variations::item::Store;
+
+    let table: GlyphDefinition = ok!(Read::read(&mut setup!(AdobeVFPrototypeTTF, "GDEF")));
+    let store = ok!(table.variations.as_ref());
+    assert_eq!(store.records[0].deltas[..3], [[-40], [-35], [-30]]);
+    assert_eq!(store.records[2].deltas[0], [-70, -95]);
+    assert_eq!(store.records[3].deltas[4], [20, 30, 10]);
+    assert_eq!(store.delta(0, 0, &[-1.0, 0.0]), Some(-40.0));
+    assert_eq!(store.delta(0, 0, &[1.0, 1.0]), Some(0.0));
+    assert_eq!(store.delta(2, 0, &[1.0, 1.0]), Some(-95.0));
+    assert_eq!(store.delta(2, 0, &[-1.0, 0.0]), Some(-70.0));
+    assert_eq!(store.delta(2, 0, &[0.5, 0.0]), Some(-47.5));
+    assert_eq!(store.delta(3, 0, &[-0.5, 0.5]), Some(-7.5));
+    assert_eq!(store.delta(3, 5, &[1.0, 1.0]), None);
+    assert_eq!(store.delta(4, 0, &[1.0, 1.0]), None);
+
+    #[rustfmt::skip]
+    let data: Vec<u8> = vec![
+        0, 1, 0, 0, 0, 34, 0, 1, 0, 0, 0, 12,
+        0, 2, 0x80, 1, 0, 2, 0, 0, 0, 1,
+        0, 1, 0x86, 0xA0, 0xFF, 0xFE,
+        0xFF, 0xFF, 0xFF, 0xFF, 0, 3,
+        0, 1, 0, 2,
+        0, 0, 0x40, 0, 0x40, 0,
+        0xC0, 0, 0xC0, 0, 0, 0,
+    ];
+    let store: Store = ok!(Read::read(&mut Cursor::new(data)));
+    assert_eq!(store.records[0].deltas, [[100000, -2], [-1, 3]]);
+    assert_eq!(store.delta(0, 0, &[1.0]), Some(100000.0));
+    assert_eq!(store.delta(0, 0, &[-0.5]), Some(-1.0));
+    assert_eq!(store.delta(0, 1, &[0.5]), Some(-0.5));
+}
+
+#[test]
+fn write() {
+    use std::io::Cursor;
+
+    use opentype::tape::Write;
+
+    let table: GlyphDefinition = ok!(Read::read(&mut setup!(OpenSans, "GDEF")));
+    let mut data = vec![];
+    ok!(data.give(&table));
+    let other: GlyphDefinition = ok!(Read::read(&mut Cursor::new(data)));
+    match &other.header {
+        &Header::Version1(..) => {}
+        _ => unreachable!(),
+    }
+    assert_eq!(
+        ok!(other.glyph_class.as_ref()).get(937),
+        ok!(table.glyph_class.as_ref()).get(937),
+    );
+    assert_eq!(ok!(other.ligatures.as_ref()).count, 0);
+
+    let mut table: GlyphDefinition = ok!(Read::read(&mut setup!(AdobeVFPrototypeTTF, "GDEF")));
+    let mut data = vec![];
+    ok!(data.give(&table));
+    let other: GlyphDefinition = ok!(Read::read(&mut Cursor::new(data)));
+    match &other.header {
+        &Header::Version13(..) => {}
+        _ => unreachable!(),
+    }
+    let (store, other) = (
+        ok!(table.variations.as_ref()),
+        ok!(other.variations.as_ref()),
+    );
+    assert_eq!(other.regions.records.len(), store.regions.records.len());
+    for (record, other) in store.records.iter().zip(other.records.iter()) {
+        assert_eq!(record.region_indices, other.region_indices);
+        assert_eq!(record.deltas, other.deltas);
+    }
+    assert_eq!(other.delta(2, 0, &[0.5, 0.0]), Some(-47.5));
+
+    table.instantiate(&[1.0, 0.0]);
+    let mut data = vec![];
+    ok!(data.give(&table));
+    let other: GlyphDefinition = ok!(Read::read(&mut Cursor::new(data)));
+    match &other.header {
+        &Header::Version12(..) => {}
+        _ => unreachable!(),
+    }
+    assert!(other.variations.is_none());
+}
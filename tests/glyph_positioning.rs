@@ -5,6 +5,80 @@ mod adobe_vf_prototype {
     use opentype::tables::glyph_positioning::GlyphPositioning;
     use opentype::value::Read;
 
+    #[test]
+    fn corrections() {
+        use opentype::layout::correction::{Device, Environment};
+        use opentype::layout::{Buffer, Correction};
+        use opentype::tables::glyph_definition::GlyphDefinition;
+        use opentype::tables::glyph_positioning::{PairAdjustment, Type};
+
+        let table: GlyphPositioning = ok!(Read::read(&mut setup!(AdobeVFPrototypeTTF, "GPOS")));
+        let definition: GlyphDefinition = ok!(Read::read(&mut setup!(AdobeVFPrototypeTTF, "GDEF")));
+        let environment = |coordinates| Environment {
+            size: None,
+            units_per_em: 1000,
+            store: definition.variations.as_ref(),
+            coordinates,
+        };
+        let tables = &table.lookups.records[0].tables;
+        let value = match &tables[0] {
+            Type::PairAdjustment(PairAdjustment::Format1(table)) => {
+                ok!(table.records[0].records[0].value1.as_ref())
+            }
+            _ => unreachable!(),
+        };
+        assert_eq!(value.resolve(&Default::default()), (0.0, 0.0, -20.0, 0.0));
+        assert_eq!(value.resolve(&environment(&[])), (0.0, 0.0, -20.0, 0.0));
+        assert_eq!(value.resolve(&environment(&[1.0])), (0.0, 0.0, 0.0, 0.0));
+        assert_eq!(value.resolve(&environment(&[0.5])), (0.0, 0.0, -10.0, 0.0));
+        let value = match &tables[1] {
+            Type::PairAdjustment(PairAdjustment::Format2(table)) => {
+                ok!(table.records[1].records[1].value1.as_ref())
+            }
+            _ => unreachable!(),
+        };
+        assert_eq!(value.resolve(&environment(&[-1.0])).2, -15.0);
+        assert_eq!(value.resolve(&environment(&[1.0])).2, -10.0);
+
+        let (glyph1_id, glyph2_id) = match &tables[0] {
+            Type::PairAdjustment(PairAdjustment::Format1(table)) => (
+                ok!(table.coverage.iter().next()).0,
+                table.records[0].records[0].glyph2_id,
+            ),
+            _ => unreachable!(),
+        };
+        let advance = |coordinates| {
+            let mut buffer = Buffer::new([glyph1_id, glyph2_id]);
+            ok!(table.apply(&mut buffer, &[0], None, &environment(coordinates)));
+            buffer.glyphs[0].x_advance
+        };
+        assert_eq!(advance(&[0.0]), -20);
+        assert_eq!(advance(&[0.5]), -10);
+        assert_eq!(advance(&[1.0]), 0);
+
+        let device = |start_size, end_size, format, deltas| Device {
+            start_size,
+            end_size,
+            format,
+            deltas,
+        };
+        let value = device(9, 12, 1, vec![0x7800]);
+        let deltas = (8..14).map(|size| value.delta(size)).collect::<Vec<_>>();
+        assert_eq!(deltas, [0, 1, -1, -2, 0, 0]);
+        let value = device(11, 14, 2, vec![0x1F78]);
+        let deltas = (11..15).map(|size| value.delta(size)).collect::<Vec<_>>();
+        assert_eq!(deltas, [1, -1, 7, -8]);
+        let value = Correction::Device(device(5, 6, 3, vec![0xFD64]));
+        let environment = |size| Environment {
+            size,
+            units_per_em: 1200,
+            ..Default::default()
+        };
+        assert_eq!(value.delta(&environment(None)), 0.0);
+        assert_eq!(value.delta(&environment(Some(5))), -720.0);
+        assert_eq!(value.delta(&environment(Some(6))), 20000.0);
+    }
+
     #[test]
     fn features() {
         let GlyphPositioning { features, .. } =
@@ -19,9 +93,193 @@ mod adobe_vf_prototype {
 }
 
 mod crimson_text {
-    use opentype::tables::glyph_positioning::GlyphPositioning;
+    use opentype::tables::glyph_definition::GlyphDefinition;
+    use opentype::tables::glyph_positioning::{GlyphPositioning, PairAdjustment, Type};
     use opentype::value::Read;
 
+    #[test]
+    fn apply() {
+        use opentype::layout::correction::Environment;
+        use opentype::layout::{Attachment, Buffer};
+        use opentype::tables::glyph_positioning::Anchor;
+        use opentype::truetype::tables::character_mapping::{CharacterMapping, Encoding};
+        use opentype::File;
+
+        let mut tape = setup!(CrimsonText);
+        let file = ok!(File::read(&mut tape));
+        let mapping: CharacterMapping = ok!(ok!(file[0].take(&mut tape)));
+        let mapping = ok!(mapping
+            .encodings
+            .iter()
+            .find_map(|encoding| match encoding {
+                Encoding::Format4(encoding) => Some(encoding.mapping::<u32>()),
+                _ => None,
+            }));
+        let table: GlyphPositioning = ok!(ok!(file[0].take(&mut tape)));
+        let definition: GlyphDefinition = ok!(ok!(file[0].take(&mut tape)));
+        let definition = Some(&definition);
+        let environment = Environment::default();
+        let acute = mapping[&0x301];
+        let buffer = |text: &str| {
+            let mut buffer = Buffer::new(text.chars().map(|code| mapping[&(code as u32)]));
+            for glyph in buffer.glyphs.iter_mut() {
+                glyph.x_advance = 500;
+            }
+            buffer
+        };
+
+        let mut value = buffer("AV");
+        ok!(table.apply(&mut value, &[0], definition, &environment));
+        assert_eq!(value.glyphs[0].x_advance, 420);
+        assert_eq!(value.glyphs[1].x_advance, 500);
+
+        let mut value = buffer("a\u{301}");
+        ok!(table.apply(&mut value, &[0, 1, 2], definition, &environment));
+        assert_eq!(value.glyphs[1].attachment, Some(Attachment::Mark(0)));
+        let (x, y) = match &table.lookups.records[1].tables[0] {
+            Type::MarkToBaseAttachment(table) => {
+                let mark = &table.marks.records[ok!(table.mark_coverage.index(acute)) as usize];
+                let base = ok!(table.base_coverage.index(value.glyphs[0].glyph_id));
+                let anchor = &table.bases.records[base as usize].anchors[mark.class_id as usize];
+                match (ok!(anchor.as_ref()), &mark.anchor) {
+                    (Anchor::Format1(base), Anchor::Format1(mark)) => {
+                        (base.x as i32 - mark.x as i32, base.y as i32 - mark.y as i32)
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            _ => unreachable!(),
+        };
+        assert_eq!(value.glyphs[1].x_offset, x - 500);
+        assert_eq!(value.glyphs[1].y_offset, y);
+
+        let mut value = buffer("e\u{301}\u{301}");
+        ok!(table.apply(&mut value, &[0, 1, 2, 3, 4], definition, &environment));
+        assert_eq!(value.glyphs[1].attachment, Some(Attachment::Mark(0)));
+        assert_eq!(value.glyphs[2].attachment, Some(Attachment::Mark(1)));
+        assert!(value.glyphs[2].y_offset > value.glyphs[1].y_offset);
+
+        let mut value = buffer("\u{301}");
+        ok!(table.apply(&mut value, &[0, 1, 2, 3, 4], definition, &environment));
+        assert_eq!(value.glyphs[0].attachment, None);
+        assert_eq!((value.glyphs[0].x_offset, value.glyphs[0].y_offset), (0, 0));
+
+        let count = 1_000_000;
+        let mut value = Buffer::new(vec![0; count]);
+        for (i, glyph) in value.glyphs.iter_mut().enumerate() {
+            glyph.y_offset = 1;
+            if i + 1 < count {
+                glyph.attachment = Some(Attachment::Cursive(i + 1));
+            }
+        }
+        ok!(table.apply(&mut value, &[], definition, &environment));
+        assert_eq!(value.glyphs[0].y_offset, count as i32);
+        assert_eq!(value.glyphs[count - 1].y_offset, 1);
+    }
+
+    #[test]
+    fn skip() {
+        use opentype::layout::{Buffer, Skip};
+        use opentype::File;
+
+        let mut tape = setup!(CrimsonText);
+        let file = ok!(File::read(&mut tape));
+        let table: GlyphPositioning = ok!(ok!(file[0].take(&mut tape)));
+        let definition: GlyphDefinition = ok!(ok!(file[0].take(&mut tape)));
+        let marks = &ok!(definition.marks.as_ref()).coverages;
+        let (base, mark, other) = (247, 665, 677);
+        assert!(marks[1].contains(mark) && !marks[1].contains(other));
+        let buffer = Buffer::new([base, mark, other, mark]);
+
+        let mut record = table.lookups.records[4].clone();
+        assert_eq!(record.mark_filtering_set, Some(1));
+        let skip = Skip::new(&record, Some(&definition));
+        assert_eq!(skip.class(&buffer.glyphs[0]), 1);
+        assert_eq!(skip.class(&buffer.glyphs[2]), 3);
+        assert!(!skip.skips(&buffer.glyphs[1]));
+        assert!(skip.skips(&buffer.glyphs[2]));
+        assert_eq!(skip.forward(&buffer.glyphs, 0).collect::<Vec<_>>(), [1, 3]);
+        assert_eq!(skip.backward(&buffer.glyphs, 3).collect::<Vec<_>>(), [1, 0]);
+
+        record.flags.0 = 0x0100;
+        assert_eq!(record.flags.mark_attachment_class(), 1);
+        let skip = Skip::new(&record, Some(&definition));
+        assert!(definition.mark_class.is_none());
+        assert_eq!(skip.forward(&buffer.glyphs, 0).count(), 0);
+        assert_eq!(skip.backward(&buffer.glyphs, 3).collect::<Vec<_>>(), [0]);
+
+        record.flags.0 = 0x0008;
+        let skip = Skip::new(&record, Some(&definition));
+        assert_eq!(skip.forward(&buffer.glyphs, 0).count(), 0);
+
+        let skip = Skip::new(&record, None);
+        assert_eq!(skip.forward(&buffer.glyphs, 0).count(), 3);
+    }
+
+    #[test]
+    fn classes() {
+        use opentype::layout::class::{Class, Class1};
+
+        let table: GlyphPositioning = ok!(Read::read(&mut setup!(CrimsonText, "GPOS")));
+        let mut classes = vec![];
+        for record in table.lookups.records.iter() {
+            for table in record.tables.iter() {
+                if let Type::PairAdjustment(PairAdjustment::Format2(table)) = table {
+                    classes.push(&table.class1);
+                    classes.push(&table.class2);
+                }
+            }
+        }
+        assert!(classes
+            .iter()
+            .all(|class| matches!(class, Class::Format2(_))));
+        let class = Class::Format1(Class1 {
+            format: 1,
+            start_glyph_id: 10,
+            glyph_count: 4,
+            indices: vec![1, 0, 2, 1],
+        });
+        assert_eq!(class.get(9), 0);
+        assert_eq!(class.get(10), 1);
+        assert_eq!(class.get(11), 0);
+        assert_eq!(class.get(14), 0);
+        assert_eq!(
+            class.iter().collect::<Vec<_>>(),
+            [(10, 1), (12, 2), (13, 1)]
+        );
+        assert_eq!(class.invert()[&1], [10, 13]);
+        let other = Class::Format1(Class1 {
+            format: 1,
+            start_glyph_id: 0xFFFE,
+            glyph_count: 4,
+            indices: vec![1, 2, 3, 4],
+        });
+        assert_eq!(other.iter().collect::<Vec<_>>(), [(0xFFFE, 1), (0xFFFF, 2)]);
+        classes.push(&class);
+        for class in classes {
+            let pairs = class.iter().collect::<Vec<_>>();
+            assert!(pairs.iter().all(|(_, index)| *index > 0));
+            for (glyph_id, index) in pairs.iter() {
+                assert_eq!(class.get(*glyph_id), *index);
+            }
+            let inverted = class.invert();
+            assert_eq!(
+                inverted
+                    .values()
+                    .map(|glyph_ids| glyph_ids.len())
+                    .sum::<usize>(),
+                pairs.len(),
+            );
+            for (index, glyph_ids) in inverted.iter() {
+                assert!(glyph_ids.windows(2).all(|pair| pair[0] < pair[1]));
+                assert!(glyph_ids
+                    .iter()
+                    .all(|glyph_id| class.get(*glyph_id) == *index));
+            }
+            assert_eq!(class.get(0xFFFF), 0);
+        }
+    }
+
     #[test]
     fn features() {
         let GlyphPositioning { features, .. } = ok!(Read::read(&mut setup!(CrimsonText, "GPOS")));
@@ -32,9 +290,158 @@ mod crimson_text {
             .collect::<Vec<_>>();
         assert_eq!(tags, tags![b"kern", b"mark", b"mkmk"]);
     }
+
+    #[test]
+    fn write() {
+        use std::io::Cursor;
+
+        use opentype::tape::Write;
+
+        let table: GlyphPositioning = ok!(Read::read(&mut setup!(CrimsonText, "GPOS")));
+        let mut data = vec![];
+        ok!(data.give(&table));
+        let other: GlyphPositioning = ok!(Read::read(&mut Cursor::new(&data)));
+        let mut other_data = vec![];
+        ok!(other_data.give(&other));
+        assert_eq!(data, other_data);
+        let types = |table: &GlyphPositioning| {
+            table
+                .lookups
+                .records
+                .iter()
+                .map(|record| (record.r#type, record.tables.len()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(types(&table), types(&other));
+        let pairs = |table: &GlyphPositioning| {
+            let mut pairs = vec![];
+            for table in table.lookups.records[0].tables.iter() {
+                if let Type::PairAdjustment(PairAdjustment::Format1(table)) = table {
+                    for records in table.records.iter() {
+                        for record in records.records.iter() {
+                            let value = record.value1.as_ref().and_then(|value| value.x_advance);
+                            pairs.push((record.glyph2_id, value));
+                        }
+                    }
+                }
+            }
+            pairs
+        };
+        assert!(!pairs(&table).is_empty());
+        assert_eq!(pairs(&table), pairs(&other));
+    }
+
+    #[test]
+    fn write_overflow() {
+        use std::io::Cursor;
+
+        use opentype::layout::coverage::{Coverage, Coverage1};
+        use opentype::tables::glyph_positioning::{Pair1, Pair1s, Single};
+        use opentype::tape::Write;
+
+        let mut table: GlyphPositioning = ok!(Read::read(&mut setup!(CrimsonText, "GPOS")));
+        let mut subtable = match &table.lookups.records[0].tables[0] {
+            Type::PairAdjustment(PairAdjustment::Format1(table)) => table.clone(),
+            _ => unreachable!(),
+        };
+        assert!(subtable.value1_flags.has_x_advance());
+        subtable.coverage = Coverage::Format1(Coverage1 {
+            format: 1,
+            glyph_count: 400,
+            glyph_ids: (0..400).map(|i| 2 * i).collect(),
+        });
+        subtable.records = (0..400)
+            .map(|i| Pair1s {
+                count: 100,
+                records: (0..100)
+                    .map(|j| Pair1 {
+                        glyph2_id: j,
+                        value1: Some(Single {
+                            x_advance: Some((i * 100 + j) as i16),
+                            ..Default::default()
+                        }),
+                        value2: None,
+                    })
+                    .collect(),
+            })
+            .collect();
+        table.lookups.records[0].tables =
+            vec![Type::PairAdjustment(PairAdjustment::Format1(subtable))];
+        let mut data = vec![];
+        ok!(data.give(&table));
+        assert!(data.len() > 2 * 0xFFFF);
+        let other: GlyphPositioning = ok!(Read::read(&mut Cursor::new(&data)));
+        assert_eq!(other.lookups.records[0].r#type, 9);
+        assert!(other.lookups.records[0].tables.len() > 1);
+        assert_eq!(other.lookups.records.len(), table.lookups.records.len());
+        let mut pairs = vec![];
+        for table in other.lookups.records[0].tables.iter() {
+            let table = match table {
+                Type::ExtensionPositioning(table) => &table.table,
+                _ => unreachable!(),
+            };
+            if let Type::PairAdjustment(PairAdjustment::Format1(table)) = &**table {
+                let glyph_ids = match &table.coverage {
+                    Coverage::Format1(coverage) => coverage.glyph_ids.clone(),
+                    _ => unreachable!(),
+                };
+                for (glyph1_id, records) in glyph_ids.iter().zip(table.records.iter()) {
+                    for record in records.records.iter() {
+                        let value = record.value1.as_ref().and_then(|value| value.x_advance);
+                        pairs.push((*glyph1_id, record.glyph2_id, value));
+                    }
+                }
+            }
+        }
+        assert_eq!(pairs.len(), 400 * 100);
+        assert!(pairs
+            .iter()
+            .all(|&(i, j, value)| value == Some((i / 2 * 100 + j) as i16)));
+        let mut other_data = vec![];
+        ok!(other_data.give(&other));
+        let another: GlyphPositioning = ok!(Read::read(&mut Cursor::new(&other_data)));
+        assert_eq!(another.lookups.records[0].r#type, 9);
+        assert_eq!(
+            another.lookups.records[0].tables.len(),
+            other.lookups.records[0].tables.len(),
+        );
+    }
+}
+
+mod gingham_extension {
+    use opentype::tables::glyph_positioning::{GlyphPositioning, PairAdjustment, Type};
+    use opentype::value::Read;
+
+    #[test]
+    fn lookups() {
+        let GlyphPositioning { lookups, .. } =
+            ok!(Read::read(&mut setup!(GinghamExtension, "GPOS")));
+        let counts = lookups
+            .records
+            .iter()
+            .map(|record| (record.r#type, record.tables.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, [(9, 1), (9, 4)]);
+        let counts = lookups
+            .records
+            .iter()
+            .flat_map(|record| record.tables.iter())
+            .map(|table| match table {
+                Type::ExtensionPositioning(table) => match &*table.table {
+                    Type::PairAdjustment(PairAdjustment::Format2(table)) => {
+                        (table.class1_count, table.class2_count)
+                    }
+                    _ => unreachable!(),
+                },
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(counts, [(2, 3), (18, 28), (15, 16), (2, 21), (1, 14)]);
+    }
 }
 
 mod source_serif {
+    use opentype::layout::feature::Parameters;
     use opentype::layout::Language;
     use opentype::layout::Script;
     use opentype::tables::glyph_positioning::{GlyphPositioning, PairAdjustment, Type};
@@ -62,6 +469,18 @@ mod source_serif {
             .map(|record| record.lookup_index_count)
             .collect::<Vec<_>>();
         assert_eq!(lookups, &[1, 1, 1, 1, 1, 0, 0, 0, 0, 0]);
+        for record in features.records[5..].iter() {
+            match record.parameters {
+                Some(Parameters::Size(ref value)) => {
+                    assert_eq!(value.design_size, 100);
+                    assert_eq!(value.subfamily_id, 0);
+                }
+                _ => unreachable!(),
+            }
+        }
+        assert!(features.records[..5]
+            .iter()
+            .all(|record| record.parameters.is_none()));
     }
 
     #[test]
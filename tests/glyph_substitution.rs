@@ -1,6 +1,127 @@
 #[macro_use]
 mod support;
 
+mod adobe_vf_prototype {
+    use opentype::layout::feature::Condition;
+    use opentype::tables::glyph_substitution::GlyphSubstitution;
+    use opentype::value::Read;
+
+    #[test]
+    fn variations() {
+        let GlyphSubstitution {
+            features,
+            variations,
+            ..
+        } = ok!(Read::read(&mut setup!(AdobeVFPrototypeTTF, "GSUB")));
+        let variations = ok!(variations);
+        assert_eq!(variations.records.len(), 1);
+        let record = &variations.records[0];
+        let conditions = ok!(record.conditions.as_ref());
+        let conditions = conditions
+            .records
+            .iter()
+            .map(|condition| match condition {
+                Condition::Format1(value) => {
+                    (value.axis_index, value.min_value.0, value.max_value.0)
+                }
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(conditions, [(1, 0, 16384), (0, 11199, 16384)]);
+        let substitutions = ok!(record.substitutions.as_ref());
+        assert_eq!(substitutions.headers[0].feature_index, 6);
+        assert_eq!(substitutions.records[0].lookup_indices, [9]);
+
+        let indices = |coordinates: &[f32]| {
+            variations
+                .substitute(&features, coordinates)
+                .iter()
+                .map(|record| record.lookup_indices.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(indices(&[0.0, 0.0])[6], Vec::<u16>::new());
+        assert_eq!(indices(&[0.5, 1.0])[6], Vec::<u16>::new());
+        assert_eq!(indices(&[0.7, 0.0])[6], [9]);
+        assert_eq!(indices(&[1.0, 0.5])[6], [9]);
+        assert_eq!(indices(&[1.0, -0.5])[6], Vec::<u16>::new());
+        assert_eq!(indices(&[1.0, 0.5])[2], [8]);
+    }
+
+    #[test]
+    fn parameters() {
+        use std::io::Cursor;
+
+        use opentype::layout::feature::{Parameters, StylisticSet};
+        use opentype::tape::Write;
+        use opentype::truetype::Tag;
+
+        let mut table: GlyphSubstitution =
+            ok!(Read::read(&mut setup!(AdobeVFPrototypeTTF, "GSUB")));
+        table.features.headers[6].tag = Tag(*b"ss01");
+        let variations = ok!(table.variations.as_mut());
+        let substitutions = ok!(variations.records[0].substitutions.as_mut());
+        substitutions.records[0].parameters = Some(Parameters::StylisticSet(StylisticSet {
+            version: 0,
+            ui_name_id: 256,
+        }));
+
+        let mut data = vec![];
+        ok!(data.give(&table));
+        let table: GlyphSubstitution = ok!(Read::read(&mut Cursor::new(&data)));
+        let variations = ok!(table.variations.as_ref());
+        let substitutions = ok!(variations.records[0].substitutions.as_ref());
+        match substitutions.records[0].parameters {
+            Some(Parameters::StylisticSet(ref value)) => assert_eq!(value.ui_name_id, 256),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn write() {
+        use std::io::Cursor;
+
+        use opentype::tape::Write;
+
+        let table: GlyphSubstitution = ok!(Read::read(&mut setup!(AdobeVFPrototypeTTF, "GSUB")));
+        let mut data = vec![];
+        ok!(data.give(&table));
+        let other: GlyphSubstitution = ok!(Read::read(&mut Cursor::new(&data)));
+        assert_eq!(other.minor_version, 1);
+        let variations = ok!(other.variations);
+        assert_eq!(
+            variations.substitute(&other.features, &[1.0, 1.0])[6].lookup_indices,
+            [9]
+        );
+    }
+}
+
+mod gingham_extension {
+    use opentype::layout::Coverage;
+    use opentype::tables::glyph_substitution::{GlyphSubstitution, SingleSubstitution, Type};
+    use opentype::value::Read;
+
+    #[test]
+    fn lookups() {
+        let GlyphSubstitution { lookups, .. } =
+            ok!(Read::read(&mut setup!(GinghamExtension, "GSUB")));
+        assert_eq!(lookups.records.len(), 1);
+        assert_eq!(lookups.records[0].r#type, 7);
+        let table = match &lookups.records[0].tables[0] {
+            Type::ExtensionSubstitution(table) => table,
+            _ => unreachable!(),
+        };
+        assert_eq!(table.r#type, 1);
+        let table = match &*table.table {
+            Type::SingleSubstitution(SingleSubstitution::Format1(table)) => table,
+            _ => unreachable!(),
+        };
+        assert_eq!(table.delta_glyph_id, 26);
+        match &table.coverage {
+            Coverage::Format1(coverage) => assert_eq!(coverage.glyph_ids, [28]),
+            _ => unreachable!(),
+        }
+    }
+}
+
 mod londrina_solid {
     use opentype::tables::glyph_substitution::GlyphSubstitution;
     use opentype::value::Read;
@@ -18,6 +139,252 @@ mod source_serif {
     use opentype::tables::glyph_substitution::{GlyphSubstitution, SingleSubstitution, Type};
     use opentype::value::Read;
 
+    #[test]
+    fn apply() {
+        use opentype::layout::coverage::{Coverage, Coverage1};
+        use opentype::layout::Buffer;
+        use opentype::tables::glyph_substitution::{MultipleSubstitution, Sequence};
+        use opentype::truetype::tables::character_mapping::{CharacterMapping, Encoding};
+        use opentype::File;
+
+        let mut tape = setup!(SourceSerifPro);
+        let file = ok!(File::read(&mut tape));
+        let mapping: CharacterMapping = ok!(ok!(file[0].take(&mut tape)));
+        let mapping = ok!(mapping
+            .encodings
+            .iter()
+            .find_map(|encoding| match encoding {
+                Encoding::Format4(encoding) => Some(encoding.mapping::<u32>()),
+                _ => None,
+            }));
+        let buffer = |text: &str| Buffer::new(text.chars().map(|code| mapping[&(code as u32)]));
+        let mut table: GlyphSubstitution = ok!(ok!(file[0].take(&mut tape)));
+        let glyphs = |buffer: &Buffer| {
+            buffer
+                .glyphs
+                .iter()
+                .map(|glyph| (glyph.glyph_id, glyph.cluster))
+                .collect::<Vec<_>>()
+        };
+
+        let mut value = buffer("office");
+        ok!(table.apply(&mut value, &[17], None, 0));
+        assert_eq!(
+            glyphs(&value),
+            [(42, 0), (33, 1), (211, 2), (30, 4), (32, 5)]
+        );
+
+        let mut value = buffer("ffl");
+        ok!(table.apply(&mut value, &[17], None, 0));
+        assert_eq!(glyphs(&value), [(33, 0), (212, 1)]);
+        assert_eq!(value.glyphs[0].class, 0);
+        assert_eq!(value.glyphs[1].class, 2);
+
+        let mut value = buffer("a 1/2");
+        ok!(table.apply(&mut value, &[4, 6, 7], None, 0));
+        assert_eq!(
+            value.glyph_ids().collect::<Vec<_>>(),
+            [28, 1, 374, 429, 361]
+        );
+
+        let mut value = buffer("fi");
+        ok!(table.apply(&mut value, &[1], None, 1));
+        assert_eq!(value.glyph_ids().collect::<Vec<_>>(), [33, 397]);
+        let mut value = buffer("fi");
+        ok!(table.apply(&mut value, &[1], None, 42));
+        assert_eq!(value.glyph_ids().collect::<Vec<_>>(), [33, 36]);
+
+        let mut value = buffer("f i");
+        value.glyphs[1].class = 3;
+        ok!(table.apply(&mut value, &[17], None, 0));
+        assert_eq!(value.len(), 3);
+        table.lookups.records[17].flags.0 |= 0b1000;
+        let mut value = buffer("f i");
+        value.glyphs[1].class = 3;
+        ok!(table.apply(&mut value, &[17], None, 0));
+        assert_eq!(glyphs(&value), [(211, 0), (1, 0)]);
+        assert_ne!(value.glyphs[0].ligature_id, 0);
+        assert_eq!(value.glyphs[1].ligature_id, value.glyphs[0].ligature_id);
+        assert_eq!(value.glyphs[1].component_index, 1);
+
+        table.lookups.records[0].tables = vec![Type::MultipleSubstitution(MultipleSubstitution {
+            format: 1,
+            coverage_offset: 0,
+            record_count: 1,
+            record_offsets: vec![0],
+            coverage: Coverage::Format1(Coverage1 {
+                format: 1,
+                glyph_count: 2,
+                glyph_ids: vec![33, 36],
+            }),
+            records: vec![
+                Sequence {
+                    glyph_count: 3,
+                    glyph_ids: vec![33, 1, 33],
+                },
+                Sequence {
+                    glyph_count: 0,
+                    glyph_ids: vec![],
+                },
+            ],
+        })];
+        let mut value = buffer("fif");
+        ok!(table.apply(&mut value, &[0], None, 0));
+        assert_eq!(
+            glyphs(&value),
+            [(33, 0), (1, 0), (33, 0), (33, 2), (1, 2), (33, 2)],
+        );
+        assert!(table.apply(&mut value, &[19], None, 0).is_err());
+    }
+
+    #[test]
+    fn limits() {
+        use opentype::layout::context::{Action, ChainedContext3};
+        use opentype::layout::coverage::{Coverage, Coverage1};
+        use opentype::layout::{Buffer, ChainedContext};
+        use opentype::tables::glyph_substitution::{MultipleSubstitution, Sequence};
+
+        let mut table: GlyphSubstitution = ok!(Read::read(&mut setup!(SourceSerifPro, "GSUB")));
+        let coverage = || {
+            Coverage::Format1(Coverage1 {
+                format: 1,
+                glyph_count: 1,
+                glyph_ids: vec![33],
+            })
+        };
+        let context = |lookup_index| {
+            Type::ChainedContextualSubstitution(ChainedContext::Format3(ChainedContext3 {
+                format: 3,
+                backward_glyph_count: 0,
+                backward_coverage_offsets: vec![],
+                glyph_count: 1,
+                coverage_offsets: vec![0],
+                forward_glyph_count: 0,
+                forward_coverage_offsets: vec![],
+                action_count: 2,
+                actions: vec![
+                    Action {
+                        position_index: 0,
+                        lookup_index,
+                    },
+                    Action {
+                        position_index: 0,
+                        lookup_index,
+                    },
+                ],
+                backward_coverages: vec![],
+                coverages: vec![coverage()],
+                forward_coverages: vec![],
+            }))
+        };
+
+        table.lookups.records[0].tables = vec![context(0)];
+        let mut buffer = Buffer::new([33, 33]);
+        assert!(table.apply(&mut buffer, &[0], None, 0).is_err());
+
+        for i in 0..18 {
+            table.lookups.records[i].tables = vec![context(i as u16 + 1)];
+        }
+        let mut buffer = Buffer::new([33, 33]);
+        let error = table.apply(&mut buffer, &[0], None, 0).unwrap_err();
+        assert!(error.to_string().contains("operations"));
+
+        table.lookups.records[0].tables = vec![Type::MultipleSubstitution(MultipleSubstitution {
+            format: 1,
+            coverage_offset: 0,
+            record_count: 1,
+            record_offsets: vec![0],
+            coverage: coverage(),
+            records: vec![Sequence {
+                glyph_count: 2,
+                glyph_ids: vec![33, 33],
+            }],
+        })];
+        let mut buffer = Buffer::new([33, 33]);
+        let error = table.apply(&mut buffer, &[0; 16], None, 0).unwrap_err();
+        assert!(error.to_string().contains("glyphs"));
+    }
+
+    #[test]
+    fn coverages() {
+        use opentype::layout::coverage::{Coverage1, Coverage2, Record};
+        use opentype::layout::Coverage;
+
+        let table: GlyphSubstitution = ok!(Read::read(&mut setup!(SourceSerifPro, "GSUB")));
+        let mut coverages = vec![];
+        for record in table.lookups.records.iter() {
+            for table in record.tables.iter() {
+                match table {
+                    Type::SingleSubstitution(SingleSubstitution::Format1(table)) => {
+                        coverages.push(&table.coverage);
+                    }
+                    Type::SingleSubstitution(SingleSubstitution::Format2(table)) => {
+                        coverages.push(&table.coverage);
+                    }
+                    Type::LigatureSubstitution(table) => coverages.push(&table.coverage),
+                    _ => {}
+                }
+            }
+        }
+        assert!(coverages
+            .iter()
+            .any(|coverage| matches!(coverage, Coverage::Format1(_))));
+        assert!(coverages
+            .iter()
+            .any(|coverage| matches!(coverage, Coverage::Format2(_))));
+        for coverage in coverages.iter() {
+            let pairs = coverage.iter().collect::<Vec<_>>();
+            assert_eq!(pairs.len(), coverage.len());
+            for (i, (glyph_id, index)) in pairs.iter().enumerate() {
+                assert_eq!(*index as usize, i);
+                assert_eq!(coverage.index(*glyph_id), Some(*index));
+            }
+            assert_eq!(coverage.index(0xFFFF), None);
+        }
+
+        let (first, second) = (coverages[0], coverages[1]);
+        let union = first.union(second);
+        let intersection = first.intersection(second);
+        for glyph_id in 0..2000 {
+            let (left, right) = (first.contains(glyph_id), second.contains(glyph_id));
+            assert_eq!(union.contains(glyph_id), left || right);
+            assert_eq!(intersection.contains(glyph_id), left && right);
+        }
+        assert_eq!(union.len() + intersection.len(), first.len() + second.len());
+        let glyph_ids = union
+            .iter()
+            .map(|(glyph_id, _)| glyph_id)
+            .collect::<Vec<_>>();
+        assert!(glyph_ids.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let first = Coverage::Format1(Coverage1 {
+            format: 1,
+            glyph_count: 2,
+            glyph_ids: vec![0xFFFD, 0xFFFF],
+        });
+        let second = Coverage::Format2(Coverage2 {
+            format: 2,
+            record_count: 2,
+            records: vec![
+                Record {
+                    start_glyph_id: 10,
+                    end_glyph_id: 5,
+                    index: 0,
+                },
+                Record {
+                    start_glyph_id: 0xFFFE,
+                    end_glyph_id: 0xFFFF,
+                    index: 0,
+                },
+            ],
+        });
+        assert_eq!(second.len(), 2);
+        let union = first.union(&second);
+        assert_eq!(union.len(), 3);
+        assert!(union.contains(0xFFFF));
+        assert_eq!(first.intersection(&second).len(), 1);
+    }
+
     #[test]
     fn features() {
         let GlyphSubstitution { features, .. } =
@@ -78,6 +445,33 @@ mod source_serif {
         );
     }
 
+    #[test]
+    fn lookup_indices() {
+        use opentype::layout::Feature;
+
+        let mut table: GlyphSubstitution = ok!(Read::read(&mut setup!(SourceSerifPro, "GSUB")));
+        let features = [
+            Feature::StandardLigatures,
+            Feature::Fractions,
+            Feature::LocalizedForms,
+        ];
+        let indices = table.lookup_indices(Script::Latin, None, features);
+        assert_eq!(indices, [4, 6, 7, 17]);
+        let indices = table.lookup_indices(Script::Latin, Some(Language::Turkish), features);
+        assert_eq!(indices, [2, 4, 6, 7, 17]);
+        let indices = table.lookup_indices(Script::Latin, Some(Language::Romanian), features);
+        assert_eq!(indices, [4, 6, 7, 17]);
+        let indices = table.lookup_indices(Script::Greek, Some(Language::Turkish), features);
+        assert_eq!(indices, [4, 6, 7, 17]);
+        let indices = table.lookup_indices(Script::Latin, None, [Feature::Kerning]);
+        assert!(indices.is_empty());
+
+        let record = ok!(table.scripts.records[1].default_language.as_mut());
+        record.required_feature_index = 30;
+        let indices = table.lookup_indices(Script::Latin, None, [Feature::StandardLigatures]);
+        assert_eq!(indices, [2, 17]);
+    }
+
     #[test]
     fn lookups() {
         let GlyphSubstitution { lookups, .. } =
@@ -113,6 +507,61 @@ mod source_serif {
         }
     }
 
+    #[test]
+    fn parameters() {
+        use std::io::Cursor;
+
+        use opentype::layout::feature::{CharacterVariant, Parameters, StylisticSet};
+        use opentype::tape::Write;
+        use opentype::truetype::Tag;
+
+        let mut table: GlyphSubstitution = ok!(Read::read(&mut setup!(SourceSerifPro, "GSUB")));
+        assert!(table
+            .features
+            .records
+            .iter()
+            .all(|record| record.parameters.is_none()));
+        table.features.headers[0].tag = Tag(*b"ss01");
+        table.features.records[0].parameters = Some(Parameters::StylisticSet(StylisticSet {
+            version: 0,
+            ui_name_id: 256,
+        }));
+        table.features.headers[5].tag = Tag(*b"cv01");
+        table.features.records[5].parameters =
+            Some(Parameters::CharacterVariant(CharacterVariant {
+                label_name_id: 257,
+                tooltip_name_id: 258,
+                sample_name_id: 259,
+                parameter_count: 2,
+                first_parameter_name_id: 260,
+                character_count: 2,
+                characters: vec![0x61, 0x1F600],
+                ..Default::default()
+            }));
+        table.features.headers[10].tag = Tag(*b"liga");
+        table.features.records[10].parameters = table.features.records[0].parameters.clone();
+
+        let mut data = vec![];
+        ok!(data.give(&table));
+        let table: GlyphSubstitution = ok!(Read::read(&mut Cursor::new(&data)));
+        match table.features.records[0].parameters {
+            Some(Parameters::StylisticSet(ref value)) => assert_eq!(value.ui_name_id, 256),
+            _ => unreachable!(),
+        }
+        match table.features.records[5].parameters {
+            Some(Parameters::CharacterVariant(ref value)) => {
+                assert_eq!(value.label_name_id, 257);
+                assert_eq!(value.tooltip_name_id, 258);
+                assert_eq!(value.sample_name_id, 259);
+                assert_eq!(value.parameter_name_ids().collect::<Vec<_>>(), [260, 261]);
+                assert_eq!(value.characters, [0x61, 0x1F600]);
+            }
+            _ => unreachable!(),
+        }
+        assert!(table.features.records[10].parameter_offset > 0);
+        assert!(table.features.records[10].parameters.is_none());
+    }
+
     #[test]
     fn scripts() {
         let GlyphSubstitution { scripts, .. } =
@@ -144,4 +593,34 @@ mod source_serif {
         assert_eq!(record.language_count, 3);
         assert!(record.get(Language::Turkish).is_some());
     }
+
+    #[test]
+    fn write() {
+        use std::io::Cursor;
+
+        use opentype::tape::Write;
+
+        let table: GlyphSubstitution = ok!(Read::read(&mut setup!(SourceSerifPro, "GSUB")));
+        let mut data = vec![];
+        ok!(data.give(&table));
+        let other: GlyphSubstitution = ok!(Read::read(&mut Cursor::new(&data)));
+        let mut other_data = vec![];
+        ok!(other_data.give(&other));
+        assert_eq!(data, other_data);
+        let tags = |table: &GlyphSubstitution| {
+            let scripts = table.scripts.headers.iter().map(|header| header.tag);
+            let features = table.features.headers.iter().map(|header| header.tag);
+            scripts.chain(features).collect::<Vec<_>>()
+        };
+        assert_eq!(tags(&table), tags(&other));
+        assert_eq!(other.lookups.records.len(), 19);
+        match &other.lookups.records[17].tables[0] {
+            Type::LigatureSubstitution(table) => {
+                let table = &table.records[0];
+                assert_eq!(table.count, 3);
+                assert_eq!(table.records[0].glyph_count, 2);
+            }
+            _ => unreachable!(),
+        }
+    }
 }
@@ -0,0 +1,101 @@
+#[macro_use]
+mod support;
+
+use std::io::Cursor;
+
+use opentype::tables::glyph_variations::Points;
+use opentype::tables::{GlyphVariations, HorizontalVariations};
+use opentype::truetype::tables::{
+    FontHeader, GlyphData, GlyphMapping, HorizontalHeader, HorizontalMetrics, MaximumProfile,
+};
+use opentype::value::Read;
+use opentype::File;
+
+#[test]
+fn apply() {
+    let mut tape = setup!(AdobeVFPrototypeTTF);
+    let file = ok!(File::read(&mut tape));
+    let table = ok!(ok!(file[0].take::<_, GlyphVariations>(&mut tape)));
+    let variations = ok!(ok!(file[0].take::<_, HorizontalVariations>(&mut tape)));
+    let font_header = ok!(ok!(file[0].take::<_, FontHeader>(&mut tape)));
+    let header = ok!(ok!(file[0].take::<_, HorizontalHeader>(&mut tape)));
+    let profile = ok!(ok!(file[0].take::<_, MaximumProfile>(&mut tape)));
+    let metrics = ok!(ok!(
+        file[0].take_given::<_, HorizontalMetrics>(&mut tape, (&header, &profile))
+    ));
+    let mapping = ok!(ok!(
+        file[0].take_given::<_, GlyphMapping>(&mut tape, (&font_header, &profile))
+    ));
+    let data = ok!(ok!(file[0].take_given::<_, GlyphData>(&mut tape, &mapping)));
+    let outline = ok!(table.apply(&data, &metrics, 3, &[0.0, 0.0]));
+    assert_eq!(outline.points.len(), 38);
+    assert_eq!(outline.points[0], (90.0, 737.0));
+    assert_eq!(outline.advance_width(), 350.0);
+    assert_eq!(outline.left_side_bearing(), 40.0);
+    let outline = ok!(table.apply(&data, &metrics, 3, &[1.0, 0.0]));
+    assert_eq!(outline.points.len(), 38);
+    assert_eq!(outline.points[0], (128.0, 726.0));
+    assert_eq!(outline.advance_width(), 484.0);
+    let outline = ok!(table.apply(&data, &metrics, 3, &[0.5, 0.5]));
+    assert_eq!(outline.points[0], (109.0, 731.5));
+    for coordinates in [[1.0, 0.0], [-1.0, 0.0], [0.0, 1.0], [1.0, 1.0]] {
+        for glyph_id in 0..table.glyph_count {
+            let outline = ok!(table.apply(&data, &metrics, glyph_id, &coordinates));
+            assert_eq!(
+                outline.advance_width(),
+                variations.get(&metrics, glyph_id, &coordinates).0,
+            );
+        }
+    }
+}
+
+#[test]
+fn deltas() {
+    #[rustfmt::skip]
+    let data: Vec<u8> = vec![
+        0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 24, 0, 1, 0, 0, 0, 0, 0, 24,
+        0, 0, 0, 9,
+        0, 1, 0, 10, 0, 8, 0xA0, 0, 0x40, 0,
+        2, 1, 0, 2, 1, 10, 20, 0x81,
+    ];
+    let table: GlyphVariations = ok!(Read::read(&mut Cursor::new(data)));
+    assert_eq!(table.axis_count, 1);
+    assert!(table.shared_tuples.is_empty());
+    let record = ok!(table.records[0].as_ref());
+    assert!(!record.has_shared_points());
+    assert_eq!(record.variation_count(), 1);
+    assert!(record.headers[0].has_embedded_peak());
+    assert!(record.headers[0].has_private_points());
+    assert_eq!(record.deltas[0].points, Points::Some(vec![0, 2]));
+    assert_eq!(record.deltas[0].x, vec![10, 20]);
+    assert_eq!(record.deltas[0].y, vec![0, 0]);
+    let points = [
+        (0.0, 0.0),
+        (100.0, 0.0),
+        (100.0, 100.0),
+        (0.0, 100.0),
+        (50.0, 50.0),
+        (0.0, 0.0),
+        (100.0, 0.0),
+        (0.0, 0.0),
+        (0.0, 0.0),
+    ];
+    assert_eq!(
+        table.deltas(0, &points, &[3, 4], &[0.5]),
+        vec![
+            (5.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 0.0),
+            (5.0, 0.0),
+            (0.0, 0.0),
+            (0.0, 0.0),
+            (0.0, 0.0),
+            (0.0, 0.0),
+            (0.0, 0.0),
+        ],
+    );
+    assert_eq!(
+        table.deltas(0, &points, &[3, 4], &[-0.5]),
+        vec![(0.0, 0.0); 9]
+    );
+}
@@ -0,0 +1,39 @@
+#[macro_use]
+mod support;
+
+use opentype::tables::HorizontalVariations;
+use opentype::truetype::tables::{HorizontalHeader, HorizontalMetrics, MaximumProfile};
+use opentype::value::Read;
+use opentype::File;
+
+#[test]
+fn table() {
+    let table: HorizontalVariations = ok!(Read::read(&mut setup!(AdobeVFPrototypeTTF, "HVAR")));
+    assert_eq!(table.store.count, 4);
+    assert!(table.left_side_bearing_mapping.is_none());
+    assert!(table.right_side_bearing_mapping.is_none());
+    let mapping = ok!(table.advance_width_mapping.as_ref());
+    assert_eq!(mapping.count, 312);
+    assert_eq!(mapping.get(0), Some((0, 0)));
+    assert_eq!(mapping.get(1), Some((2, 97)));
+    assert_eq!(table.advance_width_delta(2, &[1.0, 0.0]), 84.0);
+    assert_eq!(table.advance_width_delta(2, &[-1.0, 0.0]), -37.0);
+    assert_eq!(table.left_side_bearing_delta(2, &[1.0, 0.0]), None);
+}
+
+#[test]
+fn get() {
+    let mut tape = setup!(AdobeVFPrototypeTTF);
+    let file = ok!(File::read(&mut tape));
+    let table = ok!(ok!(file[0].take::<_, HorizontalVariations>(&mut tape)));
+    let header = ok!(ok!(file[0].take::<_, HorizontalHeader>(&mut tape)));
+    let profile = ok!(ok!(file[0].take::<_, MaximumProfile>(&mut tape)));
+    let metrics = ok!(ok!(
+        file[0].take_given::<_, HorizontalMetrics>(&mut tape, (&header, &profile))
+    ));
+    assert_eq!(metrics.get(3), (350, 40));
+    assert_eq!(table.get(&metrics, 3, &[]), (350.0, 40.0));
+    assert_eq!(table.get(&metrics, 3, &[1.0, 0.0]), (484.0, 40.0));
+    assert_eq!(table.get(&metrics, 3, &[-1.0, 0.0]), (290.0, 40.0));
+    assert_eq!(table.get(&metrics, 3, &[0.0, 1.0]), (349.0, 40.0));
+}
@@ -0,0 +1,253 @@
+#[macro_use]
+mod support;
+
+use std::io::Cursor;
+
+use opentype::tables::compact2::Segment;
+use opentype::tables::glyph_definition::{GlyphDefinition, Header};
+use opentype::tables::glyph_positioning::{GlyphPositioning, PairAdjustment, Type};
+use opentype::tables::{
+    Compact2, GlyphSubstitution, GlyphVariations, HorizontalVariations, MetricVariations,
+};
+use opentype::truetype::tables::glyph_data::Description;
+use opentype::truetype::tables::{
+    FontHeader, GlyphData, GlyphMapping, HorizontalHeader, HorizontalMetrics, MaximumProfile,
+    WindowsMetrics,
+};
+use opentype::truetype::Tag;
+use opentype::{File, Font, Instancer};
+
+#[test]
+fn instantiate() {
+    let mut tape = setup!(AdobeVFPrototypeTTF);
+    let file = ok!(File::read(&mut tape));
+    let instancer = ok!(Instancer::new(
+        &file[0],
+        &mut tape,
+        [(Tag(*b"wght"), 900.0)]
+    ));
+    assert_eq!(instancer.coordinates(), [1.0, 0.0]);
+    let data = ok!(ok!(instancer.instantiate(&file[0], &mut tape)).build());
+
+    let mut tape = Cursor::new(data);
+    let file = ok!(File::read(&mut tape));
+    let tags = file[0]
+        .offsets
+        .records
+        .iter()
+        .map(|record| record.tag)
+        .collect::<Vec<_>>();
+    for tag in [b"HVAR", b"MVAR", b"avar", b"fvar", b"gvar"] {
+        assert!(!tags.contains(&Tag(*tag)));
+    }
+    for record in file[0].offsets.records.iter() {
+        assert_eq!(record.checksum, ok!(record.checksum(&mut tape)));
+    }
+
+    let table = ok!(ok!(file[0].take::<_, GlyphSubstitution>(&mut tape)));
+    assert!(table.variations.is_none());
+    assert_eq!(table.minor_version, 0);
+    assert_eq!(ok!(table.features.get(Tag(*b"rvrn"))).lookup_indices, [9]);
+
+    let table = ok!(ok!(file[0].take::<_, GlyphPositioning>(&mut tape)));
+    let tables = &table.lookups.records[0].tables;
+    match &tables[0] {
+        Type::PairAdjustment(PairAdjustment::Format1(table)) => {
+            assert_eq!(table.value1_flags.0, 0x0004);
+            let value = ok!(table.records[0].records[0].value1.as_ref());
+            assert_eq!(value.x_advance, Some(0));
+            assert!(value.x_advance_correction.is_none());
+        }
+        _ => unreachable!(),
+    }
+    match &tables[1] {
+        Type::PairAdjustment(PairAdjustment::Format2(table)) => {
+            let value = ok!(table.records[1].records[1].value1.as_ref());
+            assert_eq!(value.x_advance, Some(-10));
+            assert!(value.x_advance_correction.is_none());
+        }
+        _ => unreachable!(),
+    }
+
+    let table = ok!(ok!(file[0].take::<_, GlyphDefinition>(&mut tape)));
+    match &table.header {
+        &Header::Version12(..) => {}
+        _ => unreachable!(),
+    }
+    assert!(table.variations.is_none());
+}
+
+#[test]
+fn outlines() {
+    let mut tape = setup!(AdobeVFPrototypeTTF);
+    let file = ok!(File::read(&mut tape));
+    let instancer = ok!(Instancer::new(
+        &file[0],
+        &mut tape,
+        [(Tag(*b"wght"), 900.0)]
+    ));
+    let coordinates = instancer.coordinates();
+    let glyph_variations = ok!(ok!(file[0].take::<_, GlyphVariations>(&mut tape)));
+    let metric_variations = ok!(ok!(file[0].take::<_, MetricVariations>(&mut tape)));
+    let (glyph_data, metrics) = read(&file[0], &mut tape);
+    let windows_metrics = ok!(ok!(file[0].take::<_, WindowsMetrics>(&mut tape)));
+    let data = ok!(ok!(instancer.instantiate(&file[0], &mut tape)).build());
+
+    let mut other_tape = Cursor::new(data);
+    let other_file = ok!(File::read(&mut other_tape));
+    let (other_glyph_data, other_metrics) = read(&other_file[0], &mut other_tape);
+    let mut count = 0;
+    for glyph_id in 0..glyph_data.len() {
+        let outline =
+            ok!(glyph_variations.apply(&glyph_data, &metrics, glyph_id as u16, coordinates));
+        let (advance_width, _) = other_metrics.get(glyph_id as u16);
+        assert_eq!(advance_width, outline.advance_width().round() as u16);
+        let glyph = match &other_glyph_data[glyph_id] {
+            Some(glyph) => glyph,
+            _ => continue,
+        };
+        if let Description::Simple(description) = &glyph.description {
+            let (mut x, mut y) = (0, 0);
+            let points = description
+                .x
+                .iter()
+                .zip(description.y.iter())
+                .map(|(dx, dy)| {
+                    x += *dx as i32;
+                    y += *dy as i32;
+                    (x, y)
+                })
+                .collect::<Vec<_>>();
+            let expected = outline
+                .points
+                .iter()
+                .map(|(x, y)| (x.round() as i32, y.round() as i32))
+                .collect::<Vec<_>>();
+            assert_eq!(points, expected);
+            count += 1;
+        }
+    }
+    assert!(count > 0);
+
+    let other_windows_metrics = ok!(ok!(other_file[0].take::<_, WindowsMetrics>(&mut other_tape)));
+    let delta = metric_variations.delta(Tag(*b"xhgt"), coordinates);
+    let (value, other_value, average_width) = match (&windows_metrics, &other_windows_metrics) {
+        (WindowsMetrics::Version3(value), WindowsMetrics::Version3(other_value)) => (
+            value.x_height,
+            other_value.x_height,
+            other_value.average_char_width,
+        ),
+        (WindowsMetrics::Version4(value), WindowsMetrics::Version4(other_value)) => (
+            value.x_height,
+            other_value.x_height,
+            other_value.average_char_width,
+        ),
+        _ => unreachable!(),
+    };
+    assert_eq!(
+        other_value,
+        (value as f32 + delta.unwrap_or(0.0)).round() as i16
+    );
+    let widths = (0..glyph_data.len())
+        .map(|glyph_id| other_metrics.get(glyph_id as u16).0 as f32)
+        .filter(|width| *width > 0.0)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        average_width,
+        (widths.iter().sum::<f32>() / widths.len() as f32).round() as i16
+    );
+}
+
+#[test]
+fn compact() {
+    let mut tape = setup!(AdobeVFPrototypeCFF);
+    let file = ok!(File::read(&mut tape));
+    let instancer = ok!(Instancer::new(
+        &file[0],
+        &mut tape,
+        [(Tag(*b"wght"), 900.0)]
+    ));
+    let coordinates = instancer.coordinates();
+    let table = ok!(ok!(file[0].take::<_, Compact2>(&mut tape)));
+    let horizontal_variations = ok!(ok!(file[0].take::<_, HorizontalVariations>(&mut tape)));
+    let maximum_profile = ok!(ok!(file[0].take::<_, MaximumProfile>(&mut tape)));
+    let horizontal_header = ok!(ok!(file[0].take::<_, HorizontalHeader>(&mut tape)));
+    let metrics = ok!(ok!(file[0].take_given::<_, HorizontalMetrics>(
+        &mut tape,
+        (&horizontal_header, &maximum_profile)
+    )));
+    let data = ok!(ok!(instancer.instantiate(&file[0], &mut tape)).build());
+
+    let mut other_tape = Cursor::new(data);
+    let other_file = ok!(File::read(&mut other_tape));
+    for record in other_file[0].offsets.records.iter() {
+        assert!(record.tag != Tag(*b"HVAR"));
+        assert_eq!(record.checksum, ok!(record.checksum(&mut other_tape)));
+    }
+    let other_table = ok!(ok!(other_file[0].take::<_, Compact2>(&mut other_tape)));
+    assert!(other_table.store.is_none());
+    let other_maximum_profile = ok!(ok!(other_file[0].take::<_, MaximumProfile>(&mut other_tape)));
+    let other_horizontal_header = ok!(ok!(
+        other_file[0].take::<_, HorizontalHeader>(&mut other_tape)
+    ));
+    let other_metrics = ok!(ok!(other_file[0].take_given::<_, HorizontalMetrics>(
+        &mut other_tape,
+        (&other_horizontal_header, &other_maximum_profile)
+    )));
+    let round = |(x, y): (f32, f32)| (x.round(), y.round());
+    for glyph_id in 0..table.glyph_count() as u16 {
+        let outline = ok!(table.outline(glyph_id, coordinates));
+        let other_outline = ok!(other_table.outline(glyph_id, &[]));
+        assert_eq!(outline.contours.len(), other_outline.contours.len());
+        for (contour, other_contour) in outline.contours.iter().zip(&other_outline.contours) {
+            assert_eq!(round(contour.start), other_contour.start);
+            let segments = contour
+                .segments
+                .iter()
+                .map(|segment| match *segment {
+                    Segment::Linear(a) => Segment::Linear(round(a)),
+                    Segment::Cubic(a, b, c) => Segment::Cubic(round(a), round(b), round(c)),
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(segments, other_contour.segments);
+        }
+        let (advance_width, _) = horizontal_variations.get(&metrics, glyph_id, coordinates);
+        assert_eq!(other_metrics.get(glyph_id).0, advance_width.round() as u16);
+    }
+}
+
+#[test]
+fn default() {
+    let mut tape = setup!(AdobeVFPrototypeTTF);
+    let file = ok!(File::read(&mut tape));
+    let instancer = Instancer::with_coordinates(vec![]);
+    let data = ok!(ok!(instancer.instantiate(&file[0], &mut tape)).build());
+
+    let mut tape = Cursor::new(data);
+    let file = ok!(File::read(&mut tape));
+    let table = ok!(ok!(file[0].take::<_, GlyphSubstitution>(&mut tape)));
+    assert_ne!(ok!(table.features.get(Tag(*b"rvrn"))).lookup_indices, [9]);
+    let table = ok!(ok!(file[0].take::<_, GlyphPositioning>(&mut tape)));
+    match &table.lookups.records[0].tables[0] {
+        Type::PairAdjustment(PairAdjustment::Format1(table)) => {
+            let value = ok!(table.records[0].records[0].value1.as_ref());
+            assert_eq!(value.x_advance, Some(-20));
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn read<T: opentype::tape::Read>(font: &Font, tape: &mut T) -> (GlyphData, HorizontalMetrics) {
+    let font_header = ok!(ok!(font.take::<_, FontHeader>(tape)));
+    let maximum_profile = ok!(ok!(font.take::<_, MaximumProfile>(tape)));
+    let horizontal_header = ok!(ok!(font.take::<_, HorizontalHeader>(tape)));
+    let metrics = ok!(ok!(font.take_given::<_, HorizontalMetrics>(
+        tape,
+        (&horizontal_header, &maximum_profile)
+    )));
+    let mapping = ok!(ok!(
+        font.take_given::<_, GlyphMapping>(tape, (&font_header, &maximum_profile))
+    ));
+    let glyph_data = ok!(ok!(font.take_given::<_, GlyphData>(tape, &mapping)));
+    (glyph_data, metrics)
+}
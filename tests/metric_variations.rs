@@ -0,0 +1,46 @@
+#[macro_use]
+mod support;
+
+use std::io::Cursor;
+
+use opentype::tables::MetricVariations;
+use opentype::truetype::Tag;
+use opentype::value::Read;
+
+#[test]
+fn table() {
+    let table: MetricVariations = ok!(Read::read(&mut setup!(AdobeVFPrototypeTTF, "MVAR")));
+    assert_eq!(table.record_size, 8);
+    let tags = table
+        .records
+        .iter()
+        .map(|record| record.tag)
+        .collect::<Vec<_>>();
+    assert_eq!(tags, tags![b"stro", b"xhgt"]);
+    assert_eq!(table.delta(Tag(*b"xhgt"), &[]), Some(0.0));
+    assert_eq!(table.delta(Tag(*b"xhgt"), &[1.0, 0.0]), Some(13.0));
+    assert_eq!(table.delta(Tag(*b"stro"), &[-1.0, 1.0]), Some(-2.0));
+    assert_eq!(table.delta(Tag(*b"hasc"), &[1.0, 0.0]), None);
+    assert_eq!(
+        table.deltas(tags![b"hasc", b"stro", b"xhgt"], &[1.0, 0.0]),
+        [0.0, 8.0, 13.0],
+    );
+
+    let table: MetricVariations = ok!(Read::read(&mut setup!(AdobeVFPrototypeCFF, "MVAR")));
+    assert_eq!(table.delta(Tag(*b"stro"), &[-1.0, 1.0]), Some(1.0));
+}
+
+#[test]
+fn stride() {
+    #[rustfmt::skip]
+    let mut data: Vec<u8> = vec![
+        0, 1, 0, 0, 0, 0, 0, 10, 0, 2, 0, 32,
+        b'c', b'p', b'h', b't', 0, 0, 0, 1, 0xFF, 0xFF,
+        b'h', b'a', b's', b'c', 0, 0, 0, 0, 0xFF, 0xFF,
+    ];
+    data.extend(support::store(&[10, -20]));
+    let table: MetricVariations = ok!(Read::read(&mut Cursor::new(data)));
+    assert_eq!(table.records.len(), 2);
+    assert_eq!(table.delta(Tag(*b"hasc"), &[0.5]), Some(5.0));
+    assert_eq!(table.delta(Tag(*b"cpht"), &[1.0]), Some(-20.0));
+}
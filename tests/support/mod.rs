@@ -24,6 +24,7 @@ pub enum Fixture {
     AdobeVFPrototypeCFF,
     AdobeVFPrototypeTTF,
     CrimsonText,
+    GinghamExtension,
     KaushanScript,
     LondrinaSolid,
     NotoColorEmoji,
@@ -37,6 +38,7 @@ impl Fixture {
             Fixture::AdobeVFPrototypeCFF => "AdobeVFPrototype.otf",
             Fixture::AdobeVFPrototypeTTF => "AdobeVFPrototype.ttf",
             Fixture::CrimsonText => "CrimsonText-Regular.ttf",
+            Fixture::GinghamExtension => "Gingham-Extension.ttf",
             Fixture::KaushanScript => "KaushanScript-Regular.ttf",
             Fixture::LondrinaSolid => "LondrinaSolid-Regular.ttf",
             Fixture::NotoColorEmoji => "NotoColorEmoji-Regular.ttf",
@@ -49,17 +51,30 @@ impl Fixture {
     pub fn offset(&self, table: &str) -> u64 {
         match *self {
             Fixture::AdobeVFPrototypeCFF => match table {
+                "CFF2" => 7376,
+                "MVAR" => 120280,
                 _ => unreachable!(),
             },
             Fixture::AdobeVFPrototypeTTF => match table {
+                "GDEF" => 183148,
                 "GPOS" => 137912,
+                "GSUB" => 136752,
+                "HVAR" => 135728,
+                "MVAR" => 135608,
+                "avar" => 183640,
                 "fvar" => 41556,
+                "gvar" => 41960,
                 _ => unreachable!(),
             },
             Fixture::CrimsonText => match table {
                 "GPOS" => 94952,
                 _ => unreachable!(),
             },
+            Fixture::GinghamExtension => match table {
+                "GPOS" => 316,
+                "GSUB" => 2824,
+                _ => unreachable!(),
+            },
             Fixture::KaushanScript => match table {
                 _ => unreachable!(),
             },
@@ -91,3 +106,19 @@ pub fn setup(fixture: Fixture, table: Option<&str>) -> File {
     )));
     file
 }
+
+/// Return an item variation store with a record of one delta per item and a region peaking at the
+/// maximum of the first axis.
+pub fn store(deltas: &[i16]) -> Vec<u8> {
+    let region_offset = 12 + 8 + 2 * deltas.len() as u32;
+    let mut data = vec![0, 1];
+    data.extend(region_offset.to_be_bytes());
+    data.extend([0, 1, 0, 0, 0, 12]);
+    data.extend((deltas.len() as u16).to_be_bytes());
+    data.extend([0, 1, 0, 1, 0, 0]);
+    for delta in deltas {
+        data.extend(delta.to_be_bytes());
+    }
+    data.extend([0, 1, 0, 1, 0, 0, 0x40, 0, 0x40, 0]);
+    data
+}
@@ -0,0 +1,31 @@
+#[macro_use]
+mod support;
+
+use std::io::Cursor;
+
+use opentype::tables::VerticalVariations;
+use opentype::value::Read;
+
+#[test]
+fn get() {
+    let store = support::store(&[10, -20]);
+    let mut data: Vec<u8> = vec![0, 1, 0, 0, 0, 0, 0, 24, 0, 0, 0, 0];
+    data.extend((24 + store.len() as u32).to_be_bytes());
+    data.extend([0; 8]);
+    data.extend(store);
+    data.extend([0, 0, 0, 2, 1, 0]);
+    let table: VerticalVariations = ok!(Read::read(&mut Cursor::new(data)));
+    assert!(table.advance_height_mapping.is_none());
+    assert!(table.bottom_side_bearing_mapping.is_none());
+    assert!(table.vertical_origin_mapping.is_none());
+    assert_eq!(table.advance_height_delta(0, &[1.0]), 10.0);
+    assert_eq!(table.advance_height_delta(0, &[0.5]), 5.0);
+    assert_eq!(table.advance_height_delta(1, &[1.0]), -20.0);
+    assert_eq!(table.advance_height_delta(2, &[1.0]), 0.0);
+    assert_eq!(table.top_side_bearing_delta(0, &[1.0]), Some(-20.0));
+    assert_eq!(table.top_side_bearing_delta(1, &[1.0]), Some(10.0));
+    assert_eq!(table.top_side_bearing_delta(5, &[1.0]), Some(10.0));
+    assert_eq!(table.bottom_side_bearing_delta(0, &[1.0]), None);
+    assert_eq!(table.vertical_origin_delta(0, &[1.0]), None);
+    assert_eq!(table.get((1000, 50), 0, &[0.5]), (1005.0, 40.0));
+}
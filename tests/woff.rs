@@ -0,0 +1,115 @@
+#[macro_use]
+mod support;
+
+use std::io::{Cursor, Read, Write};
+
+use opentype::tape::Write as _;
+use opentype::truetype::tables::offsets::Offsets;
+use opentype::value::Read as _;
+use opentype::woff;
+use opentype::File;
+
+const METADATA: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><metadata version=\"1.0\"/>";
+const PRIVATE: &[u8] = b"private";
+
+#[test]
+fn file() {
+    let mut tape = Cursor::new(encode());
+    let file: woff::File = ok!(woff::File::read(&mut tape));
+    assert_eq!(file.header.table_count, 19);
+    assert_eq!(file.header.flavor, 0x00010000);
+    assert!(file
+        .records
+        .iter()
+        .zip(&file.tables)
+        .all(|(record, table)| record.original_size as usize == table.len()));
+    assert_eq!(ok!(file.metadata.as_ref()), METADATA);
+    assert_eq!(ok!(file.private.as_ref()), PRIVATE);
+}
+
+#[test]
+fn font() {
+    use opentype::tables::GlyphDefinition;
+    use opentype::truetype::tables::{FontHeader, GlyphData, GlyphMapping, MaximumProfile};
+
+    let mut tape = Cursor::new(encode());
+    let file = ok!(File::read(&mut tape));
+    assert_eq!(file.len(), 1);
+    let font_header = ok!(ok!(file[0].take::<_, FontHeader>(&mut tape)));
+    assert_eq!(font_header.units_per_em, 2048);
+    let maximum_profile = ok!(ok!(file[0].take::<_, MaximumProfile>(&mut tape)));
+    let glyph_mapping = ok!(ok!(
+        file[0].take_given::<_, GlyphMapping>(&mut tape, (&font_header, &maximum_profile))
+    ));
+    let _ = ok!(ok!(
+        file[0].take_given::<_, GlyphData>(&mut tape, &glyph_mapping)
+    ));
+    let table = ok!(ok!(file[0].take::<_, GlyphDefinition>(&mut tape)));
+    assert!(table.glyph_class.is_some());
+    assert!(file[0].position::<_, FontHeader>(&mut tape).is_err());
+}
+
+fn encode() -> Vec<u8> {
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+
+    fn compress(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+        ok!(encoder.write_all(data));
+        ok!(encoder.finish())
+    }
+
+    fn pad(data: &mut Vec<u8>) {
+        data.resize((data.len() + 3) & !3, 0);
+    }
+
+    let mut data = vec![];
+    ok!(setup!(OpenSans).read_to_end(&mut data));
+    let offsets = ok!(Offsets::read(&mut Cursor::new(&data)));
+    let count = offsets.records.len();
+    let mut tables = vec![];
+    let mut directory = vec![];
+    let mut offset = 44 + 20 * count;
+    for record in offsets.records.iter() {
+        let table = &data[record.offset as usize..][..record.size as usize];
+        let compressed = compress(table);
+        let table = if compressed.len() < table.len() {
+            compressed
+        } else {
+            table.to_vec()
+        };
+        ok!(directory.give(&record.tag));
+        ok!(directory.give(&(offset as u32)));
+        ok!(directory.give(&(table.len() as u32)));
+        ok!(directory.give(&record.size));
+        ok!(directory.give(&record.checksum));
+        tables.extend(table);
+        pad(&mut tables);
+        offset = 44 + 20 * count + tables.len();
+    }
+    let metadata = compress(METADATA.as_bytes());
+    let metadata_offset = offset;
+    let private_offset = (metadata_offset + metadata.len() + 3) & !3;
+    let size = private_offset + PRIVATE.len();
+
+    let mut tape = vec![];
+    ok!(tape.give(b"wOFF"));
+    ok!(tape.give(&offsets.header.version));
+    ok!(tape.give(&(size as u32)));
+    ok!(tape.give(&(count as u16)));
+    ok!(tape.give(&0u16));
+    ok!(tape.give(&(data.len() as u32)));
+    ok!(tape.give(&1u16));
+    ok!(tape.give(&0u16));
+    ok!(tape.give(&(metadata_offset as u32)));
+    ok!(tape.give(&(metadata.len() as u32)));
+    ok!(tape.give(&(METADATA.len() as u32)));
+    ok!(tape.give(&(private_offset as u32)));
+    ok!(tape.give(&(PRIVATE.len() as u32)));
+    tape.extend(directory);
+    tape.extend(tables);
+    tape.extend(metadata);
+    pad(&mut tape);
+    tape.extend(PRIVATE);
+    tape
+}
@@ -0,0 +1,483 @@
+#[macro_use]
+mod support;
+
+use std::io::{Cursor, Read, Write};
+
+use opentype::tape::Write as _;
+use opentype::truetype::tables::glyph_data::{Description, GlyphData};
+use opentype::truetype::tables::offsets::Offsets;
+use opentype::truetype::tables::{
+    FontHeader, GlyphMapping, HorizontalHeader, HorizontalMetrics, MaximumProfile,
+};
+use opentype::truetype::Tag;
+use opentype::value::Read as _;
+use opentype::woff2;
+use opentype::File;
+
+const METADATA: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><metadata version=\"1.0\"/>";
+const PRIVATE: &[u8] = b"private";
+
+mod open_sans {
+    use super::*;
+
+    #[test]
+    fn transform() {
+        let data = read(setup!(OpenSans));
+        let mut original = Cursor::new(&data);
+        let mut tape = Cursor::new(encode(&data, 1, true));
+        let file = ok!(File::read(&mut tape));
+        let original = &mut original;
+        let original_file = ok!(File::read(original));
+        let (glyph_data, metrics) = glyphs(&file[0], &mut tape);
+        let (original_glyph_data, original_metrics) = glyphs(&original_file[0], original);
+        assert_eq!(glyph_data.len(), original_glyph_data.len());
+        for (glyph, original_glyph) in glyph_data.iter().zip(original_glyph_data.iter()) {
+            let (glyph, original_glyph) = match (glyph, original_glyph) {
+                (Some(glyph), Some(original_glyph)) => (glyph, original_glyph),
+                (None, None) => continue,
+                _ => unreachable!(),
+            };
+            assert_eq!(glyph.contour_count, original_glyph.contour_count);
+            assert_eq!(
+                (glyph.min_x, glyph.min_y, glyph.max_x, glyph.max_y),
+                (
+                    original_glyph.min_x,
+                    original_glyph.min_y,
+                    original_glyph.max_x,
+                    original_glyph.max_y,
+                ),
+            );
+            match (&glyph.description, &original_glyph.description) {
+                (Description::Simple(value), Description::Simple(original_value)) => {
+                    assert_eq!(value.end_points, original_value.end_points);
+                    assert_eq!(value.instructions, original_value.instructions);
+                    assert_eq!(points(value), points(original_value));
+                }
+                (Description::Composite(value), Description::Composite(original_value)) => {
+                    assert_eq!(value.components.len(), original_value.components.len());
+                    assert_eq!(value.instructions, original_value.instructions);
+                }
+                _ => unreachable!(),
+            }
+        }
+        for i in 0..glyph_data.len() {
+            assert_eq!(metrics.get(i as u16), original_metrics.get(i as u16));
+        }
+    }
+}
+
+mod source_serif {
+    use opentype::tables::{GlyphPositioning, GlyphSubstitution};
+
+    use super::*;
+
+    #[test]
+    fn collection() {
+        let data = read(setup!(SourceSerifPro));
+        let mut tape = Cursor::new(encode(&data, 2, false));
+        let file: woff2::File = ok!(woff2::File::read(&mut tape));
+        assert_eq!(ok!(file.collection.as_ref()).font_count, 2);
+        tape.set_position(0);
+        let file = ok!(File::read(&mut tape));
+        assert_eq!(file.len(), 2);
+        for font in file.iter() {
+            let table = ok!(ok!(font.take::<_, GlyphSubstitution>(&mut tape)));
+            assert_eq!(table.lookups.records.len(), 19);
+        }
+    }
+
+    #[test]
+    fn file() {
+        let data = read(setup!(SourceSerifPro));
+        let mut tape = Cursor::new(encode(&data, 1, false));
+        let file: woff2::File = ok!(woff2::File::read(&mut tape));
+        assert!(file.collection.is_none());
+        assert_eq!(ok!(file.metadata.as_ref()), METADATA);
+        assert_eq!(ok!(file.private.as_ref()), PRIVATE);
+        let offsets = ok!(Offsets::read(&mut Cursor::new(&data)));
+        for (record, table) in file.records.iter().zip(&file.tables) {
+            let original = ok!(offsets.iter().find(|other| other.tag == record.tag));
+            let original = &data[original.offset as usize..][..original.size as usize];
+            assert_eq!(&table[..], original);
+        }
+    }
+
+    #[test]
+    fn font() {
+        let data = read(setup!(SourceSerifPro));
+        let mut tape = Cursor::new(encode(&data, 1, false));
+        let file = ok!(File::read(&mut tape));
+        assert_eq!(file.len(), 1);
+        let table = ok!(ok!(file[0].take::<_, FontHeader>(&mut tape)));
+        assert_eq!(table.units_per_em, 1000);
+        let table = ok!(ok!(file[0].take::<_, GlyphSubstitution>(&mut tape)));
+        assert_eq!(table.lookups.records.len(), 19);
+        let table = ok!(ok!(file[0].take::<_, GlyphPositioning>(&mut tape)));
+        assert_eq!(table.lookups.records.len(), 1);
+    }
+}
+
+fn glyphs<T: opentype::tape::Read>(
+    font: &opentype::Font,
+    tape: &mut T,
+) -> (GlyphData, HorizontalMetrics) {
+    let font_header = ok!(ok!(font.take::<_, FontHeader>(tape)));
+    let horizontal_header = ok!(ok!(font.take::<_, HorizontalHeader>(tape)));
+    let maximum_profile = ok!(ok!(font.take::<_, MaximumProfile>(tape)));
+    let glyph_mapping = ok!(ok!(
+        font.take_given::<_, GlyphMapping>(tape, (&font_header, &maximum_profile))
+    ));
+    let glyph_data = ok!(ok!(font.take_given::<_, GlyphData>(tape, &glyph_mapping)));
+    let metrics = ok!(ok!(font.take_given::<_, HorizontalMetrics>(
+        tape,
+        (&horizontal_header, &maximum_profile),
+    )));
+    (glyph_data, metrics)
+}
+
+fn points(
+    description: &opentype::truetype::tables::glyph_data::SimpleDescription,
+) -> Vec<(bool, i32, i32)> {
+    let (mut x, mut y) = (0, 0);
+    description
+        .flags
+        .iter()
+        .zip(description.x.iter().zip(description.y.iter()))
+        .map(|(flags, (dx, dy))| {
+            x += *dx as i32;
+            y += *dy as i32;
+            (flags.is_on_curve(), x, y)
+        })
+        .collect()
+}
+
+fn read(mut file: std::fs::File) -> Vec<u8> {
+    let mut data = vec![];
+    ok!(file.read_to_end(&mut data));
+    data
+}
+
+fn encode(data: &[u8], font_count: usize, transform: bool) -> Vec<u8> {
+    fn compress(data: &[u8]) -> Vec<u8> {
+        let mut writer = brotli::CompressorWriter::new(vec![], 4096, 11, 22);
+        ok!(writer.write_all(data));
+        writer.into_inner()
+    }
+
+    fn give_u16(tape: &mut Vec<u8>, value: u16) {
+        if value < 253 {
+            ok!(tape.give(&(value as u8)));
+        } else {
+            ok!(tape.give(&253u8));
+            ok!(tape.give(&value));
+        }
+    }
+
+    fn give_u32(tape: &mut Vec<u8>, value: u32) {
+        let mut bytes = vec![(value & 0x7f) as u8];
+        let mut value = value >> 7;
+        while value > 0 {
+            bytes.push((value & 0x7f) as u8 | 0x80);
+            value >>= 7;
+        }
+        bytes.reverse();
+        tape.extend(bytes);
+    }
+
+    fn pad(data: &mut Vec<u8>) {
+        data.resize((data.len() + 3) & !3, 0);
+    }
+
+    let offsets = ok!(Offsets::read(&mut Cursor::new(data)));
+    let tables = offsets
+        .records
+        .iter()
+        .map(|record| &data[record.offset as usize..][..record.size as usize])
+        .collect::<Vec<_>>();
+    let find = |tag: &[u8; 4]| {
+        offsets
+            .records
+            .iter()
+            .position(|record| record.tag == Tag(*tag))
+    };
+    let (glyph_data, glyph_metrics) = if transform {
+        let (glyph_data, minima) = transform_glyphs(data);
+        let metrics = transform_metrics(data, &minima, tables[ok!(find(b"hmtx"))]);
+        (Some(glyph_data), metrics)
+    } else {
+        (None, None)
+    };
+
+    let mut directory = vec![];
+    let mut stream = vec![];
+    for (record, table) in offsets.records.iter().zip(tables.iter()) {
+        let (version, transformed) = match &record.tag.0 {
+            b"glyf" => match glyph_data {
+                Some(ref value) => (0, Some(&value[..])),
+                _ => (3, None),
+            },
+            b"loca" => match glyph_data {
+                Some(_) => (0, Some(&[][..])),
+                _ => (3, None),
+            },
+            b"hmtx" => match glyph_metrics {
+                Some(ref value) => (1, Some(&value[..])),
+                _ => (0, None),
+            },
+            _ => (0, None),
+        };
+        ok!(directory.give(&(0x3fu8 | version << 6)));
+        ok!(directory.give(&record.tag));
+        give_u32(&mut directory, record.size);
+        match transformed {
+            Some(value) => {
+                give_u32(&mut directory, value.len() as u32);
+                stream.extend(value);
+            }
+            _ => stream.extend(*table),
+        }
+    }
+    if font_count > 1 {
+        ok!(directory.give(&0x00010000u32));
+        give_u16(&mut directory, font_count as u16);
+        for _ in 0..font_count {
+            give_u16(&mut directory, tables.len() as u16);
+            ok!(directory.give(&offsets.header.version));
+            for i in 0..tables.len() {
+                give_u16(&mut directory, i as u16);
+            }
+        }
+    }
+    let stream = compress(&stream);
+    let metadata = compress(METADATA.as_bytes());
+    let mut metadata_offset = 48 + directory.len() + stream.len();
+    metadata_offset = (metadata_offset + 3) & !3;
+    let private_offset = (metadata_offset + metadata.len() + 3) & !3;
+    let size = private_offset + PRIVATE.len();
+
+    let mut tape = vec![];
+    ok!(tape.give(b"wOF2"));
+    if font_count > 1 {
+        ok!(tape.give(b"ttcf"));
+    } else {
+        ok!(tape.give(&offsets.header.version));
+    }
+    ok!(tape.give(&(size as u32)));
+    ok!(tape.give(&(tables.len() as u16)));
+    ok!(tape.give(&0u16));
+    ok!(tape.give(&(data.len() as u32)));
+    ok!(tape.give(&(stream.len() as u32)));
+    ok!(tape.give(&1u16));
+    ok!(tape.give(&0u16));
+    ok!(tape.give(&(metadata_offset as u32)));
+    ok!(tape.give(&(metadata.len() as u32)));
+    ok!(tape.give(&(METADATA.len() as u32)));
+    ok!(tape.give(&(private_offset as u32)));
+    ok!(tape.give(&(PRIVATE.len() as u32)));
+    tape.extend(directory);
+    tape.extend(stream);
+    pad(&mut tape);
+    tape.extend(metadata);
+    pad(&mut tape);
+    tape.extend(PRIVATE);
+    tape
+}
+
+fn transform_glyphs(data: &[u8]) -> (Vec<u8>, Vec<i16>) {
+    fn give_u16(tape: &mut Vec<u8>, value: u16) {
+        if value < 253 {
+            ok!(tape.give(&(value as u8)));
+        } else {
+            ok!(tape.give(&253u8));
+            ok!(tape.give(&value));
+        }
+    }
+
+    fn give_triplet(flags: &mut Vec<u8>, glyphs: &mut Vec<u8>, on_curve: bool, dx: i32, dy: i32) {
+        let on_curve = if on_curve { 0 } else { 0x80 };
+        let (x, y) = (dx.unsigned_abs(), dy.unsigned_abs());
+        if dx == 0 && y < 1280 {
+            flags.push(on_curve | ((y >> 8) << 1) as u8 | (dy >= 0) as u8);
+            glyphs.push(y as u8);
+        } else if dy == 0 && x < 1280 {
+            flags.push(on_curve | (10 + ((x >> 8) << 1)) as u8 | (dx >= 0) as u8);
+            glyphs.push(x as u8);
+        } else {
+            flags.push(on_curve | 124 | (dx >= 0) as u8 | ((dy >= 0) as u8) << 1);
+            ok!(glyphs.give(&(x as u16)));
+            ok!(glyphs.give(&(y as u16)));
+        }
+    }
+
+    let mut tape = Cursor::new(data);
+    let file = ok!(File::read(&mut tape));
+    let (glyph_data, _) = glyphs(&file[0], &mut tape);
+    let font_header = ok!(ok!(file[0].take::<_, FontHeader>(&mut tape)));
+    let glyph_count = glyph_data.len();
+    let bitmap_size = ((glyph_count + 31) >> 5) << 2;
+
+    let mut contours = vec![];
+    let mut points = vec![];
+    let mut flags = vec![];
+    let mut glyphs = vec![];
+    let mut composites = vec![];
+    let mut bitmap = vec![0u8; bitmap_size];
+    let mut boundings = vec![];
+    let mut instructions = vec![];
+    let mut minima = vec![];
+    for (i, glyph) in glyph_data.iter().enumerate() {
+        let glyph = match glyph {
+            Some(glyph) => glyph,
+            _ => {
+                ok!(contours.give(&0i16));
+                minima.push(0);
+                continue;
+            }
+        };
+        minima.push(glyph.min_x);
+        ok!(contours.give(&glyph.contour_count));
+        let bounding = [glyph.min_x, glyph.min_y, glyph.max_x, glyph.max_y];
+        match &glyph.description {
+            Description::Simple(description) => {
+                let mut previous = 0;
+                for end_point in description.end_points.iter() {
+                    give_u16(&mut points, end_point + 1 - previous);
+                    previous = end_point + 1;
+                }
+                let values = self::points(description);
+                let (mut x, mut y) = (0, 0);
+                for (on_curve, x_next, y_next) in values.iter() {
+                    give_triplet(&mut flags, &mut glyphs, *on_curve, x_next - x, y_next - y);
+                    (x, y) = (*x_next, *y_next);
+                }
+                give_u16(&mut glyphs, description.instruction_size);
+                instructions.extend(&description.instructions);
+                let computed = [
+                    ok!(values.iter().map(|value| value.1).min()),
+                    ok!(values.iter().map(|value| value.2).min()),
+                    ok!(values.iter().map(|value| value.1).max()),
+                    ok!(values.iter().map(|value| value.2).max()),
+                ];
+                if computed != bounding.map(|value| value as i32) {
+                    bitmap[i >> 3] |= 0x80 >> (i & 7);
+                    ok!(boundings.give(&bounding[..]));
+                }
+            }
+            Description::Composite(description) => {
+                bitmap[i >> 3] |= 0x80 >> (i & 7);
+                ok!(boundings.give(&bounding[..]));
+                for component in description.components.iter() {
+                    let flags = component.flags;
+                    ok!(composites.give(&flags.0));
+                    ok!(composites.give(&component.glyph_id));
+                    let mut arguments = vec![];
+                    use opentype::truetype::tables::glyph_data::Arguments;
+                    match component.arguments {
+                        Arguments::Offsets(x, y) => {
+                            if flags.are_arguments_words() {
+                                ok!(arguments.give(&x));
+                                ok!(arguments.give(&y));
+                            } else {
+                                ok!(arguments.give(&(x as i8)));
+                                ok!(arguments.give(&(y as i8)));
+                            }
+                        }
+                        Arguments::Indices(x, y) => {
+                            if flags.are_arguments_words() {
+                                ok!(arguments.give(&x));
+                                ok!(arguments.give(&y));
+                            } else {
+                                ok!(arguments.give(&(x as u8)));
+                                ok!(arguments.give(&(y as u8)));
+                            }
+                        }
+                    }
+                    composites.extend(arguments);
+                    use opentype::truetype::tables::glyph_data::Options;
+                    match component.options {
+                        Options::None => {}
+                        Options::Scalar(value) => ok!(composites.give(&value)),
+                        Options::Vector(x, y) => {
+                            ok!(composites.give(&x));
+                            ok!(composites.give(&y));
+                        }
+                        Options::Matrix(a, b, c, d) => {
+                            ok!(composites.give(&a));
+                            ok!(composites.give(&b));
+                            ok!(composites.give(&c));
+                            ok!(composites.give(&d));
+                        }
+                    }
+                }
+                if description.instruction_size > 0 {
+                    give_u16(&mut glyphs, description.instruction_size);
+                    instructions.extend(&description.instructions);
+                }
+            }
+        }
+    }
+    boundings.splice(0..0, bitmap);
+
+    let mut tape = vec![];
+    ok!(tape.give(&0u16));
+    ok!(tape.give(&0u16));
+    ok!(tape.give(&(glyph_count as u16)));
+    ok!(tape.give(&(font_header.glyph_mapping_format as u16)));
+    for stream in [
+        &contours,
+        &points,
+        &flags,
+        &glyphs,
+        &composites,
+        &boundings,
+        &instructions,
+    ] {
+        ok!(tape.give(&(stream.len() as u32)));
+    }
+    for stream in [
+        contours,
+        points,
+        flags,
+        glyphs,
+        composites,
+        boundings,
+        instructions,
+    ] {
+        tape.extend(stream);
+    }
+    (tape, minima)
+}
+
+fn transform_metrics(data: &[u8], minima: &[i16], table: &[u8]) -> Option<Vec<u8>> {
+    let mut tape = Cursor::new(data);
+    let file = ok!(File::read(&mut tape));
+    let (_, metrics) = glyphs(&file[0], &mut tape);
+    let count = metrics.records.len();
+    let proportional = metrics
+        .records
+        .iter()
+        .zip(minima)
+        .all(|(record, minimum)| record.left_side_bearing == *minimum);
+    let monospaced = metrics
+        .left_side_bearings
+        .iter()
+        .zip(&minima[count..])
+        .all(|(bearing, minimum)| bearing == minimum);
+    if !proportional && !monospaced {
+        return None;
+    }
+    let mut tape = vec![(proportional as u8) | (monospaced as u8) << 1];
+    for record in metrics.records.iter() {
+        ok!(tape.give(&record.advance_width));
+    }
+    if !proportional {
+        for record in metrics.records.iter() {
+            ok!(tape.give(&record.left_side_bearing));
+        }
+    }
+    if !monospaced {
+        ok!(tape.give(&metrics.left_side_bearings[..]));
+    }
+    assert!(tape.len() < table.len());
+    Some(tape)
+}